@@ -0,0 +1,842 @@
+use crate::{
+    backend::{Request, REDACTED},
+    config::Target,
+    recording,
+};
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Utc};
+use flate2::{write::GzEncoder, Compression};
+use reqwest::{
+    header::{self, HeaderMap, HeaderValue},
+    RequestBuilder, Response, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use std::{
+    fmt,
+    io::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    time::Duration,
+};
+
+#[derive(Debug, Deserialize)]
+struct DevtoError {
+    error: String,
+}
+
+/// An unsuccessful response from a Forem api
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "Dev.to error {:#?} {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Content and metadata sent when creating or updating an article
+#[derive(Debug, Default, Serialize)]
+pub struct ArticleInput<'a> {
+    pub body_markdown: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub main_image_alt: Option<&'a str>,
+}
+
+/// deserialize an article id, numeric for Forem instances and textual for other backends
+pub fn id<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u64),
+        Text(String),
+    }
+    Ok(match Id::deserialize(deserializer)? {
+        Id::Number(n) => n.to_string(),
+        Id::Text(s) => s,
+    })
+}
+
+/// deserialize tags, listed for an account's own articles and comma separated elsewhere
+fn tags<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Tags {
+        List(Vec<String>),
+        Text(String),
+    }
+    Ok(match Option::<Tags>::deserialize(deserializer)? {
+        None => Vec::new(),
+        Some(Tags::List(tags)) => tags,
+        Some(Tags::Text(tags)) => tags
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+    })
+}
+
+/// The author of an article listed publicly
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct User {
+    pub name: String,
+    pub username: String,
+}
+
+/// A comment on an article, with its replies
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Comment {
+    pub id_code: String,
+    pub created_at: Option<String>,
+    pub body_html: String,
+    pub user: User,
+    pub children: Vec<Comment>,
+}
+
+/// The profile of an account
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct UserProfile {
+    pub name: String,
+    pub username: String,
+    pub summary: Option<String>,
+    pub website_url: Option<String>,
+    pub location: Option<String>,
+    pub joined_at: Option<String>,
+}
+
+/// Profile fields changed when updating a profile, those left out staying as they are
+#[derive(Debug, Default, Serialize)]
+pub struct UserProfileInput<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website_url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<&'a str>,
+}
+
+/// A webhook notifying an endpoint of events on the account's articles
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Webhook {
+    #[serde(deserialize_with = "id")]
+    pub id: String,
+    pub target_url: String,
+    pub source: String,
+    pub events: Vec<String>,
+    pub created_at: Option<String>,
+}
+
+/// An article as represented by the dev.to api
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Article {
+    #[serde(deserialize_with = "id")]
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub published: bool,
+    pub published_at: Option<String>,
+    pub published_timestamp: Option<String>,
+    pub edited_at: Option<String>,
+    #[serde(deserialize_with = "tags")]
+    pub tag_list: Vec<String>,
+    pub slug: String,
+    pub url: String,
+    pub canonical_url: Option<String>,
+    pub cover_image: Option<String>,
+    pub body_markdown: String,
+    pub page_views_count: i64,
+    pub public_reactions_count: i64,
+    pub comments_count: i64,
+    pub user: User,
+}
+
+impl Article {
+    /// when this article last changed remotely, if known
+    pub fn changed_at(&self) -> Option<DateTime<Utc>> {
+        [
+            &self.edited_at,
+            &self.published_timestamp,
+            &self.published_at,
+        ]
+        .iter()
+        .filter_map(|date| date.as_deref())
+        .filter_map(|date| DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| date.with_timezone(&Utc))
+        .max()
+    }
+
+    /// a name for local copies of this article, its slug when it has one, made safe to use
+    /// as a file name on Windows too
+    pub fn file_stem(&self) -> String {
+        let name = if self.slug.is_empty() {
+            &self.id
+        } else {
+            &self.slug
+        };
+        let name = name
+            .replace(['<', '>', ':', '"', '/', '\\', '|', '?', '*'], "-")
+            .trim_end_matches(['.', ' '])
+            .to_string();
+        // device names can't name files on Windows, whatever their extension
+        let device = name
+            .split('.')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let numbered = (device.starts_with("com") || device.starts_with("lpt"))
+            && device.len() == 4
+            && device.ends_with(|c: char| c.is_ascii_digit());
+        if matches!(device.as_str(), "con" | "prn" | "aux" | "nul") || numbered {
+            format!("{}-", name)
+        } else {
+            name
+        }
+    }
+}
+
+/// identifies devtogo to the services it talks to
+pub const USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (+",
+    env!("CARGO_PKG_HOMEPAGE"),
+    ")"
+);
+
+/// Idle connections kept open to each host, i.e. for many images uploaded from one server
+const IDLE_CONNECTIONS: usize = 16;
+
+/// an http client identifying itself as devtogo. Every call shares one client, so connections
+/// are pooled and kept alive across a run, and multiplexed over HTTP/2 where servers offer it.
+/// A client which can't be built, i.e. without a tls backend, fails every call rather than
+/// falling back on one without these settings
+pub fn http() -> anyhow::Result<reqwest::Client> {
+    // reqwest's errors can't be cloned, so a failure is kept as its message
+    static HTTP: OnceLock<Result<reqwest::Client, String>> = OnceLock::new();
+    HTTP.get_or_init(|| {
+        reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .pool_max_idle_per_host(IDLE_CONNECTIONS)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .tcp_keepalive(Duration::from_secs(60))
+            .http2_adaptive_window(true)
+            .http2_keep_alive_interval(Duration::from_secs(30))
+            .http2_keep_alive_while_idle(true)
+            .build()
+            .map_err(|err| err.to_string())
+    })
+    .clone()
+    .map_err(|err| anyhow!("couldn't set up an http client: {}", err))
+}
+
+/// published articles, or an article, from a public endpoint of a Forem target's api, i.e.
+/// articles?tag=rust, which need no authentication
+pub async fn public<T: DeserializeOwned>(
+    target: &Target,
+    path: &str,
+) -> anyhow::Result<T> {
+    let resp =
+        recording::send(http()?.get(format!("{}/{}", target.api_url(), path).as_str())).await?;
+    parse(resp).await
+}
+
+/// How requests are authenticated with a Forem instance
+#[derive(Debug, Clone)]
+pub enum Auth {
+    /// a long lived key generated in account settings
+    ApiKey(String),
+    /// an access token obtained through OAuth
+    Bearer(String),
+}
+
+impl Auth {
+    fn apply(
+        &self,
+        req: RequestBuilder,
+    ) -> RequestBuilder {
+        match self {
+            Auth::ApiKey(key) => req.header("api-key", key.as_str()),
+            Auth::Bearer(token) => req.bearer_auth(token),
+        }
+    }
+}
+
+/// The outcome of an attempt to create an article
+enum Attempt {
+    /// the response to a create request
+    Sent(Response),
+    /// an article created by an earlier attempt whose response was lost
+    Found(Box<Article>),
+}
+
+/// Size of request bodies above which they are gzipped for targets accepting compressed requests
+const COMPRESS_OVER: usize = 32 * 1024;
+
+/// A json request body, gzipped when it's worth it
+#[derive(Clone)]
+struct Json {
+    bytes: Vec<u8>,
+    gzipped: bool,
+}
+
+impl Json {
+    fn new(
+        input: &impl Serialize,
+        gzip: bool,
+    ) -> anyhow::Result<Self> {
+        let bytes = serde_json::to_vec(input)?;
+        if !gzip || bytes.len() < COMPRESS_OVER {
+            return Ok(Json {
+                bytes,
+                gzipped: false,
+            });
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&bytes)?;
+        Ok(Json {
+            bytes: encoder.finish()?,
+            gzipped: true,
+        })
+    }
+
+    /// a request sending this body, with headers describing it
+    fn attach(
+        &self,
+        req: RequestBuilder,
+    ) -> RequestBuilder {
+        let req = req.header("content-type", "application/json");
+        let req = if self.gzipped {
+            req.header("content-encoding", "gzip")
+        } else {
+            req
+        };
+        req.body(self.bytes.clone())
+    }
+}
+
+/// A thin Forem api client bound to a single account
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    auth: Auth,
+    /// headers sent with every request, besides authentication
+    headers: HeaderMap,
+    /// requests made again after failing, across clones
+    retries: Arc<AtomicUsize>,
+    /// whether large request bodies are gzipped
+    gzip: bool,
+}
+
+impl Client {
+    /// a client for a Forem target's api
+    pub fn new(
+        target: &Target,
+        auth: Auth,
+    ) -> anyhow::Result<Self> {
+        let mut headers = HeaderMap::new();
+        if let Some(version) = target.api_version() {
+            headers.insert(
+                header::ACCEPT,
+                HeaderValue::from_str(&format!("application/vnd.forem.api-v{}+json", version))?,
+            );
+        }
+        Ok(Client {
+            http: http()?,
+            base_url: target.api_url(),
+            auth,
+            headers,
+            retries: Arc::default(),
+            gzip: target.gzip_requests,
+        })
+    }
+
+    /// number of requests made again after failing
+    pub fn retries(&self) -> usize {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// a request authenticated as the account, with the headers sent with every request
+    fn apply(
+        &self,
+        req: RequestBuilder,
+    ) -> RequestBuilder {
+        self.auth.apply(req.headers(self.headers.clone()))
+    }
+
+    /// list all articles, published or not, for the current account
+    pub async fn articles(&self) -> anyhow::Result<Vec<Article>> {
+        let resp = recording::send(
+            self.apply(
+                self.http
+                    .get(format!("{}/articles/me/all?per_page=1000", self.base_url).as_str()),
+            ),
+        )
+        .await?;
+
+        if !resp.status().is_success() {
+            bail!("Dev.to error {:#?} - bad or invalid API Key", resp.status());
+        }
+        Ok(resp.json().await?)
+    }
+
+    /// rate limit headers returned for a cheap authenticated request
+    pub async fn limits(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let resp = recording::send(
+            self.apply(
+                self.http
+                    .get(format!("{}/users/me", self.base_url).as_str()),
+            ),
+        )
+        .await?;
+        if resp.status() == StatusCode::UNAUTHORIZED {
+            bail!("Dev.to error {:#?} - bad or invalid API Key", resp.status());
+        }
+        Ok(resp
+            .headers()
+            .iter()
+            .filter(|(name, _)| name.as_str().contains("ratelimit") || name == &header::RETRY_AFTER)
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    value.to_str().unwrap_or_default().to_string(),
+                )
+            })
+            .collect())
+    }
+
+    /// the request creating an article, or updating the one with an id, sends
+    pub fn request(
+        &self,
+        id: Option<&str>,
+        input: &ArticleInput<'_>,
+    ) -> anyhow::Result<Request> {
+        let (method, url) = match id {
+            Some(id) => ("PUT", format!("{}/articles/{}", self.base_url, id)),
+            None => ("POST", format!("{}/articles", self.base_url)),
+        };
+        let mut headers = vec![("user-agent".to_string(), USER_AGENT.to_string())];
+        headers.extend(self.headers.iter().map(|(name, value)| {
+            (
+                name.to_string(),
+                value.to_str().unwrap_or_default().to_string(),
+            )
+        }));
+        headers.push(match self.auth {
+            Auth::ApiKey(_) => ("api-key".into(), REDACTED.into()),
+            Auth::Bearer(_) => ("authorization".into(), format!("Bearer {}", REDACTED)),
+        });
+        headers.push(("content-type".into(), "application/json".into()));
+        Ok(Request {
+            method,
+            url,
+            headers,
+            body: serde_json::to_value(input)?,
+        })
+    }
+
+    /// create a new article from markdown content
+    pub async fn create(
+        &self,
+        input: &ArticleInput<'_>,
+    ) -> anyhow::Result<Article> {
+        let client = self.clone();
+        let url = format!("{}/articles", self.base_url);
+        let body = Json::new(input, self.gzip)?;
+        let markdown = input.body_markdown.to_string();
+        let mut attempts = 0;
+        let attempt = again::retry(move || {
+            let retrying = attempts > 0;
+            attempts += 1;
+            let client = client.clone();
+            let url = url.clone();
+            let body = body.clone();
+            let markdown = markdown.clone();
+            async move {
+                if retrying {
+                    client.retries.fetch_add(1, Ordering::Relaxed);
+                    // a request which timed out may have created the article anyway
+                    if let Some(article) = client.existing(&markdown).await? {
+                        return Ok(Attempt::Found(Box::new(article)));
+                    }
+                }
+                recording::send(body.attach(client.apply(client.http.post(url.as_str()))))
+                    .await
+                    .map(Attempt::Sent)
+            }
+        })
+        .await?;
+        match attempt {
+            Attempt::Sent(resp) => parse(resp).await,
+            Attempt::Found(article) => Ok(*article),
+        }
+    }
+
+    /// reply to a comment, where the instance's api permits posting comments
+    pub async fn reply(
+        &self,
+        parent: &str,
+        body_markdown: &str,
+    ) -> anyhow::Result<Comment> {
+        let resp = recording::send(
+            self.apply(
+                self.http
+                    .post(format!("{}/comments", self.base_url).as_str()),
+            )
+            .json(&serde_json::json!({
+                "comment": {
+                    "body_markdown": body_markdown,
+                    "parent_id_code": parent,
+                }
+            })),
+        )
+        .await?;
+        supported(&resp, "posting comments", "Reply on the web instead")?;
+        parse(resp).await
+    }
+
+    /// a resource the current account can see, i.e. users/me
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> anyhow::Result<T> {
+        let resp = recording::send(
+            self.apply(
+                self.http
+                    .get(format!("{}/{}", self.base_url, path).as_str()),
+            ),
+        )
+        .await?;
+        parse(resp).await
+    }
+
+    /// the current account's profile
+    pub async fn profile(&self) -> anyhow::Result<UserProfile> {
+        self.get("users/me").await
+    }
+
+    /// change fields of the current account's profile, where the instance's api permits it
+    pub async fn update_profile(
+        &self,
+        input: &UserProfileInput<'_>,
+    ) -> anyhow::Result<UserProfile> {
+        let resp = recording::send(
+            self.apply(
+                self.http
+                    .put(format!("{}/users/me", self.base_url).as_str()),
+            )
+            .json(&serde_json::json!({ "user": input })),
+        )
+        .await?;
+        supported(
+            &resp,
+            "updating profiles",
+            "Update it in your settings on the web instead",
+        )?;
+        parse(resp).await
+    }
+
+    /// every page of a list the current account can see, i.e. readinglist or followers/users
+    pub async fn all<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> anyhow::Result<Vec<T>> {
+        const PER_PAGE: usize = 1000;
+        let mut items = Vec::new();
+        for page in 1.. {
+            let resp = recording::send(
+                self.apply(
+                    self.http.get(
+                        format!(
+                            "{}/{}?per_page={}&page={}",
+                            self.base_url, path, PER_PAGE, page
+                        )
+                        .as_str(),
+                    ),
+                ),
+            )
+            .await?;
+            let batch: Vec<T> = parse(resp).await?;
+            let last = batch.len() < PER_PAGE;
+            items.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// webhooks registered for the current account
+    pub async fn webhooks(&self) -> anyhow::Result<Vec<Webhook>> {
+        let resp = recording::send(
+            self.apply(
+                self.http
+                    .get(format!("{}/webhooks", self.base_url).as_str()),
+            ),
+        )
+        .await?;
+        parse(resp).await
+    }
+
+    /// register an endpoint to be notified of events
+    pub async fn create_webhook(
+        &self,
+        target_url: &str,
+        source: &str,
+        events: &[String],
+    ) -> anyhow::Result<Webhook> {
+        let resp = recording::send(
+            self.apply(
+                self.http
+                    .post(format!("{}/webhooks", self.base_url).as_str()),
+            )
+            .json(&serde_json::json!({
+                "webhook_endpoint": {
+                    "target_url": target_url,
+                    "source": source,
+                    "events": events,
+                }
+            })),
+        )
+        .await?;
+        parse(resp).await
+    }
+
+    /// stop notifying an endpoint registered as a webhook
+    pub async fn delete_webhook(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<()> {
+        let resp = recording::send(
+            self.apply(
+                self.http
+                    .delete(format!("{}/webhooks/{}", self.base_url, id).as_str()),
+            ),
+        )
+        .await?;
+        if resp.status().is_success() {
+            return Ok(());
+        }
+        parse::<serde_json::Value>(resp).await.map(|_| ())
+    }
+
+    /// an article with exactly this markdown, if there is one
+    async fn existing(
+        &self,
+        markdown: &str,
+    ) -> anyhow::Result<Option<Article>> {
+        Ok(self
+            .articles()
+            .await?
+            .into_iter()
+            .find(|article| article.body_markdown == markdown))
+    }
+
+    /// replace the content of an existing article
+    pub async fn update(
+        &self,
+        id: &str,
+        input: &ArticleInput<'_>,
+    ) -> anyhow::Result<Article> {
+        let client = self.clone();
+        let url = format!("{}/articles/{}", self.base_url, id);
+        let body = Json::new(input, self.gzip)?;
+        let retries = self.retries.clone();
+        let mut attempts = 0;
+        let resp = again::retry(move || {
+            if attempts > 0 {
+                retries.fetch_add(1, Ordering::Relaxed);
+            }
+            attempts += 1;
+            recording::send(body.attach(client.apply(client.http.put(url.as_str()))))
+        })
+        .await?;
+        parse(resp).await
+    }
+}
+
+/// fail when an instance's api doesn't offer an endpoint, as some Forem versions leave
+/// endpoints out
+fn supported(
+    resp: &Response,
+    what: &str,
+    instead: &str,
+) -> anyhow::Result<()> {
+    if matches!(
+        resp.status(),
+        StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED
+    ) {
+        bail!(
+            "this instance's api doesn't permit {}.\n  ▶ {}",
+            what,
+            instead
+        );
+    }
+    Ok(())
+}
+
+async fn parse<T: DeserializeOwned>(resp: Response) -> anyhow::Result<T> {
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().await?;
+        let message = serde_json::from_str::<DevtoError>(&text)
+            .map(|e| e.error)
+            .unwrap_or(text);
+        return Err(ApiError { status, message }.into());
+    }
+    Ok(resp.json().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_agent_names_devtogo() {
+        assert!(USER_AGENT.starts_with(concat!("devtogo/", env!("CARGO_PKG_VERSION"))));
+    }
+
+    #[test]
+    fn profile_input_leaves_out_unchanged_fields() -> anyhow::Result<()> {
+        let input = UserProfileInput {
+            summary: Some("Writes about Rust"),
+            ..UserProfileInput::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&input)?,
+            r#"{"summary":"Writes about Rust"}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn json_gzips_large_bodies() -> anyhow::Result<()> {
+        let input = ArticleInput {
+            body_markdown: &"a".repeat(COMPRESS_OVER),
+            ..ArticleInput::default()
+        };
+        let body = Json::new(&input, true)?;
+        assert!(body.gzipped);
+        let mut json = Vec::new();
+        std::io::Read::read_to_end(
+            &mut flate2::read::GzDecoder::new(&body.bytes[..]),
+            &mut json,
+        )?;
+        assert_eq!(json, serde_json::to_vec(&input)?);
+        assert!(!Json::new(&input, false)?.gzipped);
+        assert!(!Json::new(&ArticleInput::default(), true)?.gzipped);
+        Ok(())
+    }
+
+    #[test]
+    fn article_tolerates_partial_responses() -> anyhow::Result<()> {
+        let article: Article = serde_json::from_str(r#"{"id": 1, "title": "foo"}"#)?;
+        assert_eq!(article.id, "1");
+        assert_eq!(article.body_markdown, "");
+        Ok(())
+    }
+
+    #[test]
+    fn article_tags_may_be_listed_or_comma_separated() -> anyhow::Result<()> {
+        let article: Article = serde_json::from_str(r#"{"tag_list": ["rust", "cli"]}"#)?;
+        assert_eq!(article.tag_list, vec!["rust", "cli"]);
+        let article: Article = serde_json::from_str(r#"{"tag_list": "rust, cli"}"#)?;
+        assert_eq!(article.tag_list, vec!["rust", "cli"]);
+        let article: Article = serde_json::from_str(r#"{"tag_list": null}"#)?;
+        assert!(article.tag_list.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn article_file_stem_is_a_safe_file_name() {
+        let article = |slug: &str| Article {
+            id: "1".into(),
+            slug: slug.into(),
+            ..Article::default()
+        };
+        assert_eq!(article("").file_stem(), "1");
+        assert_eq!(article("what-is-c-4k2j").file_stem(), "what-is-c-4k2j");
+        assert_eq!(article("a:b?c.").file_stem(), "a-b-c");
+        assert_eq!(article("con").file_stem(), "con-");
+        assert_eq!(article("lpt1").file_stem(), "lpt1-");
+        assert_eq!(article("console").file_stem(), "console");
+    }
+
+    #[test]
+    fn article_changed_at_is_latest_timestamp() -> anyhow::Result<()> {
+        let article: Article = serde_json::from_str(
+            r#"{"published_at": "2021-01-01T00:00:00Z", "edited_at": "2021-02-01T00:00:00Z"}"#,
+        )?;
+        assert_eq!(
+            article.changed_at().map(|d| d.to_rfc3339()),
+            Some("2021-02-01T00:00:00+00:00".into())
+        );
+        assert_eq!(Article::default().changed_at(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn request_redacts_credentials() -> anyhow::Result<()> {
+        let client = Client::new(&Target::default(), Auth::ApiKey("s3cr3t".into()))?;
+        let input = ArticleInput {
+            body_markdown: "hi",
+            ..ArticleInput::default()
+        };
+        let request = client.request(Some("42"), &input)?;
+        assert_eq!(request.method, "PUT");
+        assert_eq!(request.url, "https://dev.to/api/articles/42");
+        assert!(request
+            .headers
+            .contains(&("api-key".into(), REDACTED.into())));
+        assert!(!request.to_string().contains("s3cr3t"));
+        assert!(request
+            .to_string()
+            .ends_with("\n\n{\n  \"body_markdown\": \"hi\"\n}"));
+        Ok(())
+    }
+
+    #[test]
+    fn article_input_omits_unset_fields() -> anyhow::Result<()> {
+        assert_eq!(
+            serde_json::to_string(&ArticleInput {
+                body_markdown: "hi",
+                ..ArticleInput::default()
+            })?,
+            r#"{"body_markdown":"hi"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&ArticleInput {
+                body_markdown: "hi",
+                collection_id: Some(1),
+                main_image_alt: Some("a cat"),
+            })?,
+            r#"{"body_markdown":"hi","collection_id":1,"main_image_alt":"a cat"}"#
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,1201 @@
+use crate::{
+    api::ArticleInput,
+    config::{Config, Kind},
+    dates::{self, Zone},
+    diagrams, footnotes, headings, images, math, mdx, notebook, snippets, state, typography, yaml,
+};
+use anyhow::anyhow;
+use colored::Colorize;
+use frontmatter::Yaml;
+use std::{
+    convert::TryFrom,
+    error::Error,
+    ffi::OsStr,
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use walkdir::WalkDir;
+
+#[derive(PartialEq, Debug)]
+pub enum PublishStatus {
+    Published,
+    Draft,
+}
+
+impl fmt::Display for PublishStatus {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let s = format!(
+            "{}",
+            match self {
+                PublishStatus::Published => "published".dimmed(),
+                PublishStatus::Draft => "draft".dimmed(),
+            }
+        );
+        f.write_str(&s)
+    }
+}
+
+/// Where an article is in an editorial workflow, set with `status` frontmatter
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum Status {
+    Idea,
+    InReview,
+    Approved,
+    Published,
+}
+
+impl Status {
+    /// every status, in workflow order
+    pub const ALL: [Status; 4] = [
+        Status::Idea,
+        Status::InReview,
+        Status::Approved,
+        Status::Published,
+    ];
+
+    /// name of this status as written in frontmatter
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Idea => "idea",
+            Status::InReview => "in-review",
+            Status::Approved => "approved",
+            Status::Published => "published",
+        }
+    }
+
+    /// true if articles in this status are pushed, as drafts until published
+    pub fn pushed(self) -> bool {
+        self >= Status::Approved
+    }
+}
+
+impl FromStr for Status {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        Status::ALL
+            .iter()
+            .copied()
+            .find(|status| status.name() == value)
+            .ok_or_else(|| anyhow!("{} is not a status", value))
+    }
+}
+
+/// frontmatter and body of content, taking dates without an offset to be in UTC. Rendered
+/// content has them in RFC 3339 already
+pub fn extract(
+    name: &str,
+    content: &str,
+) -> anyhow::Result<(Frontmatter, String)> {
+    parse(name, content, Strictness::Normal, Zone::default()).map(|(front, body, _)| (front, body))
+}
+
+/// frontmatter and body of content, parsed as strictly as asked, along with problems lenient
+/// parsing turned into warnings. Dates without an offset are taken to be in `zone`
+pub fn parse(
+    name: &str,
+    content: &str,
+    strictness: Strictness,
+    zone: Zone,
+) -> anyhow::Result<(Frontmatter, String, Vec<FieldError>)> {
+    let (front, back) = match frontmatter::parse_and_find_content(content) {
+        Ok((front, back)) => (front, back),
+        Err(err) => {
+            eprintln!("Error extracting front matter from {}", name);
+            Err(err)?
+        }
+    };
+    let metadata = front.ok_or_else(
+        || {
+            anyhow!(
+                "file {} is missing required markdown frontmatter.\n  ▶ Please see https://dev.to/p/editor_guide more information on what frontmatter is expected", name
+            )
+        }
+    )?;
+
+    let (front, warnings) = Frontmatter::from_file(name, metadata, strictness, zone)?;
+    Ok((front, back.into(), warnings))
+}
+
+/// tags listed in frontmatter, as a comma separated string or a yaml list
+pub fn tags(content: &str) -> Vec<String> {
+    let front = match frontmatter::parse_and_find_content(content) {
+        Ok((Some(front), _)) => front,
+        _ => return Vec::new(),
+    };
+    match &front["tags"] {
+        Yaml::String(tags) => tags
+            .split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+        Yaml::Array(tags) => tags
+            .iter()
+            .filter_map(|tag| tag.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// tags listed in frontmatter followed by those of the directories an article is in
+pub fn merged_tags(
+    config: &Config,
+    source: &Path,
+    path: &Path,
+    content: &str,
+) -> Vec<String> {
+    let mut merged = tags(content);
+    for tag in config
+        .directory_tags
+        .tags(&state::key(source, path), config.layout)
+    {
+        if !merged.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            merged.push(tag);
+        }
+    }
+    merged
+}
+
+/// content following frontmatter, if any
+pub fn body(content: &str) -> &str {
+    match frontmatter::parse_and_find_content(content) {
+        Ok((Some(_), body)) => body,
+        _ => content,
+    }
+}
+
+/// Longest title Forem accepts, in characters
+const MAX_TITLE: usize = 128;
+/// Longest description Forem accepts, in characters
+const MAX_DESCRIPTION: usize = 170;
+/// Most tags Forem accepts per article
+pub const MAX_TAGS: usize = 4;
+/// Longest tag Forem accepts, in characters
+const MAX_TAG: usize = 30;
+/// Largest body Forem accepts, in characters
+const MAX_BODY: usize = 800_000;
+
+/// Frontmatter fields devtogo knows
+const FIELDS: &[&str] = &[
+    "title",
+    "description",
+    "published",
+    "tags",
+    "date",
+    "series",
+    "canonical_url",
+    "cover_image",
+    "cover_image_alt",
+    "collection_id",
+    "targets",
+    "api_key_ref",
+    "status",
+    "author",
+    "reviewed_by",
+];
+
+/// How forgiving frontmatter parsing is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strictness {
+    /// unknown fields are ignored and invalid ones are errors
+    Normal,
+    /// unknown fields are errors too, so nothing is silently dropped
+    Strict,
+    /// invalid optional fields are warned about and left out
+    Lenient,
+}
+
+impl Strictness {
+    /// strictness asked for with --strict or --lenient
+    pub fn from_flags(
+        strict: bool,
+        lenient: bool,
+    ) -> Self {
+        match (strict, lenient) {
+            (true, _) => Strictness::Strict,
+            (_, true) => Strictness::Lenient,
+            _ => Strictness::Normal,
+        }
+    }
+}
+
+/// A problem with a specific frontmatter field
+#[derive(Debug)]
+pub struct FieldError {
+    pub field: String,
+    message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for FieldError {}
+
+/// Markdown frontmatter dev.to api documents as acceptable input
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Frontmatter {
+    pub title: String,
+    pub description: Option<String>,
+    pub published: Option<bool>,
+    /// Comma separated, with tags written as a yaml list joined
+    pub tags: Option<String>,
+    pub date: Option<String>,
+    pub series: Option<String>,
+    pub canonical_url: Option<String>,
+    pub cover_image: Option<String>,
+    pub cover_image_alt: Option<String>,
+    pub collection_id: Option<u32>,
+    /// names of the configured targets to publish to. Defaults to all
+    pub targets: Option<Vec<String>>,
+    /// name of a configured profile whose account publishes this article
+    pub api_key_ref: Option<String>,
+    /// where this article is in an editorial workflow, which overrides published
+    pub status: Option<Status>,
+    /// who wrote this article
+    pub author: Option<String>,
+    /// who reviewed this article, which must be someone other than its author
+    pub reviewed_by: Option<String>,
+}
+
+impl Frontmatter {
+    pub fn publish_status(&self) -> PublishStatus {
+        let published = match self.status {
+            Some(status) => status == Status::Published,
+            None => self.published.unwrap_or_default(),
+        };
+        if published {
+            PublishStatus::Published
+        } else {
+            PublishStatus::Draft
+        }
+    }
+    /// true if this article is ready to be pushed, which articles without a status always are
+    pub fn pushed(&self) -> bool {
+        self.status.map(Status::pushed).unwrap_or(true)
+    }
+
+    /// true if this article should be published to a given target
+    pub fn targets(
+        &self,
+        target: &str,
+    ) -> bool {
+        match &self.targets {
+            None => true,
+            Some(targets) => targets.iter().any(|t| t == target),
+        }
+    }
+
+    /// ways this article exceeds limits Forem would reject it for
+    pub fn limits(
+        &self,
+        content: &str,
+    ) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        let mut exceeds = |field: &'static str, message: String| {
+            errors.push(FieldError {
+                field: field.into(),
+                message,
+            });
+        };
+        let title = self.title.chars().count();
+        if title > MAX_TITLE {
+            exceeds(
+                "title",
+                format!(
+                    "title is {} characters long. Forem allows at most {}",
+                    title, MAX_TITLE
+                ),
+            );
+        }
+        let description = self
+            .description
+            .as_deref()
+            .map(|d| d.chars().count())
+            .unwrap_or_default();
+        if description > MAX_DESCRIPTION {
+            exceeds(
+                "description",
+                format!(
+                    "description is {} characters long. Forem allows at most {}",
+                    description, MAX_DESCRIPTION
+                ),
+            );
+        }
+        let tags = self
+            .tags
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>();
+        if tags.len() > MAX_TAGS {
+            exceeds(
+                "tags",
+                format!(
+                    "{} tags are listed. Forem allows at most {}",
+                    tags.len(),
+                    MAX_TAGS
+                ),
+            );
+        }
+        for tag in tags {
+            if tag.chars().count() > MAX_TAG || !tag.chars().all(char::is_alphanumeric) {
+                exceeds(
+                    "tags",
+                    format!(
+                        "tag {} is invalid. Forem tags are alphanumeric and at most {} characters long",
+                        tag, MAX_TAG
+                    ),
+                );
+            }
+        }
+        let body = content.chars().count();
+        if body > MAX_BODY {
+            exceeds(
+                "body",
+                format!(
+                    "body is {} characters long. Forem allows at most {}",
+                    body, MAX_BODY
+                ),
+            );
+        }
+        errors
+    }
+
+    /// api input for uploading content described by this frontmatter
+    pub fn input<'a>(
+        &'a self,
+        content: &'a str,
+    ) -> ArticleInput<'a> {
+        ArticleInput {
+            body_markdown: content,
+            collection_id: self.collection_id,
+            main_image_alt: self.cover_image_alt.as_deref(),
+        }
+    }
+
+    /// extract and validate raw yaml frontmatter, along with problems leniency turned into warnings
+    fn from_file(
+        name: &str,
+        metadata: Yaml,
+        strictness: Strictness,
+        zone: Zone,
+    ) -> anyhow::Result<(Frontmatter, Vec<FieldError>)> {
+        let hash = metadata
+            .into_hash()
+            .ok_or_else(|| anyhow!("file {} contains frontmatter that not well formatted", name))?;
+        let string = |name: &str| -> Option<String> {
+            hash.get(&Yaml::String(name.into()))
+                .and_then(|v| v.as_str().map(|s| s.into()))
+        };
+        let boolean = |name: &str| -> Option<bool> {
+            hash.get(&Yaml::String(name.into()))
+                .and_then(|v| v.as_bool())
+        };
+        if strictness == Strictness::Strict {
+            let unknown = hash
+                .keys()
+                .filter_map(Yaml::as_str)
+                .find(|field| !FIELDS.contains(field));
+            if let Some(field) = unknown {
+                return Err(FieldError {
+                    field: field.into(),
+                    message: format!(
+                        "file {} contains frontmatter with an unknown field {}",
+                        name, field
+                    ),
+                }
+                .into());
+            }
+        }
+        let mut warnings = Vec::new();
+        // lenient parsing warns about invalid optional fields rather than failing on them
+        let mut invalid = |err: FieldError| -> Result<(), FieldError> {
+            if strictness == Strictness::Lenient {
+                warnings.push(err);
+                Ok(())
+            } else {
+                Err(err)
+            }
+        };
+        let title = string("title").ok_or_else(|| FieldError {
+            field: "title".into(),
+            message: format!("file {} contains frontmatter missing a string title", name),
+        })?;
+        let description = string("description");
+        let published = boolean("published");
+        let tags = match hash.get(&Yaml::String("tags".into())) {
+            Some(Yaml::Array(tags)) => Some(
+                tags.iter()
+                    .filter_map(Yaml::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            _ => string("tags"),
+        };
+        let date = match string("date") {
+            None => None,
+            Some(value) => match dates::normalize(&value, zone) {
+                Some(date) => Some(date),
+                None => {
+                    invalid(FieldError {
+                        field: "date".into(),
+                        message: format!(
+                            "file {} contains frontmatter with an invalid date: {}. Expected a date like 2024-05-01, 2024-05-01 10:00 or 2024-05-01T10:00:00+02:00",
+                            name, value
+                        ),
+                    })?;
+                    None
+                }
+            },
+        };
+        let series = string("series");
+        let canonical_url = string("canonical_url");
+        let cover_image = string("cover_image");
+        let cover_image_alt = string("cover_image_alt");
+        let api_key_ref = string("api_key_ref");
+        let author = string("author");
+        let reviewed_by = string("reviewed_by");
+        let status = match string("status") {
+            None => None,
+            Some(value) => match value.parse() {
+                Ok(status) => Some(status),
+                Err(_) => {
+                    invalid(FieldError {
+                        field: "status".into(),
+                        message: format!(
+                            "file {} contains frontmatter with an invalid status {}. Expected idea, in-review, approved or published",
+                            name, value
+                        ),
+                    })?;
+                    None
+                }
+            },
+        };
+        let collection_id = match hash.get(&Yaml::String("collection_id".into())) {
+            None => None,
+            Some(value) => match value.as_i64().and_then(|id| u32::try_from(id).ok()) {
+                Some(id) => Some(id),
+                None => {
+                    invalid(FieldError {
+                        field: "collection_id".into(),
+                        message: format!(
+                            "file {} contains frontmatter with an invalid collection_id. Expected a positive integer",
+                            name
+                        ),
+                    })?;
+                    None
+                }
+            },
+        };
+
+        // a yaml list or comma separated string
+        let targets = match hash.get(&Yaml::String("targets".into())) {
+            None => None,
+            Some(Yaml::Array(values)) => Some(
+                values
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect(),
+            ),
+            Some(Yaml::String(value)) => Some(
+                value
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect(),
+            ),
+            Some(_) => {
+                invalid(FieldError {
+                    field: "targets".into(),
+                    message: format!(
+                        "file {} contains frontmatter with invalid targets. Expected a list of target names",
+                        name
+                    ),
+                })?;
+                None
+            }
+        };
+
+        let front = Frontmatter {
+            title,
+            description,
+            published,
+            tags,
+            date,
+            series,
+            canonical_url,
+            cover_image,
+            cover_image_alt,
+            collection_id,
+            targets,
+            api_key_ref,
+            status,
+            author,
+            reviewed_by,
+        };
+        Ok((front, warnings))
+    }
+}
+
+pub fn valid_path(path: &Path) -> bool {
+    !path.is_dir()
+        && path.extension().into_iter().any(|e| {
+            e == "md" || e == "markdown" || e == notebook::EXTENSION || e == mdx::EXTENSION
+        })
+}
+
+/// true if an article is written in something other than markdown, converted when read
+pub fn converted(
+    config: &Config,
+    path: &Path,
+) -> bool {
+    config.converter(path).is_some()
+        || path.extension() == Some(OsStr::new(notebook::EXTENSION))
+        || path.extension() == Some(OsStr::new(mdx::EXTENSION))
+}
+
+/// an article's markdown content
+pub fn read(
+    config: &Config,
+    source: &Path,
+    path: &Path,
+) -> anyhow::Result<String> {
+    match config.converter(path) {
+        Some(converter) => converter.run(path),
+        None => match path.extension().and_then(OsStr::to_str) {
+            Some(notebook::EXTENSION) => notebook::to_markdown(config, source, path),
+            Some(mdx::EXTENSION) => Ok(mdx::to_markdown(&config.mdx, &fs::read_to_string(path)?)),
+            _ => Ok(fs::read_to_string(path)?),
+        },
+    }
+}
+
+/// an article's content as published to a kind of service, with snippets and code samples
+/// included and, when configured, math converted, footnotes converted, headings demoted,
+/// typography smartened, frontmatter defaults of its content root filled in and series and tags
+/// of its directories added
+pub async fn render(
+    config: &Config,
+    source: &Path,
+    path: &Path,
+    kind: Kind,
+) -> anyhow::Result<String> {
+    render_content(config, source, path, kind, &read(config, source, path)?).await
+}
+
+/// content rendered as a file at `path` would be, for content read from elsewhere, i.e. stdin
+pub async fn render_content(
+    config: &Config,
+    source: &Path,
+    path: &Path,
+    kind: Kind,
+    content: &str,
+) -> anyhow::Result<String> {
+    let mut content = snippets::expand(source, content)?;
+    if config.title_from_heading {
+        content = title_from_heading(&content, true)?;
+    }
+    // forem only knows published, so a status decides it
+    let status = frontmatter::parse_and_find_content(&content)
+        .ok()
+        .and_then(|(front, _)| front)
+        .and_then(|front| front["status"].as_str()?.parse::<Status>().ok());
+    if let Some(status) = status {
+        content = set(
+            &content,
+            "published",
+            &(status == Status::Published).to_string(),
+        )?;
+    }
+    // forem only understands RFC 3339 dates
+    let date = frontmatter::parse_and_find_content(&content)
+        .ok()
+        .and_then(|(front, _)| front)
+        .and_then(|front| front["date"].as_str().map(String::from));
+    if let Some(normalized) = date
+        .as_deref()
+        .and_then(|date| dates::normalize(date, config.timezone))
+    {
+        if date.as_deref() != Some(normalized.as_str()) {
+            content = set(&content, "date", &serde_json::to_string(&normalized)?)?;
+        }
+    }
+    content = snippets::code(source, path, &content).await?;
+    content = images::publish(config, source, path, &content)?;
+    content = diagrams::render(config, source, path, &content)?;
+    if let Some(math) = &config.math {
+        content = math::render(math, kind, &content);
+    }
+    if config.convert_footnotes {
+        content = footnotes::to_notes(&content);
+    }
+    if config.normalize_headings {
+        content = headings::demote(&content);
+    }
+    if config.smart_typography {
+        content = typography::smarten(&content);
+    }
+    if let Some(series) = config.directory_series(&state::key(source, path)) {
+        if position(&content, "series").is_none() {
+            content = set(&content, "series", &serde_json::to_string(&series)?)?;
+        }
+    }
+    if let Some(root) = config.root(source) {
+        if !root.tags.is_empty() && position(&content, "tags").is_none() {
+            content = set(
+                &content,
+                "tags",
+                &serde_json::to_string(&root.tags.join(", "))?,
+            )?;
+        }
+        if let (Some(series), None) = (&root.series, position(&content, "series")) {
+            content = set(&content, "series", &serde_json::to_string(series)?)?;
+        }
+    }
+    let listed = tags(&content);
+    let merged = merged_tags(config, source, path, &content);
+    if merged != listed {
+        content = set(
+            &content,
+            "tags",
+            &serde_json::to_string(&merged.join(", "))?,
+        )?;
+    }
+    if let Some(base) = &config.canonical_base_url {
+        if position(&content, "canonical_url").is_none() {
+            let url = canonical_url(base, &state::key(source, path));
+            content = set(&content, "canonical_url", &serde_json::to_string(&url)?)?;
+        }
+    }
+    Ok(content)
+}
+
+/// where an article, keyed by its path within its source directory, is published under a
+/// canonical base url. Bundles are published at the path of their directory
+fn canonical_url(
+    base: &str,
+    key: &str,
+) -> String {
+    let path = key.rsplit_once('.').map(|(path, _)| path).unwrap_or(key);
+    let path = match path.strip_suffix("index") {
+        Some(dir) if dir.is_empty() || dir.ends_with('/') => dir.trim_end_matches('/'),
+        _ => path,
+    };
+    format!("{}/{}", base.trim_end_matches('/'), path)
+}
+
+/// 1-based line and column of a frontmatter field's value, if present
+pub fn position(
+    content: &str,
+    field: &str,
+) -> Option<(usize, usize)> {
+    content
+        .lines()
+        .enumerate()
+        .skip(1)
+        .take_while(|(_, line)| line.trim_end() != "---")
+        .find_map(|(n, line)| {
+            let indent = line.len() - line.trim_start().len();
+            let rest = line.trim_start().strip_prefix(field)?.strip_prefix(':')?;
+            let value = rest.len() - rest.trim_start().len();
+            Some((n + 1, indent + field.len() + 1 + value + 1))
+        })
+}
+
+/// content with a top level frontmatter field set to a yaml `value`, replacing the field or
+/// adding it at the end of the frontmatter. The rest of the frontmatter is left as written,
+/// comments and quoting included, so rewritten files make small diffs
+pub fn set(
+    content: &str,
+    field: &str,
+    value: &str,
+) -> anyhow::Result<String> {
+    let lines = content.split('\n').collect::<Vec<_>>();
+    let end = match frontmatter_end(content) {
+        Some(end) => end,
+        None => anyhow::bail!("no frontmatter to set {} in", field),
+    };
+    let yaml = yaml::set(&lines[1..end].join("\n"), field, value);
+    let mut edited = vec![lines[0]];
+    if !yaml.is_empty() {
+        edited.push(&yaml);
+    }
+    edited.extend(&lines[end..]);
+    let edited = edited.join("\n");
+    if frontmatter::parse_and_find_content(&edited).is_err() {
+        anyhow::bail!(
+            "setting {} to {} would leave invalid frontmatter",
+            field,
+            value
+        );
+    }
+    Ok(edited)
+}
+
+/// number of the line closing frontmatter, counting from 0, if there is frontmatter
+pub fn frontmatter_end(content: &str) -> Option<usize> {
+    let mut lines = content.split('\n');
+    if lines.next()?.trim_end() != "---" {
+        return None;
+    }
+    lines
+        .position(|line| line.trim_end() == "---")
+        .map(|end| end + 1)
+}
+
+/// content with a title taken from the first `# heading` of its body when its frontmatter has
+/// none. With `strip`, the heading is removed so it isn't shown twice
+pub fn title_from_heading(
+    content: &str,
+    strip: bool,
+) -> anyhow::Result<String> {
+    let end = match frontmatter_end(content) {
+        Some(end) if position(content, "title").is_none() => end,
+        _ => return Ok(content.into()),
+    };
+    let mut lines = content.split('\n').collect::<Vec<_>>();
+    let mut fenced = false;
+    let heading = lines
+        .iter()
+        .enumerate()
+        .skip(end + 1)
+        .find_map(|(n, line)| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                fenced = !fenced;
+            }
+            let title = line.strip_prefix("# ").filter(|_| !fenced)?;
+            Some((n, title.trim().trim_end_matches('#').trim_end().to_string()))
+        });
+    let (n, title) = match heading {
+        Some(heading) => heading,
+        None => return Ok(content.into()),
+    };
+    if strip {
+        lines.remove(n);
+        if lines.get(n).map(|line| line.trim().is_empty()) == Some(true) {
+            lines.remove(n);
+        }
+    }
+    set(&lines.join("\n"), "title", &serde_json::to_string(&title)?)
+}
+
+/// the language of a translated article, named like my-post.es.md, and the path of
+/// its primary language version, my-post.md. Only configured languages are recognized
+/// so names like node.js.md are left alone
+pub fn language(
+    path: &Path,
+    languages: &[String],
+) -> Option<(String, PathBuf)> {
+    let stem = path.file_stem()?.to_str()?;
+    let (base, language) = stem.rsplit_once('.')?;
+    if base.is_empty() || !languages.iter().any(|l| l == language) {
+        return None;
+    }
+    let extension = path.extension()?.to_str()?;
+    Some((
+        language.to_string(),
+        path.with_file_name(format!("{}.{}", base, extension)),
+    ))
+}
+
+/// article files under a source directory, skipping devtogo's own state and shared snippets
+pub fn paths(
+    source: &Path,
+    config: &Config,
+) -> impl Iterator<Item = PathBuf> {
+    let snippets = source.join(snippets::DIR);
+    let layout = config.layout;
+    let converters = config.converters.keys().cloned().collect::<Vec<_>>();
+    WalkDir::new(source)
+        // in order of file names, so numbered parts of a series are pushed in order
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(move |e| e.file_name() != state::DIR && e.path() != snippets)
+        .filter_map(|e| e.ok().map(|e| e.path().to_path_buf()))
+        .filter(move |p| {
+            let converted = p.is_file()
+                && p.extension()
+                    .into_iter()
+                    .any(|e| converters.iter().any(|c| e == c.as_str()));
+            (valid_path(p) || converted) && layout.includes(p)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Layout;
+
+    #[test]
+    fn frontmatter_is_published_when_expected() {
+        assert_eq!(
+            Frontmatter::default().publish_status(),
+            PublishStatus::Draft
+        );
+        assert_eq!(
+            Frontmatter {
+                published: Some(false),
+                ..Frontmatter::default()
+            }
+            .publish_status(),
+            PublishStatus::Draft
+        );
+        assert_eq!(
+            Frontmatter {
+                published: Some(true),
+                ..Frontmatter::default()
+            }
+            .publish_status(),
+            PublishStatus::Published
+        );
+    }
+    #[test]
+    fn status_overrides_published() -> anyhow::Result<()> {
+        let (front, _) = extract(
+            "foo.md",
+            "---\ntitle: foo\npublished: true\nstatus: in-review\n---\n",
+        )?;
+        assert_eq!(front.status, Some(Status::InReview));
+        assert_eq!(front.publish_status(), PublishStatus::Draft);
+        assert!(!front.pushed());
+        let (front, _) = extract("foo.md", "---\ntitle: foo\nstatus: published\n---\n")?;
+        assert_eq!(front.publish_status(), PublishStatus::Published);
+        assert!(front.pushed());
+        assert!(extract("foo.md", "---\ntitle: foo\nstatus: done\n---\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn valid_path_isnt_dirs() {
+        assert!(!valid_path(&PathBuf::from("/")))
+    }
+
+    #[test]
+    fn valid_path_contains_md_ext() {
+        assert!(valid_path(&PathBuf::from("/foo.md")));
+    }
+
+    #[test]
+    fn valid_path_contains_markdown_ext() {
+        assert!(valid_path(&PathBuf::from("/foo.markdown")));
+    }
+
+    #[test]
+    fn valid_path_contains_notebook_ext() {
+        assert!(valid_path(&PathBuf::from("/foo.ipynb")));
+    }
+
+    #[test]
+    fn valid_path_doesnt_contains_other_ext() {
+        assert!(!valid_path(&PathBuf::from("/foo.txt")));
+    }
+
+    #[test]
+    fn paths_finds_markdown_files() {
+        let found = paths(Path::new("tests/data"), &Config::default()).collect::<Vec<_>>();
+        assert_eq!(found.len(), 2);
+        let bundles = Config {
+            layout: Layout::Bundles,
+            ..Config::default()
+        };
+        assert_eq!(paths(Path::new("tests/data"), &bundles).count(), 0);
+    }
+
+    #[test]
+    fn language_reads_suffixes() {
+        let languages = ["en".to_string(), "es".into(), "pt-BR".into()];
+        assert_eq!(
+            language(Path::new("posts/my-post.es.md"), &languages),
+            Some(("es".into(), PathBuf::from("posts/my-post.md")))
+        );
+        assert_eq!(
+            language(Path::new("my-post/index.pt-BR.markdown"), &languages),
+            Some(("pt-BR".into(), PathBuf::from("my-post/index.markdown")))
+        );
+        assert_eq!(language(Path::new("my-post.md"), &languages), None);
+        assert_eq!(language(Path::new("my-post.fr.md"), &languages), None);
+        assert_eq!(language(Path::new("node.js.md"), &languages), None);
+    }
+
+    #[test]
+    fn position_finds_frontmatter_values() {
+        let content = "---\ntitle: foo\n  date: bar\n---\ndate: baz";
+        assert_eq!(position(content, "title"), Some((2, 8)));
+        assert_eq!(position(content, "date"), Some((3, 9)));
+        assert_eq!(position(content, "series"), None);
+    }
+
+    #[test]
+    fn tags_reads_strings_and_lists() {
+        assert_eq!(tags("---\ntags: rust, cli\n---\n"), vec!["rust", "cli"]);
+        assert_eq!(tags("---\ntags: [rust, cli]\n---\n"), vec!["rust", "cli"]);
+        assert_eq!(tags("---\ntitle: foo\n---\n"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn title_from_heading_takes_first_h1() -> anyhow::Result<()> {
+        let content =
+            "---\ntags: rust\n---\n```sh\n# not a heading\n```\n# Hello, world #\n\nhi\n# Other";
+        assert_eq!(
+            title_from_heading(content, true)?,
+            "---\ntags: rust\ntitle: \"Hello, world\"\n---\n```sh\n# not a heading\n```\nhi\n# Other"
+        );
+        assert_eq!(
+            title_from_heading(content, false)?,
+            "---\ntags: rust\ntitle: \"Hello, world\"\n---\n```sh\n# not a heading\n```\n# Hello, world #\n\nhi\n# Other"
+        );
+        let titled = "---\ntitle: foo\n---\n# Hello";
+        assert_eq!(title_from_heading(titled, true)?, titled);
+        assert_eq!(title_from_heading("# Hello", true)?, "# Hello");
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_url_is_article_path_under_base() {
+        assert_eq!(
+            canonical_url("https://example.com/blog/", "posts/hello.md"),
+            "https://example.com/blog/posts/hello"
+        );
+        assert_eq!(
+            canonical_url("https://example.com", "hello/index.md"),
+            "https://example.com/hello"
+        );
+        assert_eq!(
+            canonical_url("https://example.com", "reindex.md"),
+            "https://example.com/reindex"
+        );
+    }
+
+    #[test]
+    fn set_replaces_or_adds_frontmatter_fields() -> anyhow::Result<()> {
+        let content = "---\ntitle: foo\npublished: false\n---\npublished: false";
+        assert_eq!(
+            set(content, "published", "true")?,
+            "---\ntitle: foo\npublished: true\n---\npublished: false"
+        );
+        assert_eq!(
+            set(content, "series", "bar")?,
+            "---\ntitle: foo\npublished: false\nseries: bar\n---\npublished: false"
+        );
+        assert!(set("foo", "title", "bar").is_err());
+        let content = "---\ntitle: foo # working title\ntags:\n  - rust\n---\nhi";
+        assert_eq!(
+            set(content, "title", "\"bar\"")?,
+            "---\ntitle: bar # working title\ntags:\n  - rust\n---\nhi"
+        );
+        assert!(set(content, "tags", "[rust").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn extract_reports_invalid_fields() {
+        let err = extract("foo.md", "---\ntitle: foo\ndate: ...\n---\n").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<FieldError>().map(|e| e.field.as_str()),
+            Some("date")
+        );
+    }
+
+    #[test]
+    fn parse_strictness() -> anyhow::Result<()> {
+        let content = "---\ntitle: foo\ndate: ...\nlayout: post\n---\n";
+        assert!(parse("foo.md", content, Strictness::Normal, Zone::Utc).is_err());
+        let (front, _, warnings) = parse("foo.md", content, Strictness::Lenient, Zone::Utc)?;
+        assert_eq!(front.date, None);
+        assert_eq!(warnings[0].field, "date");
+        let err = parse(
+            "foo.md",
+            "---\ntitle: foo\nlayout: post\n---\n",
+            Strictness::Strict,
+            Zone::Utc,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<FieldError>().map(|e| e.field.as_str()),
+            Some("layout")
+        );
+        assert!(parse(
+            "foo.md",
+            "---\ntitle: foo\n---\n",
+            Strictness::Strict,
+            Zone::Utc
+        )
+        .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn extract_reads_collection_and_alt_text() -> anyhow::Result<()> {
+        let (front, _) = extract(
+            "foo.md",
+            "---\ntitle: foo\ncollection_id: 42\ncover_image_alt: a cat\n---\n",
+        )?;
+        assert_eq!(front.collection_id, Some(42));
+        assert_eq!(front.cover_image_alt.as_deref(), Some("a cat"));
+        assert!(extract("foo.md", "---\ntitle: foo\ncollection_id: nope\n---\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn limits_accepts_typical_articles() -> anyhow::Result<()> {
+        let (front, _) = extract("foo.md", "---\ntitle: foo\ntags: rust, cli\n---\n")?;
+        assert!(front.limits("hi").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn limits_counts_tags_listed_in_yaml() -> anyhow::Result<()> {
+        for content in [
+            "---\ntitle: foo\ntags: [a, b, c, d, e]\n---\n",
+            "---\ntitle: foo\ntags:\n  - a\n  - b\n  - c\n  - d\n  - e\n---\n",
+        ] {
+            let (front, _) = extract("foo.md", content)?;
+            assert_eq!(front.tags.as_deref(), Some("a, b, c, d, e"));
+            let fields = front
+                .limits("hi")
+                .iter()
+                .map(|e| e.field.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(fields, vec!["tags"]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn limits_reports_each_excess() {
+        let front = Frontmatter {
+            title: "t".repeat(129),
+            description: Some("d".repeat(171)),
+            tags: Some("a, b, c, d, e-f".into()),
+            ..Frontmatter::default()
+        };
+        let fields = front
+            .limits("hi")
+            .iter()
+            .map(|e| e.field.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(fields, vec!["title", "description", "tags", "tags"]);
+        assert_eq!(
+            front.limits("hi")[0].to_string(),
+            "title is 129 characters long. Forem allows at most 128"
+        );
+    }
+
+    #[test]
+    fn extract_reads_api_key_ref() -> anyhow::Result<()> {
+        let (front, _) = extract("foo.md", "---\ntitle: foo\napi_key_ref: alice\n---\n")?;
+        assert_eq!(front.api_key_ref.as_deref(), Some("alice"));
+        Ok(())
+    }
+
+    #[test]
+    fn extract_reads_targets() -> anyhow::Result<()> {
+        let (front, _) = extract("foo.md", "---\ntitle: foo\ntargets: [devto, forem]\n---\n")?;
+        assert!(front.targets("forem"));
+        assert!(!front.targets("other"));
+        let (front, _) = extract("foo.md", "---\ntitle: foo\ntargets: devto, forem\n---\n")?;
+        assert_eq!(front.targets, Some(vec!["devto".into(), "forem".into()]));
+        let (front, _) = extract("foo.md", "---\ntitle: foo\n---\n")?;
+        assert!(front.targets("anything"));
+        Ok(())
+    }
+
+    #[test]
+    fn body_skips_frontmatter() {
+        assert_eq!(body("---\ntitle: foo\n---\n# hi"), "# hi");
+        assert_eq!(body("# hi"), "# hi");
+    }
+
+    #[test]
+    fn publish_status_impl_display() {
+        fn test(_: impl fmt::Display) {}
+        test(PublishStatus::Draft)
+    }
+
+    #[test]
+    fn test_extract_fails_with_missing_frontmatter() {
+        let result = extract(
+            "foo.md",
+            r#"
+        "#,
+        );
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn test_extract_fails_with_missing_title() {
+        let result = extract(
+            "foo.md",
+            r#"
+        --
+        --
+        "#,
+        );
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn test_extract_passes_with_missing_frontmatter() -> anyhow::Result<()> {
+        let (front, _) = extract(
+            "foo.md",
+            r#"---
+            title: foo
+            ---
+            "#,
+        )?;
+        assert_eq!(
+            front,
+            Frontmatter {
+                title: "foo".into(),
+                ..Frontmatter::default()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_validates_date() -> anyhow::Result<()> {
+        let result = extract(
+            "foo.md",
+            r#"---
+            title: foo
+            date: ...
+            ---
+            "#,
+        );
+        assert!(result.is_err());
+        let (front, _) = extract("foo.md", "---\ntitle: foo\ndate: 2024-05-01 10:00\n---\n")?;
+        assert_eq!(front.date.as_deref(), Some("2024-05-01T10:00:00+00:00"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_validates_dates_in_the_configured_zone() -> anyhow::Result<()> {
+        let berlin: Zone = "Europe/Berlin".parse()?;
+        let (front, _, _) = parse(
+            "foo.md",
+            "---\ntitle: foo\ndate: 2024-05-01 10:00\n---\n",
+            Strictness::Normal,
+            berlin,
+        )?;
+        assert_eq!(front.date.as_deref(), Some("2024-05-01T10:00:00+02:00"));
+        // skipped in Berlin by clocks going forward, though not in UTC
+        let skipped = "---\ntitle: foo\ndate: 2024-03-31 02:30\n---\n";
+        assert!(parse("foo.md", skipped, Strictness::Normal, berlin).is_err());
+        assert!(parse("foo.md", skipped, Strictness::Normal, Zone::Utc).is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,300 @@
+use crate::{
+    config::Config,
+    duration::Duration,
+    owners::DEFAULT_PROFILE,
+    state::{self, DIR},
+};
+use anyhow::anyhow;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use structopt::StructOpt;
+
+/// Name of the file, within a source directory's .devtogo directory, the audit log is kept in
+const FILE: &str = "audit.jsonl";
+
+/// Lists publishing actions recorded in the audit log, newest last
+///
+/// Every article created, updated, published or unpublished by push or sync is appended to
+/// .devtogo/audit.jsonl, which is never rewritten
+#[derive(StructOpt, Debug)]
+pub struct Audit {
+    /// Directory whose .devtogo directory the audit log is kept in. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Only list actions on this file
+    #[structopt(long)]
+    file: Option<PathBuf>,
+    /// Only list actions of this kind: create, update, publish or unpublish
+    #[structopt(long)]
+    action: Option<Action>,
+    /// Only list actions taken by this profile
+    #[structopt(long)]
+    profile: Option<String>,
+    /// Only list actions taken within this long, i.e. 7d
+    #[structopt(long)]
+    since: Option<Duration>,
+    /// Print matching records as JSON lines, as they're kept
+    #[structopt(long)]
+    json: bool,
+}
+
+/// A publishing action
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Create,
+    Update,
+    Publish,
+    Unpublish,
+}
+
+impl Action {
+    fn as_str(self) -> &'static str {
+        match self {
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Publish => "publish",
+            Action::Unpublish => "unpublish",
+        }
+    }
+
+    /// actions taken by uploading an article, from whether it was created and whether it was
+    /// published before and after
+    pub fn taken(
+        created: bool,
+        was_published: bool,
+        published: bool,
+    ) -> Vec<Action> {
+        let mut actions = vec![if created {
+            Action::Create
+        } else {
+            Action::Update
+        }];
+        match (was_published, published) {
+            (false, true) => actions.push(Action::Publish),
+            (true, false) => actions.push(Action::Unpublish),
+            _ => (),
+        }
+        actions
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Action {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        [
+            Action::Create,
+            Action::Update,
+            Action::Publish,
+            Action::Unpublish,
+        ]
+        .iter()
+        .copied()
+        .find(|action| action.as_str() == value)
+        .ok_or_else(|| {
+            anyhow!(
+                "{} is not an action. Expected create, update, publish or unpublish",
+                value
+            )
+        })
+    }
+}
+
+/// A record of a publishing action
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub at: DateTime<Utc>,
+    pub action: Action,
+    /// configured target the action was taken on
+    pub target: String,
+    /// path of the article within its source directory
+    pub file: String,
+    /// remote article id
+    pub id: String,
+    /// profile whose account took the action
+    pub profile: String,
+    /// hash of the local file content
+    pub local: String,
+    /// hash of the markdown uploaded
+    pub remote: String,
+}
+
+impl Event {
+    pub fn new(
+        action: Action,
+        target: &str,
+        file: &str,
+        id: &str,
+        profile: Option<&str>,
+        local: &str,
+        remote: &str,
+    ) -> Self {
+        Event {
+            at: Utc::now(),
+            action,
+            target: target.into(),
+            file: file.into(),
+            id: id.into(),
+            profile: profile.unwrap_or(DEFAULT_PROFILE).into(),
+            local: state::hash(local),
+            remote: state::hash(remote),
+        }
+    }
+}
+
+fn path(source: &Path) -> PathBuf {
+    source.join(DIR).join(FILE)
+}
+
+/// append events to a source directory's audit log
+pub fn record(
+    source: &Path,
+    events: &[Event],
+) -> anyhow::Result<()> {
+    let path = path(source);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut lines = String::new();
+    for event in events {
+        lines.push_str(&serde_json::to_string(event)?);
+        lines.push('\n');
+    }
+    // a single write per push of an article, so concurrent pushes don't interleave lines
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?
+        .write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// every event in a source directory's audit log, oldest first
+fn events(source: &Path) -> anyhow::Result<Vec<Event>> {
+    let path = path(source);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| {
+            serde_json::from_str(line)
+                .map_err(|err| anyhow!("line {} of {} is invalid: {}", n + 1, path.display(), err))
+        })
+        .collect()
+}
+
+/// true if an event matches the filters asked for
+fn matches(
+    event: &Event,
+    file: Option<&str>,
+    action: Option<Action>,
+    profile: Option<&str>,
+    after: Option<DateTime<Utc>>,
+) -> bool {
+    file.map(|file| event.file == file).unwrap_or(true)
+        && action.map(|action| event.action == action).unwrap_or(true)
+        && profile
+            .map(|profile| event.profile == profile)
+            .unwrap_or(true)
+        && after.map(|after| event.at >= after).unwrap_or(true)
+}
+
+pub fn run(
+    config: &Config,
+    args: Audit,
+) -> anyhow::Result<()> {
+    let Audit {
+        source,
+        file,
+        action,
+        profile,
+        since,
+        json,
+    } = args;
+    let source = config.source(source);
+    let file = file.map(|file| state::key(&source, &file));
+    let after = since.map(|since| Utc::now() - since.0);
+    let events = events(&source)?;
+    let matching = events
+        .iter()
+        .filter(|event| matches(event, file.as_deref(), action, profile.as_deref(), after));
+    for event in matching {
+        if json {
+            println!("{}", serde_json::to_string(event)?);
+        } else {
+            println!(
+                "{}  {:<9}  {}  {}  {} #{}  {}",
+                event.at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                event.action,
+                event.profile,
+                event.file,
+                event.target,
+                event.id,
+                &event.local[..12.min(event.local.len())]
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn taken_reports_publishing() {
+        assert_eq!(
+            Action::taken(true, false, true),
+            vec![Action::Create, Action::Publish]
+        );
+        assert_eq!(Action::taken(false, false, false), vec![Action::Update]);
+        assert_eq!(
+            Action::taken(false, true, false),
+            vec![Action::Update, Action::Unpublish]
+        );
+    }
+
+    #[test]
+    fn record_appends() -> anyhow::Result<()> {
+        let source = std::env::temp_dir().join(format!("devtogo-audit-{}", std::process::id()));
+        let event = |action| Event::new(action, "devto", "a.md", "1", None, "local", "remote");
+        record(&source, &[event(Action::Create), event(Action::Publish)])?;
+        record(&source, &[event(Action::Update)])?;
+        let events = events(&source)?;
+        fs::remove_dir_all(&source)?;
+        assert_eq!(
+            events.iter().map(|e| e.action).collect::<Vec<_>>(),
+            vec![Action::Create, Action::Publish, Action::Update]
+        );
+        assert_eq!(events[0].profile, "default");
+        assert!(matches(
+            &events[1],
+            Some("a.md"),
+            Some(Action::Publish),
+            Some("default"),
+            None
+        ));
+        assert!(!matches(&events[1], Some("b.md"), None, None, None));
+        Ok(())
+    }
+}
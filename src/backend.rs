@@ -0,0 +1,211 @@
+use crate::{
+    api::{Article, Client},
+    article::Frontmatter,
+    config::{Config, Kind, Target},
+    hashnode::Hashnode,
+    medium::Medium,
+    oauth,
+};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt};
+
+/// Stands in for credentials in requests printed for debugging
+pub const REDACTED: &str = "<redacted>";
+
+/// A local article ready to be published
+pub struct Post<'a> {
+    pub meta: &'a Frontmatter,
+    /// full file content, including frontmatter
+    pub content: &'a str,
+    /// content following frontmatter
+    pub body: &'a str,
+}
+
+/// An api request publishing a post, as printed by verbose dry runs with credentials redacted
+#[derive(Debug, PartialEq)]
+pub struct Request {
+    pub method: &'static str,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+impl fmt::Display for Request {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        writeln!(f, "{} {}", self.method, self.url)?;
+        for (name, value) in &self.headers {
+            writeln!(f, "{}: {}", name, value)?;
+        }
+        write!(
+            f,
+            "\n{}",
+            serde_json::to_string_pretty(&self.body).map_err(|_| fmt::Error)?
+        )
+    }
+}
+
+/// A service articles can be published to
+#[async_trait]
+pub trait Backend: Send + Sync {
+    /// all articles published by the current account. Services which can't list
+    /// articles return none, leaving devtogo to rely on what it recorded when pushing
+    async fn articles(&self) -> anyhow::Result<Vec<Article>>;
+
+    async fn create(
+        &self,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Article>;
+
+    async fn update(
+        &self,
+        id: &str,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Article>;
+
+    /// remove the article with an id, false for services which can't remove articles
+    async fn remove(
+        &self,
+        _id: &str,
+    ) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    /// the request creating a post, or updating the article with an id, sends
+    fn request(
+        &self,
+        id: Option<&str>,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Request>;
+
+    /// true if articles can be listed
+    fn lists(&self) -> bool {
+        true
+    }
+
+    /// number of requests made again after failing
+    fn retries(&self) -> usize {
+        0
+    }
+
+    /// the markdown this service stores for a post, compared with remote articles to detect changes
+    fn markdown<'a>(
+        &self,
+        post: &Post<'a>,
+    ) -> &'a str {
+        post.content
+    }
+}
+
+#[async_trait]
+impl Backend for Client {
+    async fn articles(&self) -> anyhow::Result<Vec<Article>> {
+        Client::articles(self).await
+    }
+
+    async fn create(
+        &self,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Article> {
+        Client::create(self, &post.meta.input(post.content)).await
+    }
+
+    async fn update(
+        &self,
+        id: &str,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Article> {
+        Client::update(self, id, &post.meta.input(post.content)).await
+    }
+
+    fn request(
+        &self,
+        id: Option<&str>,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Request> {
+        Client::request(self, id, &post.meta.input(post.content))
+    }
+
+    fn retries(&self) -> usize {
+        Client::retries(self)
+    }
+}
+
+/// the backend for a configured target
+pub async fn new(
+    name: &str,
+    target: &Target,
+) -> anyhow::Result<Box<dyn Backend>> {
+    Ok(match target.kind {
+        Kind::Forem => Box::new(Client::new(target, oauth::auth(name, target).await?)?),
+        Kind::Hashnode => Box::new(Hashnode::new(target)?),
+        Kind::Medium => Box::new(Medium::new(target)?),
+    })
+}
+
+/// A connected account and the articles it has published
+pub struct Account {
+    pub backend: Box<dyn Backend>,
+    pub articles: Vec<Article>,
+}
+
+/// The accounts articles are published to a target with, connected as they're first needed
+pub struct Accounts<'a> {
+    config: &'a Config,
+    name: &'a str,
+    target: &'a Target,
+    connected: BTreeMap<Option<String>, Account>,
+}
+
+impl<'a> Accounts<'a> {
+    pub fn new(
+        config: &'a Config,
+        name: &'a str,
+        target: &'a Target,
+    ) -> Self {
+        Accounts {
+            config,
+            name,
+            target,
+            connected: BTreeMap::new(),
+        }
+    }
+
+    /// true if the account for a profile was already connected
+    pub fn connected(
+        &self,
+        profile: Option<&str>,
+    ) -> bool {
+        self.connected.contains_key(&profile.map(String::from))
+    }
+
+    /// number of requests connected accounts made again after failing
+    pub fn retries(&self) -> usize {
+        self.connected
+            .values()
+            .map(|account| account.backend.retries())
+            .sum()
+    }
+
+    /// the account for a profile, or the target's own account when none is given
+    pub async fn get(
+        &mut self,
+        profile: Option<&str>,
+    ) -> anyhow::Result<&Account> {
+        let key = profile.map(String::from);
+        if !self.connected.contains_key(&key) {
+            let target = match profile {
+                Some(profile) => self.target.with_profile(self.config.profile(profile)?),
+                None => self.target.clone(),
+            };
+            let backend = new(self.name, &target).await?;
+            let articles = backend.articles().await?;
+            self.connected
+                .insert(key.clone(), Account { backend, articles });
+        }
+        Ok(&self.connected[&key])
+    }
+}
@@ -0,0 +1,90 @@
+use regex::{Regex, RegexBuilder};
+
+/// Terms which must never be published, i.e. codenames, embargoed product names or internal hostnames
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    terms: Vec<(String, Regex)>,
+}
+
+/// A blocked term found in an article, positioned with 1-based lines and columns
+#[derive(Debug, PartialEq)]
+pub struct Blocked {
+    pub line: usize,
+    pub column: usize,
+    pub term: String,
+}
+
+impl Blocklist {
+    /// terms match regardless of case, as whole words where they start or end with one
+    pub fn new(terms: &[String]) -> anyhow::Result<Blocklist> {
+        let word = |c: Option<char>| c.map(|c| c.is_alphanumeric() || c == '_') == Some(true);
+        let terms = terms
+            .iter()
+            .filter(|term| !term.is_empty())
+            .map(|term| {
+                let pattern = format!(
+                    "{}{}{}",
+                    if word(term.chars().next()) { r"\b" } else { "" },
+                    regex::escape(term),
+                    if word(term.chars().last()) { r"\b" } else { "" }
+                );
+                let regex = RegexBuilder::new(&pattern).case_insensitive(true).build()?;
+                Ok((term.clone(), regex))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Blocklist { terms })
+    }
+
+    /// blocked terms anywhere in an article, frontmatter and code included
+    pub fn find(
+        &self,
+        content: &str,
+    ) -> Vec<Blocked> {
+        let mut blocked = Vec::new();
+        for (n, line) in content.lines().enumerate() {
+            for (term, regex) in &self.terms {
+                for found in regex.find_iter(line) {
+                    blocked.push(Blocked {
+                        line: n + 1,
+                        // in characters, as editors count columns
+                        column: line[..found.start()].chars().count() + 1,
+                        term: term.clone(),
+                    });
+                }
+            }
+        }
+        blocked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_matches_terms_regardless_of_case() -> anyhow::Result<()> {
+        let blocklist = Blocklist::new(&["Falcon".into(), ".corp.internal".into()])?;
+        assert_eq!(
+            blocklist.find("---\ntitle: falcon\n---\nfalconry at db.corp.internal"),
+            vec![
+                Blocked {
+                    line: 2,
+                    column: 8,
+                    term: "Falcon".into()
+                },
+                Blocked {
+                    line: 4,
+                    column: 15,
+                    term: ".corp.internal".into()
+                }
+            ]
+        );
+        assert_eq!(
+            blocklist.find("naïve café falcon")[0].column,
+            12,
+            "columns count characters, not bytes"
+        );
+        assert!(Blocklist::default().find("falcon").is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    article::{self, PublishStatus, Status, Strictness},
+    config::Config,
+    console, state,
+};
+use colored::Colorize;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Widest a column of the board gets, in characters
+const WIDTH: usize = 30;
+
+/// Prints a kanban style overview of articles by their status: idea, in review, approved and
+/// published
+///
+/// Articles without a status are placed by published, as published or approved drafts
+#[derive(StructOpt, Debug)]
+pub struct Board {
+    /// Directory to source markdown files from. Defaults to current working directory, or the
+    /// configured sources
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+}
+
+/// a title shortened to fit a column
+fn fit(title: &str) -> String {
+    if title.chars().count() > WIDTH {
+        format!(
+            "{}…",
+            title.chars().take(WIDTH - 1).collect::<String>().trim_end()
+        )
+    } else {
+        title.into()
+    }
+}
+
+/// columns of titles side by side, each headed by its status and how many it holds
+fn render(columns: &[(Status, Vec<String>)]) -> Vec<String> {
+    let headings = columns
+        .iter()
+        .map(|(status, titles)| format!("{} ({})", status.name().to_uppercase(), titles.len()))
+        .collect::<Vec<_>>();
+    let cells = columns
+        .iter()
+        .map(|(_, titles)| titles.iter().map(|title| fit(title)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let widths = headings
+        .iter()
+        .zip(&cells)
+        .map(|(heading, cells)| {
+            cells
+                .iter()
+                .map(|cell| cell.chars().count())
+                .chain(std::iter::once(heading.len()))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - cell.chars().count())))
+            .collect::<Vec<_>>()
+            .join(" │ ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![
+        row(headings.iter().map(String::as_str).collect()),
+        widths
+            .iter()
+            .map(|width| "─".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("─┼─"),
+    ];
+    let depth = cells.iter().map(Vec::len).max().unwrap_or_default();
+    for n in 0..depth {
+        lines.push(row(cells
+            .iter()
+            .map(|cells| cells.get(n).map(String::as_str).unwrap_or_default())
+            .collect()));
+    }
+    lines
+}
+
+pub fn run(
+    config: &Config,
+    args: Board,
+) -> anyhow::Result<()> {
+    let mut columns = Status::ALL
+        .iter()
+        .map(|status| (*status, Vec::new()))
+        .collect::<Vec<_>>();
+    for root in config.roots(args.source) {
+        for path in article::paths(&root.dir, config) {
+            let mut content = article::read(config, &root.dir, &path)?;
+            if config.title_from_heading {
+                content = article::title_from_heading(&content, false)?;
+            }
+            let key = state::key(&root.dir, &path);
+            let (status, title) =
+                match article::parse(&key, &content, Strictness::Lenient, config.timezone) {
+                    Ok((meta, _, _)) => (
+                        meta.status.unwrap_or(match meta.publish_status() {
+                            PublishStatus::Published => Status::Published,
+                            PublishStatus::Draft => Status::Approved,
+                        }),
+                        meta.title,
+                    ),
+                    // articles which don't parse yet are most likely ideas
+                    Err(_) => (Status::Idea, key),
+                };
+            if let Some((_, titles)) = columns.iter_mut().find(|(s, _)| *s == status) {
+                titles.push(title);
+            }
+        }
+    }
+    let mut lines = render(&columns).into_iter();
+    if let Some(heading) = lines.next() {
+        println!("{}", console::text(&heading).bold());
+    }
+    for line in lines {
+        println!("{}", console::text(&line));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_lays_out_columns() {
+        let columns = vec![
+            (Status::Idea, vec!["Rust in 2030".to_string()]),
+            (Status::InReview, Vec::new()),
+            (
+                Status::Published,
+                vec![
+                    "Why I stopped worrying and learned to love the borrow checker".to_string(),
+                    "Hello".to_string(),
+                ],
+            ),
+        ];
+        assert_eq!(
+            render(&columns),
+            vec![
+                "IDEA (1)     │ IN-REVIEW (0) │ PUBLISHED (2)",
+                "─────────────┼───────────────┼───────────────────────────────",
+                "Rust in 2030 │               │ Why I stopped worrying and le…",
+                "             │               │ Hello",
+            ]
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use crate::api::ApiError;
+
+/// Time a push first pauses for once the api looks to be down, doubled after each pause that
+/// didn't help
+const FIRST_PAUSE: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Pauses a push takes before giving up on an api that stays down
+const MAX_PAUSES: u32 = 4;
+
+/// What a push does after a request
+#[derive(Debug, PartialEq)]
+pub enum Trip {
+    /// carry on
+    Closed,
+    /// wait this long for the api to recover before carrying on
+    Pause(std::time::Duration),
+    /// stop, as the api stayed down
+    Open,
+}
+
+/// Counts requests failing in a row because the api is down rather than because of an article,
+/// so a push pauses during an outage instead of failing every remaining file
+pub struct Breaker {
+    /// failures in a row which trip the breaker, 0 never tripping it
+    threshold: usize,
+    failures: usize,
+    pauses: u32,
+}
+
+/// true if an error means the api is down or unreachable: a 5xx response, a timeout or a
+/// failed connection
+pub fn outage(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(err) = cause.downcast_ref::<ApiError>() {
+            return err.status.is_server_error();
+        }
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return err.is_timeout() || err.is_connect();
+        }
+        false
+    })
+}
+
+impl Breaker {
+    pub fn new(threshold: usize) -> Self {
+        Breaker {
+            threshold,
+            failures: 0,
+            pauses: 0,
+        }
+    }
+
+    /// failures in a row which tripped the breaker
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// account for a request which failed with `failure`, if it did
+    pub fn record(
+        &mut self,
+        failure: Option<&anyhow::Error>,
+    ) -> Trip {
+        match failure {
+            Some(err) if outage(err) => self.failures += 1,
+            // the api answered, so it's up
+            _ => {
+                self.failures = 0;
+                self.pauses = 0;
+                return Trip::Closed;
+            }
+        }
+        if self.threshold == 0 || self.failures < self.threshold {
+            return Trip::Closed;
+        }
+        if self.pauses == MAX_PAUSES {
+            return Trip::Open;
+        }
+        self.failures = 0;
+        self.pauses += 1;
+        Trip::Pause(FIRST_PAUSE * 2_u32.pow(self.pauses - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use reqwest::StatusCode;
+
+    fn error(status: StatusCode) -> anyhow::Error {
+        ApiError {
+            status,
+            message: String::new(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn breaker_backs_off_during_outages() {
+        let down = error(StatusCode::SERVICE_UNAVAILABLE);
+        let mut breaker = Breaker::new(2);
+        assert_eq!(breaker.record(Some(&down)), Trip::Closed);
+        assert_eq!(
+            breaker.record(Some(&down)),
+            Trip::Pause(std::time::Duration::from_secs(30))
+        );
+        // articles the api refused don't count
+        assert_eq!(
+            breaker.record(Some(&error(StatusCode::UNPROCESSABLE_ENTITY))),
+            Trip::Closed
+        );
+        let mut pauses = Vec::new();
+        loop {
+            match breaker.record(Some(&down)) {
+                Trip::Pause(pause) => pauses.push(pause.as_secs()),
+                Trip::Open => break,
+                Trip::Closed => (),
+            }
+        }
+        assert_eq!(pauses, vec![30, 60, 120, 240]);
+        assert!(!outage(&anyhow!("title is too long")));
+        assert_eq!(Breaker::new(0).record(Some(&down)), Trip::Closed);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    api, article,
+    config::{Config, Kind},
+    console,
+    push::{self, Push},
+    recording, settings, state,
+};
+use anyhow::bail;
+use colored::Colorize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+/// Checks that the canonical_url of every article resolves to a page mentioning its title
+///
+/// Flags canonical links left stale by a blog moving domain, without changing anything
+#[derive(StructOpt, Debug)]
+pub struct AuditCanonicals {
+    /// Directory to source markdown files from. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+}
+
+/// Moves canonical urls to a new domain, i.e. after migrating a blog, then pushes
+///
+/// Rewrites the canonical_url of every local file, and canonical_base_url in devtogo.toml,
+/// starting with --from to start with --to instead
+#[derive(StructOpt, Debug)]
+pub struct RewriteCanonicals {
+    /// Url canonical urls start with now, i.e. https://old.blog
+    #[structopt(long)]
+    from: String,
+    /// Url canonical urls should start with, i.e. https://new.blog
+    #[structopt(long)]
+    to: String,
+    #[structopt(flatten)]
+    args: Push,
+}
+
+/// a url moved from one base url to another, if it's under the first
+fn rewrite(
+    url: &str,
+    from: &str,
+    to: &str,
+) -> Option<String> {
+    let from = from.trim_end_matches('/');
+    let rest = url.trim().strip_prefix(from)?;
+    if !(rest.is_empty() || rest.starts_with(['/', '?', '#'])) {
+        return None;
+    }
+    Some(format!("{}{}", to.trim_end_matches('/'), rest))
+}
+
+/// html with the entities titles commonly contain decoded, in lowercase with whitespace collapsed
+fn normalize(html: &str) -> String {
+    let decoded = [
+        ("&amp;", "&"),
+        ("&quot;", "\""),
+        ("&#39;", "'"),
+        ("&#x27;", "'"),
+        ("&apos;", "'"),
+        ("&rsquo;", "’"),
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&nbsp;", " "),
+    ]
+    .iter()
+    .fold(html.to_string(), |html, (entity, text)| {
+        html.replace(entity, text)
+    });
+    decoded
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// true if a page mentions a title
+fn mentions(
+    page: &str,
+    title: &str,
+) -> bool {
+    normalize(page).contains(&normalize(title))
+}
+
+/// why a canonical url is stale, if it is
+async fn stale(
+    url: &str,
+    title: &str,
+) -> Option<String> {
+    let http = match api::http() {
+        Ok(http) => http,
+        Err(err) => return Some(err.to_string()),
+    };
+    let resp = match recording::send(http.get(url)).await {
+        Ok(resp) => resp,
+        Err(err) => return Some(err.to_string()),
+    };
+    let status = resp.status();
+    if status != reqwest::StatusCode::OK {
+        return Some(format!("{} returned {}", url, status));
+    }
+    match resp.text().await {
+        Ok(page) if mentions(&page, title) => None,
+        Ok(_) => Some(format!("{} doesn't mention the title", url)),
+        Err(err) => Some(err.to_string()),
+    }
+}
+
+pub async fn run(
+    config: &Config,
+    args: AuditCanonicals,
+) -> anyhow::Result<()> {
+    let source = config.source(args.source);
+    let mut broken = 0;
+    for path in article::paths(&source, config) {
+        let content = article::render(config, &source, &path, Kind::Forem).await?;
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let (meta, _) = article::extract(file_name.as_ref(), &content)?;
+        let url = match meta.canonical_url.as_deref().map(str::trim) {
+            Some(url) if !url.is_empty() => url,
+            _ => continue,
+        };
+        match stale(url, &meta.title).await {
+            None => println!("{}", push::line(&meta.title, "OK".green())),
+            Some(reason) => {
+                broken += 1;
+                println!("{}", push::line(&meta.title, "STALE".red()));
+                println!("{}", console::text(&format!("  ▶ {}", reason)));
+            }
+        }
+    }
+    if broken > 0 {
+        bail!(
+            "{} article(s) have stale canonical urls.\n  ▶ Point them at where the articles are published now, i.e. with rewrite-canonicals",
+            broken
+        );
+    }
+    Ok(())
+}
+
+pub async fn rewrite_canonicals(
+    config: &Config,
+    config_path: &Path,
+    args: RewriteCanonicals,
+) -> anyhow::Result<()> {
+    let RewriteCanonicals { from, to, args } = args;
+    let source = args.source(config);
+    let dryrun = args.dryrun();
+    let mut config = config;
+    let reloaded;
+    let base = config.canonical_base_url.as_deref();
+    if let Some(base) = base.and_then(|base| rewrite(base, &from, &to)) {
+        if !dryrun {
+            let text = fs::read_to_string(config_path)?;
+            fs::write(
+                config_path,
+                settings::change(&text, "canonical_base_url", &base)?,
+            )?;
+            // articles without a canonical_url of their own are pushed under the new base url,
+            // with flags still taking precedence
+            let mut updated = Config::load(config_path)?;
+            if let (true, Some(version)) = (
+                config.overrides.contains_key("api_version"),
+                &config.api_version,
+            ) {
+                updated.set_api_version(version.clone());
+            }
+            reloaded = updated;
+            config = &reloaded;
+        }
+        println!(
+            "{}",
+            push::line(&config_path.display().to_string(), "REWRITTEN".green())
+        );
+    }
+    let mut rewritten = 0;
+    for path in article::paths(&source, config) {
+        let content = fs::read_to_string(&path)?;
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        // files converted to markdown, i.e. notebooks, have no frontmatter to rewrite
+        let url = match article::extract(file_name.as_ref(), &content) {
+            Ok((meta, _)) => meta.canonical_url,
+            Err(_) => continue,
+        };
+        let url = match url.and_then(|url| rewrite(&url, &from, &to)) {
+            Some(url) => url,
+            None => continue,
+        };
+        if !dryrun {
+            let content = article::set(&content, "canonical_url", &serde_json::to_string(&url)?)?;
+            fs::write(&path, content)?;
+        }
+        println!(
+            "{}",
+            push::line(&state::key(&source, &path), "REWRITTEN".green())
+        );
+        rewritten += 1;
+    }
+    println!("Rewrote canonical urls of {} file(s)", rewritten);
+    push::run(config, args).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrite_moves_urls_under_a_base() {
+        assert_eq!(
+            rewrite(
+                "https://old.blog/posts/hello",
+                "https://old.blog/",
+                "https://new.blog"
+            ),
+            Some("https://new.blog/posts/hello".into())
+        );
+        assert_eq!(
+            rewrite("https://old.blog", "https://old.blog", "https://new.blog/"),
+            Some("https://new.blog".into())
+        );
+        assert_eq!(
+            rewrite(
+                "https://old.blogger.com/hello",
+                "https://old.blog",
+                "https://new.blog"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn mentions_decodes_entities() {
+        let page = "<title>Tom &amp; Jerry&#39;s\n  Guide to   Rust</title>";
+        assert!(mentions(page, "Tom & Jerry's guide to Rust"));
+        assert!(!mentions(page, "Guide to Go"));
+    }
+}
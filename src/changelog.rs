@@ -0,0 +1,144 @@
+use crate::{
+    config::{Config, ReleaseNotes},
+    push::{self, Push},
+};
+use anyhow::anyhow;
+use colored::Colorize;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// Writes a release announcement article from a release's section of a changelog
+#[derive(StructOpt, Debug)]
+pub struct FromChangelog {
+    /// Markdown changelog with a heading per release
+    changelog: PathBuf,
+    /// Version whose release notes to announce, i.e. 1.4.0
+    #[structopt(long)]
+    version: String,
+    /// Push articles once the announcement is written
+    #[structopt(long)]
+    push: bool,
+    #[structopt(flatten)]
+    args: Push,
+}
+
+/// heading level of a markdown heading line
+fn level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level > 0 && line[level..].starts_with(' ') {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+/// the body of a changelog section whose heading names a version, i.e. `## [1.4.0] - 2024-01-01`
+fn section(
+    changelog: &str,
+    version: &str,
+) -> Option<String> {
+    let mut lines = changelog.lines();
+    let heading = lines.by_ref().find_map(|line| {
+        let level = level(line)?;
+        line[level..]
+            .split(|c: char| !(c.is_alphanumeric() || c == '.' || c == '-'))
+            .any(|word| word.trim_start_matches('v') == version)
+            .then_some(level)
+    })?;
+    let body = lines
+        .take_while(|line| !matches!(level(line), Some(level) if level <= heading))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some(body.trim().to_string())
+}
+
+/// json strings and arrays are valid yaml values
+fn quote<T: serde::Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn render(
+    notes: &ReleaseNotes,
+    version: &str,
+    body: &str,
+) -> String {
+    let mut lines = vec![
+        "---".to_string(),
+        format!(
+            "title: {}",
+            quote(&notes.title.replace("{version}", version))
+        ),
+        format!("published: {}", notes.published),
+    ];
+    if !notes.tags.is_empty() {
+        lines.push(format!("tags: {}", quote(&notes.tags.join(", "))));
+    }
+    if let Some(series) = &notes.series {
+        lines.push(format!("series: {}", quote(series)));
+    }
+    lines.push("---".into());
+    format!("{}\n\n{}\n", lines.join("\n"), body)
+}
+
+pub async fn run(
+    config: &Config,
+    args: FromChangelog,
+) -> anyhow::Result<()> {
+    let FromChangelog {
+        changelog,
+        version,
+        push,
+        args,
+    } = args;
+    let body = section(&fs::read_to_string(&changelog)?, &version).ok_or_else(|| {
+        anyhow!(
+            "{} has no section for version {}",
+            changelog.display(),
+            version
+        )
+    })?;
+    let slug = format!("release-{}", version.replace('.', "-"));
+    let path = config.layout.path(&args.source(config), &slug);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, render(&config.release_notes, &version, &body))?;
+    println!(
+        "{}",
+        push::line(&slug, format!("WRITTEN {}", path.display()).green())
+    );
+    if push {
+        push::run(config, args).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str =
+        "# Changelog\n\n## [1.4.0] - 2024-01-01\n\n### Added\n\n* a\n\n## v1.3.0\n\n* b\n";
+
+    #[test]
+    fn section_extracts_releases() {
+        assert_eq!(
+            section(CHANGELOG, "1.4.0").as_deref(),
+            Some("### Added\n\n* a")
+        );
+        assert_eq!(section(CHANGELOG, "1.3.0").as_deref(), Some("* b"));
+        assert_eq!(section(CHANGELOG, "1.4"), None);
+    }
+
+    #[test]
+    fn render_wraps_in_frontmatter() {
+        let notes = ReleaseNotes {
+            tags: vec!["rust".into(), "release".into()],
+            ..ReleaseNotes::default()
+        };
+        assert_eq!(
+            render(&notes, "1.4.0", "* a"),
+            "---\ntitle: \"Release 1.4.0\"\npublished: false\ntags: \"rust, release\"\nseries: \"Release Notes\"\n---\n\n* a\n"
+        );
+    }
+}
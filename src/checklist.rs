@@ -0,0 +1,129 @@
+use crate::article::Frontmatter;
+use serde::{Deserialize, Serialize};
+
+/// Words read per minute, as Forem estimates reading time
+const WORDS_PER_MINUTE: usize = 275;
+
+/// Items articles must pass to be published, checked when they're first published
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Checklist {
+    /// Require a cover_image
+    pub cover_image: bool,
+    /// Require a description
+    pub description: bool,
+    /// Require a canonical_url, set by canonical_base_url for articles without their own
+    pub canonical_url: bool,
+    /// Least number of tags
+    pub min_tags: usize,
+    /// Least reading time, in minutes
+    pub min_reading_time: Option<usize>,
+    /// Most reading time, in minutes
+    pub max_reading_time: Option<usize>,
+}
+
+/// minutes it takes to read an article's body, rounded up
+pub fn reading_time(body: &str) -> usize {
+    let words = body.split_whitespace().count();
+    words.div_ceil(WORDS_PER_MINUTE)
+}
+
+fn set(value: &Option<String>) -> bool {
+    value
+        .as_deref()
+        .map(str::trim)
+        .is_some_and(|v| !v.is_empty())
+}
+
+impl Checklist {
+    /// items an article fails
+    pub fn check(
+        &self,
+        meta: &Frontmatter,
+        body: &str,
+    ) -> Vec<String> {
+        let mut failed = Vec::new();
+        if self.cover_image && !set(&meta.cover_image) {
+            failed.push("no cover_image".to_string());
+        }
+        if self.description && !set(&meta.description) {
+            failed.push("no description".to_string());
+        }
+        if self.canonical_url && !set(&meta.canonical_url) {
+            failed.push("no canonical_url".to_string());
+        }
+        let tags = meta
+            .tags
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .filter(|tag| !tag.trim().is_empty())
+            .count();
+        if tags < self.min_tags {
+            failed.push(format!(
+                "{} tag(s), fewer than the {} required",
+                tags, self.min_tags
+            ));
+        }
+        let minutes = reading_time(body);
+        match (self.min_reading_time, self.max_reading_time) {
+            (Some(min), _) if minutes < min => failed.push(format!(
+                "{} minute read, shorter than {} minutes",
+                minutes, min
+            )),
+            (_, Some(max)) if minutes > max => failed.push(format!(
+                "{} minute read, longer than {} minutes",
+                minutes, max
+            )),
+            _ => (),
+        }
+        failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reading_time_rounds_up() {
+        assert_eq!(reading_time(""), 0);
+        assert_eq!(reading_time("one word"), 1);
+        assert_eq!(reading_time(&"word ".repeat(551)), 3);
+    }
+
+    #[test]
+    fn check_reports_failed_items() {
+        let checklist = Checklist {
+            cover_image: true,
+            description: true,
+            min_tags: 2,
+            max_reading_time: Some(1),
+            ..Checklist::default()
+        };
+        let meta = Frontmatter {
+            description: Some(" ".into()),
+            tags: Some("rust, ".into()),
+            ..Frontmatter::default()
+        };
+        assert_eq!(
+            checklist.check(&meta, &"word ".repeat(300)),
+            vec![
+                "no cover_image",
+                "no description",
+                "1 tag(s), fewer than the 2 required",
+                "2 minute read, longer than 1 minutes"
+            ]
+        );
+        let meta = Frontmatter {
+            cover_image: Some("cover.png".into()),
+            description: Some("all about it".into()),
+            tags: Some("rust, cli".into()),
+            ..Frontmatter::default()
+        };
+        assert!(checklist.check(&meta, "short").is_empty());
+        assert!(Checklist::default()
+            .check(&Frontmatter::default(), "")
+            .is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{
+    api::{self, Article, Client, Comment},
+    config::{Config, Kind, Target},
+    oauth, read,
+};
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+use regex::Regex;
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Read},
+    process::Command,
+};
+use structopt::StructOpt;
+
+/// Characters of a comment quoted in excerpts
+const EXCERPT: usize = 80;
+
+/// Lists comments on articles and replies to them, to triage feedback without the web ui
+#[derive(StructOpt, Debug)]
+pub enum Comments {
+    /// Lists the comments on an article, threaded, with the ids to reply to them by
+    List {
+        /// Url of the article, its username/slug or its id
+        article: String,
+        /// Configured target the article is on. Required when more than one is configured
+        #[structopt(short, long)]
+        target: Option<String>,
+    },
+    /// Replies to a comment, where the instance's api permits posting comments. The reply is
+    /// taken from --body, else from stdin when piped, else written in $EDITOR
+    Reply {
+        /// Id of the comment, as listed by comments list or at the end of its url
+        comment: String,
+        /// Markdown to reply with
+        #[structopt(long)]
+        body: Option<String>,
+        /// Configured target the comment is on. Required when more than one is configured
+        #[structopt(short, long)]
+        target: Option<String>,
+    },
+}
+
+/// a comment's text, without its markup
+pub fn text(html: &str) -> String {
+    let tags = Regex::new(r"<[^>]*>").expect("valid regex");
+    let text = tags
+        .replace_all(html, " ")
+        .replace("&amp;", "&")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// the start of a comment's text, without its markup
+pub fn excerpt(html: &str) -> String {
+    let text = text(html);
+    if text.chars().count() <= EXCERPT {
+        return text;
+    }
+    format!(
+        "{}…",
+        text.chars().take(EXCERPT).collect::<String>().trim_end()
+    )
+}
+
+/// a reply as written in an editor, without the html comments it was prefilled with
+fn written(edited: &str) -> String {
+    let notes = Regex::new(r"(?s)<!--.*?-->").expect("valid regex");
+    notes.replace_all(edited, "").trim().to_string()
+}
+
+/// a reply written in $VISUAL or $EDITOR, prefilled with the comment replied to
+fn edit(parent: &Comment) -> anyhow::Result<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".into());
+    let path = env::temp_dir().join(format!("devtogo-reply-{}.md", parent.id_code));
+    fs::write(
+        &path,
+        format!(
+            "\n<!--\nReplying to {}:\n\n{}\n\nLines within this comment are left out of the reply\n-->\n",
+            parent.user.name,
+            text(&parent.body_html)
+        ),
+    )?;
+    let quote = format!("'{}'", path.display().to_string().replace('\'', "'\\''"));
+    let status = Command::new("sh")
+        .args(["-c", &format!("{} {}", editor, quote)])
+        .status()
+        .map_err(|err| anyhow!("failed to run {}: {}", editor, err));
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+    if !status?.success() {
+        bail!("{} exited with an error, so nothing was posted", editor);
+    }
+    Ok(written(&edited?))
+}
+
+/// print comments and their replies, indented by depth
+fn print(
+    comments: &[Comment],
+    depth: usize,
+) {
+    let indent = "  ".repeat(depth);
+    for comment in comments {
+        println!(
+            "{}{} {} {}",
+            indent,
+            comment.user.name.bold(),
+            comment.id_code.dimmed(),
+            comment
+                .created_at
+                .as_deref()
+                .unwrap_or_default()
+                .get(..10)
+                .unwrap_or_default()
+                .dimmed()
+        );
+        println!("{}  {}", indent, text(&comment.body_html));
+        print(&comment.children, depth + 1);
+    }
+}
+
+fn forem(
+    config: &Config,
+    target: Option<&str>,
+) -> anyhow::Result<(String, Target)> {
+    let (name, target) = config.target(target)?;
+    if target.kind != Kind::Forem {
+        bail!("comments are only supported with Forem targets");
+    }
+    Ok((name, target))
+}
+
+pub async fn run(
+    config: &Config,
+    args: Comments,
+) -> anyhow::Result<()> {
+    match args {
+        Comments::List { article, target } => {
+            let (_, target) = forem(config, target.as_deref())?;
+            let article: Article = api::public(&target, &read::path(&article)?).await?;
+            let comments: Vec<Comment> =
+                api::public(&target, &format!("comments?a_id={}", article.id)).await?;
+            println!(
+                "{} {}",
+                article.title.bold(),
+                format!("({} comment(s))", article.comments_count).dimmed()
+            );
+            print(&comments, 0);
+        }
+        Comments::Reply {
+            comment,
+            body,
+            target,
+        } => {
+            let (name, target) = forem(config, target.as_deref())?;
+            let parent: Comment = api::public(&target, &format!("comments/{}", comment)).await?;
+            let body = match body {
+                Some(body) => body,
+                None if !io::stdin().is_terminal() => {
+                    let mut body = String::new();
+                    io::stdin().read_to_string(&mut body)?;
+                    body
+                }
+                None => edit(&parent)?,
+            };
+            if body.trim().is_empty() {
+                bail!("the reply is empty, so nothing was posted");
+            }
+            let client = Client::new(&target, oauth::auth(&name, &target).await?)?;
+            let reply = client.reply(&parent.id_code, body.trim()).await?;
+            println!(
+                "Replied to {} {}",
+                parent.user.name.bold(),
+                reply.id_code.dimmed()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excerpt_strips_markup() {
+        assert_eq!(
+            excerpt("<p>Great <b>post</b> &amp; thanks</p>\n<p>More</p>"),
+            "Great post & thanks More"
+        );
+        assert_eq!(excerpt(&"word ".repeat(30)).chars().count(), 80);
+    }
+
+    #[test]
+    fn written_leaves_out_notes() {
+        assert_eq!(
+            written("Thanks!\n<!--\nReplying to Ada:\n\nhi\n-->\n"),
+            "Thanks!"
+        );
+        assert_eq!(written("\n<!-- Replying to Ada -->\n"), "");
+    }
+}
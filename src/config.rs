@@ -0,0 +1,925 @@
+use crate::{
+    backend::REDACTED, checklist::Checklist, convert::Converter, cover::Design, dates::Zone,
+    diagrams::Diagrams, duration::Duration, images::Optimize, math::Math, mdx::Mdx, prose::Prose,
+    recording, schema::Schema,
+};
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Name of the target used when none are configured
+pub const DEFAULT_TARGET: &str = "devto";
+
+/// Project settings, read from devtogo.toml
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Config {
+    /// Directory articles are sourced from when none is given with --source. Defaults to the
+    /// current working directory
+    pub source: Option<PathBuf>,
+    /// Url articles are originally published at, i.e. https://example.com/blog. Articles without
+    /// canonical_url frontmatter link to their path under it when published
+    pub canonical_base_url: Option<String>,
+    /// Content roots pushed together, each with its own settings, i.e. posts/ published to a
+    /// personal account and company/ to an organization's. Defaults to a single root, `source`
+    #[serde(default)]
+    pub sources: Vec<Root>,
+    /// Forem instances to publish to, by name
+    #[serde(default)]
+    pub targets: BTreeMap<String, Target>,
+    /// Forem api version to request from targets which don't set their own
+    pub api_version: Option<String>,
+    /// Alternative accounts articles may be published with, by name
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// How articles are laid out in source directories
+    #[serde(default)]
+    pub layout: Layout,
+    /// Languages articles are translated to, the primary language first
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Url source directories are published at, i.e. on GitHub, for serving local images and
+    /// images extracted from notebooks
+    pub images_url: Option<String>,
+    /// Resize and recompress local images before they are published
+    pub optimize_images: Option<Optimize>,
+    /// Replace mermaid and dot code fences with rendered images when publishing
+    pub diagrams: Option<Diagrams>,
+    /// Commands converting other markup languages to markdown, by file extension
+    #[serde(default)]
+    pub converters: BTreeMap<String, Converter>,
+    /// Convert `$..$` and `$$..$$` math to katex liquid tags, or images where they aren't supported,
+    /// when publishing
+    pub math: Option<Math>,
+    /// Replace `[^1]` style footnotes with links to a notes section when publishing
+    #[serde(default)]
+    pub convert_footnotes: bool,
+    /// Demote headings so article bodies start at `##` when publishing, and warn about skipped levels in lint
+    #[serde(default)]
+    pub normalize_headings: bool,
+    /// Take titles of articles without title frontmatter from their first `# heading`, which is
+    /// then left out of their body
+    #[serde(default)]
+    pub title_from_heading: bool,
+    /// Apply smart quotes, dashes and ellipses to prose when publishing
+    #[serde(default)]
+    pub smart_typography: bool,
+    /// Terms articles must never contain, i.e. codenames or internal hostnames. Articles containing
+    /// any are refused by push and reported by lint
+    #[serde(default)]
+    pub blocklist: Vec<String>,
+    /// Fields articles must and may set, and patterns their values must match. Articles breaking
+    /// these rules are refused by push and reported by lint
+    #[serde(default)]
+    pub frontmatter: Schema,
+    /// Least time between updates of an article, i.e. 1h, so automated pushes don't flood
+    /// followers' feeds with edits. Pushes skip articles updated more recently unless forced
+    pub update_cooldown: Option<Duration>,
+    /// Timezone of frontmatter dates written without an offset, i.e. Europe/Berlin, or local for
+    /// this machine's. Defaults to UTC
+    #[serde(default)]
+    pub timezone: Zone,
+    /// Refuse to push articles with images lacking alt text, which lint otherwise only warns about
+    #[serde(default)]
+    pub required_alt_text: bool,
+    /// Refuse to push articles as published unless reviewed_by names someone other than their
+    /// author, so every published article was seen by two people
+    #[serde(default)]
+    pub require_review: bool,
+    /// Items articles must pass to be published, i.e. a cover image and at least one tag.
+    /// Articles failing any are refused by push when they'd first be published
+    #[serde(default)]
+    pub checklist: Checklist,
+    /// Print a notice when a newer devtogo is released, checking at most once a day
+    #[serde(default)]
+    pub update_check: bool,
+    /// Encrypt state and pushed versions kept in .devtogo with the passphrase in
+    /// DEVTOGO_PASSPHRASE, i.e. to keep embargoed drafts private on shared machines and in backups
+    #[serde(default)]
+    pub encrypt_state: bool,
+    /// Commands `lint --check-code` checks code samples with, by fence language, with {file}
+    /// replaced by the path of a file holding a sample
+    #[serde(default)]
+    pub code_checks: BTreeMap<String, String>,
+    /// Dictionaries and style rules `lint --prose` checks articles with
+    #[serde(default)]
+    pub prose: Prose,
+    /// How JSX components in MDX articles are replaced
+    #[serde(default)]
+    pub mdx: Mdx,
+    /// How cover images rendered by `cover` look
+    #[serde(default)]
+    pub cover: Design,
+    /// Directory, within content roots, whose subdirectories each hold the parts of a series named
+    /// after them, i.e. series/building-a-compiler/01-lexer.md. Parts are pushed in order of their
+    /// file names, so number them to order them
+    pub series_dir: Option<String>,
+    /// Tags articles are given by the directories they're in, on top of their own
+    #[serde(default)]
+    pub directory_tags: DirectoryTags,
+    /// Frontmatter for release announcements written by `from-changelog`
+    #[serde(default)]
+    pub release_notes: ReleaseNotes,
+    /// Mail server `--email-report` sends summaries of pushes and syncs through
+    pub smtp: Option<Smtp>,
+    /// Settings overridden by command line flags, and the flag overriding each
+    #[serde(skip)]
+    pub overrides: BTreeMap<String, String>,
+}
+
+/// A mail server and the addresses summaries are sent from and to
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Smtp {
+    /// Host of the mail server, i.e. smtp.example.com
+    pub host: String,
+    /// Port of the mail server, connected to with STARTTLS. Defaults to 587
+    pub port: Option<u16>,
+    /// User to log in as, if the server requires logging in
+    pub username: Option<String>,
+    /// Name of the environment variable holding the user's password. Defaults to
+    /// DEVTOGO_SMTP_PASSWORD
+    pub password_env: Option<String>,
+    /// Address summaries are sent from
+    pub from: String,
+    /// Addresses summaries are sent to
+    pub to: Vec<String>,
+}
+
+impl Smtp {
+    /// the password to log in with, if logging in
+    pub fn password(&self) -> anyhow::Result<Option<String>> {
+        if self.username.is_none() {
+            return Ok(None);
+        }
+        let name = self
+            .password_env
+            .as_deref()
+            .unwrap_or("DEVTOGO_SMTP_PASSWORD");
+        env::var(name).map(Some).map_err(|_| {
+            anyhow!(
+                "Please export a {} env variable with your smtp password",
+                name
+            )
+        })
+    }
+}
+
+/// A directory of articles and defaults for their frontmatter
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Root {
+    /// Directory articles are sourced from
+    pub dir: PathBuf,
+    /// Tags of articles which don't list their own
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Series of articles which aren't part of one
+    pub series: Option<String>,
+    /// Profile articles are published with unless they select one with `api_key_ref`
+    pub profile: Option<String>,
+}
+
+/// Tags derived from directories, merged with the tags articles list themselves
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DirectoryTags {
+    /// Tag articles with the names of the directories they're in, within their content root, i.e.
+    /// rust/ownership.md with rust. Names which aren't valid tags are skipped
+    pub from_names: bool,
+    /// Tags every article in a directory, or its subdirectories, is given, by directory within
+    /// its content root
+    pub always: BTreeMap<String, Vec<String>>,
+}
+
+impl DirectoryTags {
+    /// tags for an article, keyed by its path within its content root
+    pub fn tags(
+        &self,
+        key: &str,
+        layout: Layout,
+    ) -> Vec<String> {
+        let mut dirs = key.split('/').collect::<Vec<_>>();
+        dirs.pop();
+        // a bundle's own directory is named after the article, not a topic
+        if layout == Layout::Bundles {
+            dirs.pop();
+        }
+        let mut tags = Vec::new();
+        if self.from_names {
+            tags.extend(
+                dirs.iter()
+                    .filter(|dir| !dir.is_empty() && dir.chars().all(char::is_alphanumeric))
+                    .map(|dir| dir.to_lowercase()),
+            );
+        }
+        let within = dirs.join("/");
+        for (dir, always) in &self.always {
+            let dir = dir.trim_matches('/');
+            if within == dir || within.starts_with(&format!("{}/", dir)) {
+                tags.extend(always.iter().cloned());
+            }
+        }
+        tags
+    }
+}
+
+/// Frontmatter for release announcements
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ReleaseNotes {
+    /// Article title, with {version} replaced by the version released
+    pub title: String,
+    pub tags: Vec<String>,
+    pub series: Option<String>,
+    pub published: bool,
+}
+
+impl Default for ReleaseNotes {
+    fn default() -> Self {
+        ReleaseNotes {
+            title: "Release {version}".into(),
+            tags: Vec::new(),
+            series: Some("Release Notes".into()),
+            published: false,
+        }
+    }
+}
+
+/// How articles are laid out in a source directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// each article is a markdown file, with images in an images directory next to it
+    #[default]
+    Files,
+    /// each article is a directory named after its slug, holding an index.md and its images.
+    /// Hugo calls these page bundles
+    Bundles,
+}
+
+impl Layout {
+    /// true if a markdown file is an article in this layout
+    pub fn includes(
+        self,
+        path: &Path,
+    ) -> bool {
+        match self {
+            Layout::Files => true,
+            Layout::Bundles => path.file_stem() == Some(OsStr::new("index")),
+        }
+    }
+
+    /// where a new article with a given slug belongs
+    pub fn path(
+        self,
+        source: &Path,
+        slug: &str,
+    ) -> PathBuf {
+        match self {
+            Layout::Files => source.join(format!("{}.md", slug)),
+            Layout::Bundles => source.join(slug).join("index.md"),
+        }
+    }
+
+    /// directory, relative to an article, its images belong in
+    pub fn images(self) -> &'static str {
+        match self {
+            Layout::Files => "images",
+            Layout::Bundles => "",
+        }
+    }
+}
+
+/// An account, other than a target's own, articles can select with `api_key_ref` frontmatter
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Profile {
+    /// Name of the environment variable holding this account's api key
+    pub api_key_env: String,
+}
+
+/// The kind of service a target publishes to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    #[default]
+    Forem,
+    Hashnode,
+    Medium,
+}
+
+/// A publishing service and the account to publish to it with
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct Target {
+    /// Kind of service. Defaults to forem
+    #[serde(default)]
+    pub kind: Kind,
+    /// Root url of the service, i.e. https://dev.to for Forem instances
+    base_url: Option<String>,
+    /// Name of the environment variable holding this service's api key
+    api_key_env: Option<String>,
+    /// Hashnode publication to publish to
+    pub publication_id: Option<String>,
+    /// Forem api version to request, i.e. 1
+    api_version: Option<String>,
+    /// Client id of an OAuth application registered with a Forem instance, enabling `login --oauth`
+    pub oauth_client_id: Option<String>,
+    /// Languages of articles to publish here. Defaults to all
+    languages: Option<Vec<String>>,
+    /// Gzip large article uploads, for Forem instances whose servers accept compressed request
+    /// bodies, i.e. to push long articles faster over slow connections
+    #[serde(default)]
+    pub gzip_requests: bool,
+}
+
+impl Target {
+    pub fn base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .unwrap_or(match self.kind {
+                Kind::Forem => "https://dev.to",
+                Kind::Hashnode => "https://gql.hashnode.com",
+                Kind::Medium => "https://api.medium.com/v1",
+            })
+            .trim_end_matches('/')
+    }
+
+    pub fn api_key_env(&self) -> &str {
+        self.api_key_env.as_deref().unwrap_or(match self.kind {
+            Kind::Forem => "DEVTO_API_KEY",
+            Kind::Hashnode => "HASHNODE_TOKEN",
+            Kind::Medium => "MEDIUM_TOKEN",
+        })
+    }
+
+    /// true if articles in a language are published here. Articles of unknown language are published everywhere
+    pub fn publishes(
+        &self,
+        language: Option<&str>,
+    ) -> bool {
+        match (&self.languages, language) {
+            (Some(languages), Some(language)) => languages.iter().any(|l| l == language),
+            _ => true,
+        }
+    }
+
+    pub fn api_version(&self) -> Option<&str> {
+        self.api_version.as_deref()
+    }
+
+    /// this target, authenticating as a profile's account
+    pub fn with_profile(
+        &self,
+        profile: &Profile,
+    ) -> Target {
+        Target {
+            api_key_env: Some(profile.api_key_env.clone()),
+            // tokens from login belong to the target's own account
+            oauth_client_id: None,
+            ..self.clone()
+        }
+    }
+
+    /// where OAuth device authorization starts
+    pub fn oauth_device_url(&self) -> String {
+        format!("{}/oauth/authorize_device", self.base_url())
+    }
+
+    /// where OAuth tokens are issued
+    pub fn oauth_token_url(&self) -> String {
+        format!("{}/oauth/token", self.base_url())
+    }
+
+    /// root of a Forem instance's api
+    pub fn api_url(&self) -> String {
+        format!("{}/api", self.base_url())
+    }
+
+    pub fn api_key(&self) -> anyhow::Result<String> {
+        if recording::replaying() {
+            return Ok(REDACTED.into());
+        }
+        env::var(self.api_key_env()).map_err(|_| {
+            anyhow!(
+                "Please export a {} env variable.\n  ▶ You can generate one by visiting {}",
+                self.api_key_env(),
+                match self.kind {
+                    Kind::Forem => format!("{}/settings/account", self.base_url()),
+                    Kind::Hashnode => "https://hashnode.com/settings/developer".into(),
+                    Kind::Medium => "https://medium.com/me/settings/security".into(),
+                }
+            )
+        })
+    }
+}
+
+/// text with `${VAR}` replaced by the value of an environment variable, or by `default` in
+/// `${VAR:-default}` when it isn't set. `$${` is left as a literal `${`
+fn expand(
+    text: &str,
+    var: &dyn Fn(&str) -> Option<String>,
+) -> anyhow::Result<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            out.push_str(&rest[..start - 1]);
+            out.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("{} has an unclosed ${{", text))?;
+        let expression = &rest[start + 2..start + end];
+        let (name, default) = match expression.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expression, None),
+        };
+        let value = match (var(name), default) {
+            // like a shell's ${VAR:-default}, a default replaces empty values too
+            (Some(value), Some(default)) if value.is_empty() => default.to_string(),
+            (Some(value), _) => value,
+            (None, Some(default)) => default.to_string(),
+            // variables exported empty on purpose are set
+            (None, None) => bail!("{} is not set", name),
+        };
+        out.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// every string in a config value with environment variables expanded
+fn expand_values(
+    value: &mut toml::Value,
+    key: &str,
+    var: &dyn Fn(&str) -> Option<String>,
+) -> anyhow::Result<()> {
+    match value {
+        toml::Value::String(text) if text.contains("${") => {
+            *text = expand(text, var).map_err(|err| anyhow!("{}: {}", key, err))?;
+        }
+        toml::Value::Array(values) => {
+            for (i, value) in values.iter_mut().enumerate() {
+                expand_values(value, &format!("{}.{}", key, i), var)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (name, value) in table.iter_mut() {
+                let key = if key.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", key, name)
+                };
+                expand_values(value, &key, var)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+impl Config {
+    /// the converter configured for a file's extension
+    pub fn converter(
+        &self,
+        path: &Path,
+    ) -> Option<&Converter> {
+        self.converters.get(path.extension()?.to_str()?)
+    }
+
+    /// the source directory given with --source, or else the configured one
+    pub fn source(
+        &self,
+        source: Option<PathBuf>,
+    ) -> PathBuf {
+        source
+            .or_else(|| self.source.clone())
+            .unwrap_or_else(|| ".".into())
+    }
+
+    /// parse config, with environment variables expanded, along with the keys of settings it
+    /// doesn't know, i.e. typos
+    pub fn parse(text: &str) -> anyhow::Result<(Config, Vec<String>)> {
+        let mut value = toml::from_str::<toml::Value>(text)?;
+        expand_values(&mut value, "", &|name| {
+            env::var_os(name).map(|value| value.to_string_lossy().into_owned())
+        })?;
+        let mut unknown = Vec::new();
+        let config = serde_ignored::deserialize(value, |key| unknown.push(key.to_string()))?;
+        Ok((config, unknown))
+    }
+
+    /// content roots to push. A directory given with --source is pushed alone, with the
+    /// settings of the configured root it matches, if any
+    pub fn roots(
+        &self,
+        source: Option<PathBuf>,
+    ) -> Vec<Root> {
+        match source {
+            Some(dir) => vec![self.root(&dir).cloned().unwrap_or(Root {
+                dir,
+                ..Root::default()
+            })],
+            None if !self.sources.is_empty() => self.sources.clone(),
+            None => vec![Root {
+                dir: self.source(None),
+                ..Root::default()
+            }],
+        }
+    }
+
+    /// the configured root of a source directory, if any
+    pub fn root(
+        &self,
+        dir: &Path,
+    ) -> Option<&Root> {
+        self.sources.iter().find(|root| root.dir == dir)
+    }
+
+    /// the series an article, keyed by its path within its content root, is part of by being in
+    /// a subdirectory of series_dir
+    pub fn directory_series(
+        &self,
+        key: &str,
+    ) -> Option<String> {
+        let within = key.strip_prefix(self.series_dir.as_deref()?.trim_matches('/'))?;
+        let mut dirs = within.strip_prefix('/')?.split('/').collect::<Vec<_>>();
+        dirs.pop();
+        if self.layout == Layout::Bundles {
+            dirs.pop();
+        }
+        let name = dirs
+            .first()
+            .filter(|name| !name.is_empty())?
+            .replace(['-', '_'], " ");
+        let mut chars = name.chars();
+        let first = chars.next()?;
+        Some(first.to_uppercase().chain(chars).collect())
+    }
+
+    /// load config from a file, falling back on defaults when it doesn't exist. Unknown
+    /// settings are warned about rather than silently ignored
+    pub fn load(path: &Path) -> anyhow::Result<Config> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let (config, unknown) = Config::parse(&fs::read_to_string(path)?)
+            .map_err(|err| anyhow!("{} is not valid: {}", path.display(), err))?;
+        for key in unknown {
+            eprintln!(
+                "{}",
+                format!("{} has an unknown setting, {}", path.display(), key).yellow()
+            );
+        }
+        Ok(config)
+    }
+
+    /// request an api version from every target, overriding configured versions
+    pub fn set_api_version(
+        &mut self,
+        version: String,
+    ) {
+        for (name, target) in &mut self.targets {
+            target.api_version = Some(version.clone());
+            self.overrides.insert(
+                format!("targets.{}.api_version", name),
+                "--api-version".into(),
+            );
+        }
+        self.api_version = Some(version);
+        self.overrides
+            .insert("api_version".into(), "--api-version".into());
+    }
+
+    /// configured targets, restricted to `selected` names when provided
+    pub fn targets(
+        &self,
+        selected: &[String],
+    ) -> anyhow::Result<Vec<(String, Target)>> {
+        let targets = if self.targets.is_empty() {
+            vec![(DEFAULT_TARGET.to_string(), Target::default())]
+        } else {
+            self.targets
+                .iter()
+                .map(|(name, target)| (name.clone(), target.clone()))
+                .collect()
+        };
+        let targets = targets
+            .into_iter()
+            .map(|(name, target)| {
+                let api_version = target
+                    .api_version
+                    .clone()
+                    .or_else(|| self.api_version.clone());
+                (
+                    name,
+                    Target {
+                        api_version,
+                        ..target
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        if let Some(unknown) = selected
+            .iter()
+            .find(|name| !targets.iter().any(|(t, _)| &t == name))
+        {
+            bail!(
+                "unknown target {}. Expected one of {}",
+                unknown,
+                targets
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        Ok(targets
+            .into_iter()
+            .filter(|(name, _)| selected.is_empty() || selected.contains(name))
+            .collect())
+    }
+
+    /// a profile named by `api_key_ref` frontmatter
+    pub fn profile(
+        &self,
+        name: &str,
+    ) -> anyhow::Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| {
+            anyhow!(
+                "unknown profile {}. Expected one of {}",
+                name,
+                self.profiles
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+    }
+
+    /// a single target, required to be named when more than one is configured
+    pub fn target(
+        &self,
+        selected: Option<&str>,
+    ) -> anyhow::Result<(String, Target)> {
+        let mut targets =
+            self.targets(&selected.map(String::from).into_iter().collect::<Vec<_>>())?;
+        if targets.len() > 1 {
+            bail!("multiple targets are configured.\n  ▶ Please select one with --target");
+        }
+        Ok(targets.remove(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_defaults_to_devto() -> anyhow::Result<()> {
+        let targets = Config::default().targets(&[])?;
+        assert_eq!(targets, vec![("devto".into(), Target::default())]);
+        assert_eq!(targets[0].1.api_url(), "https://dev.to/api");
+        Ok(())
+    }
+
+    #[test]
+    fn config_parses_targets() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [targets.devto]
+            [targets.community]
+            base_url = "https://community.example.com/"
+            api_key_env = "COMMUNITY_API_KEY"
+            "#,
+        )?;
+        assert_eq!(config.targets(&[])?.len(), 2);
+        let (name, target) = config.target(Some("community"))?;
+        assert_eq!(name, "community");
+        assert_eq!(target.api_url(), "https://community.example.com/api");
+        assert!(config.target(None).is_err());
+        assert!(config.targets(&["nope".into()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn config_api_version_applies_to_targets() -> anyhow::Result<()> {
+        let mut config: Config = toml::from_str(
+            r#"
+            api_version = "1"
+            [targets.devto]
+            [targets.community]
+            api_version = "0"
+            "#,
+        )?;
+        assert_eq!(config.target(Some("devto"))?.1.api_version(), Some("1"));
+        assert_eq!(config.target(Some("community"))?.1.api_version(), Some("0"));
+        config.set_api_version("2".into());
+        assert_eq!(config.target(Some("community"))?.1.api_version(), Some("2"));
+        assert_eq!(Config::default().targets(&[])?[0].1.api_version(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn config_profiles_swap_api_keys() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [targets.devto]
+            oauth_client_id = "abc"
+            [profiles.alice]
+            api_key_env = "ALICE_API_KEY"
+            "#,
+        )?;
+        let (_, target) = config.target(None)?;
+        let alice = target.with_profile(config.profile("alice")?);
+        assert_eq!(alice.api_key_env(), "ALICE_API_KEY");
+        assert_eq!(alice.oauth_client_id, None);
+        assert_eq!(alice.base_url(), target.base_url());
+        assert!(config.profile("bob").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn config_reports_unknown_settings() -> anyhow::Result<()> {
+        let (config, unknown) = Config::parse(
+            r#"
+            smart_typograhpy = true
+            [targets.devto]
+            base_ur = "https://dev.to"
+            "#,
+        )?;
+        assert!(!config.smart_typography);
+        assert_eq!(unknown, vec!["smart_typograhpy", "targets.devto.base_ur"]);
+        Ok(())
+    }
+
+    #[test]
+    fn expand_replaces_env_variables() -> anyhow::Result<()> {
+        let var = |name: &str| match name {
+            "HOST" => Some("example.com".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(
+            expand("https://${HOST}/blog", &var)?,
+            "https://example.com/blog"
+        );
+        assert_eq!(expand("${EMPTY:-posts}/${NOPE:-}", &var)?, "posts/");
+        assert_eq!(expand("prefix${EMPTY}", &var)?, "prefix");
+        assert_eq!(expand("$${HOST} costs $5", &var)?, "${HOST} costs $5");
+        assert!(expand("${NOPE}", &var).is_err());
+        assert!(expand("${HOST", &var).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn config_expands_env_variables() -> anyhow::Result<()> {
+        let (config, _) = Config::parse(
+            r#"
+            source = "${DEVTOGO_TEST_UNSET:-posts}"
+            [targets.devto]
+            "#,
+        )?;
+        assert_eq!(config.source(None), Path::new("posts"));
+        let err = Config::parse(r#"images_url = "${DEVTOGO_TEST_UNSET}""#)
+            .err()
+            .map(|err| err.to_string());
+        assert_eq!(
+            err.as_deref(),
+            Some("images_url: DEVTOGO_TEST_UNSET is not set")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn config_lists_roots() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [[sources]]
+            dir = "posts"
+            [[sources]]
+            dir = "company"
+            tags = ["news"]
+            profile = "org"
+            "#,
+        )?;
+        let roots = config.roots(None);
+        assert_eq!(roots.len(), 2);
+        assert_eq!(roots[1].profile.as_deref(), Some("org"));
+        assert_eq!(config.roots(Some("company".into())), vec![roots[1].clone()]);
+        assert_eq!(
+            config.roots(Some("drafts".into()))[0].tags,
+            Vec::<String>::new()
+        );
+        assert_eq!(Config::default().roots(None)[0].dir, PathBuf::from("."));
+        Ok(())
+    }
+
+    #[test]
+    fn directory_tags_follow_paths() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [directory_tags]
+            from_names = true
+            always = { "company/" = ["teamname"] }
+            "#,
+        )?;
+        let tags = |key: &str, layout: Layout| config.directory_tags.tags(key, layout);
+        assert_eq!(tags("rust/ownership.md", Layout::Files), vec!["rust"]);
+        assert_eq!(
+            tags("company/rust/async.md", Layout::Files),
+            vec!["company", "rust", "teamname"]
+        );
+        assert_eq!(
+            tags("my-posts/hello.md", Layout::Files),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            tags("rust/ownership/index.md", Layout::Bundles),
+            vec!["rust"]
+        );
+        assert_eq!(tags("companyblog/a.md", Layout::Files), vec!["companyblog"]);
+        Ok(())
+    }
+
+    #[test]
+    fn directory_series_names_series_after_directories() -> anyhow::Result<()> {
+        let mut config: Config = toml::from_str(r#"series_dir = "series""#)?;
+        assert_eq!(
+            config.directory_series("series/building-a-compiler/01-lexer.md"),
+            Some("Building a compiler".into())
+        );
+        assert_eq!(config.directory_series("series/01-lexer.md"), None);
+        assert_eq!(config.directory_series("seriesly/a/b.md"), None);
+        assert_eq!(config.directory_series("posts/a/b.md"), None);
+        config.layout = Layout::Bundles;
+        assert_eq!(
+            config.directory_series("series/compilers/01-lexer/index.md"),
+            Some("Compilers".into())
+        );
+        assert_eq!(config.directory_series("series/01-lexer/index.md"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn layout_bundles_articles_in_directories() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(r#"layout = "bundles""#)?;
+        assert_eq!(config.layout, Layout::Bundles);
+        assert!(Layout::Bundles.includes(Path::new("posts/my-post/index.md")));
+        assert!(Layout::Bundles.includes(Path::new("my-post/index.markdown")));
+        assert!(!Layout::Bundles.includes(Path::new("my-post/notes.md")));
+        assert_eq!(
+            Layout::Bundles.path(Path::new("posts"), "my-post"),
+            Path::new("posts").join("my-post").join("index.md")
+        );
+        assert_eq!(
+            Layout::Files.path(Path::new("posts"), "my-post"),
+            Path::new("posts").join("my-post.md")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn target_publishes_languages() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            languages = ["en", "es"]
+            [targets.devto]
+            [targets.es]
+            languages = ["es"]
+            "#,
+        )?;
+        let (_, devto) = config.target(Some("devto"))?;
+        let (_, es) = config.target(Some("es"))?;
+        assert!(devto.publishes(Some("es")));
+        assert!(es.publishes(Some("es")));
+        assert!(!es.publishes(Some("de")));
+        assert!(es.publishes(None));
+        Ok(())
+    }
+
+    #[test]
+    fn config_defaults_depend_on_kind() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [targets.blog]
+            kind = "hashnode"
+            publication_id = "abc"
+            "#,
+        )?;
+        let (_, target) = config.target(None)?;
+        assert_eq!(target.kind, Kind::Hashnode);
+        assert_eq!(target.base_url(), "https://gql.hashnode.com");
+        assert_eq!(target.api_key_env(), "HASHNODE_TOKEN");
+        Ok(())
+    }
+}
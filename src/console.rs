@@ -0,0 +1,80 @@
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Symbols devtogo prints and the ascii they're replaced with by --ascii
+const SYMBOLS: &[(char, &str)] = &[
+    ('▶', ">"),
+    ('…', "..."),
+    ('▁', "_"),
+    ('▂', "."),
+    ('▃', "-"),
+    ('▄', "~"),
+    ('▅', "="),
+    ('▆', "+"),
+    ('▇', "*"),
+    ('█', "#"),
+    ('│', "|"),
+    ('─', "-"),
+    ('┼', "+"),
+    ('•', "*"),
+];
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// prepare the terminal for output, limiting it to ascii when asked to
+pub fn init(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+    // older Windows consoles only understand colors once asked to
+    #[cfg(windows)]
+    {
+        if colored::control::set_virtual_terminal(true).is_err() {
+            colored::control::set_override(false);
+        }
+    }
+}
+
+/// true if output is limited to ascii
+pub fn ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// text with symbols replaced by ascii
+pub fn plain(text: &str) -> Cow<'_, str> {
+    if !text.contains(|c| SYMBOLS.iter().any(|(symbol, _)| *symbol == c)) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match SYMBOLS.iter().find(|(symbol, _)| *symbol == c) {
+            Some((_, ascii)) => out.push_str(ascii),
+            None => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// text as it should be printed, replacing symbols when output is limited to ascii
+pub fn text(text: &str) -> Cow<'_, str> {
+    if ascii() {
+        plain(text)
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_replaces_symbols() {
+        assert_eq!(
+            plain("failed\n  ▶ Re-run with --force…"),
+            "failed\n  > Re-run with --force..."
+        );
+        assert_eq!(plain("▁▄█"), "_~#");
+        assert!(matches!(plain("plain"), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// A command converting files of another markup language to markdown, i.e. pandoc
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Converter {
+    /// Shell command reading a file on stdin and writing markdown, with frontmatter, to stdout.
+    /// Runs in the file's directory
+    pub command: String,
+}
+
+impl Converter {
+    /// markdown for a file
+    pub fn run(
+        &self,
+        path: &Path,
+    ) -> anyhow::Result<String> {
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let output = Command::new("sh")
+            .args(["-c", &self.command])
+            .current_dir(dir)
+            .stdin(Stdio::from(File::open(path)?))
+            .output()
+            .map_err(|err| anyhow!("failed to run {}: {}", self.command, err))?;
+        if !output.status.success() {
+            bail!(
+                "failed to convert {} with {}\n  ▶ {}",
+                path.display(),
+                self.command,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8(output.stdout)?)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_pipes_files_through_commands() -> anyhow::Result<()> {
+        let converter = Converter {
+            command: "sed 's/^title:/title: converted/'".into(),
+        };
+        let markdown = converter.run(Path::new("tests/data/foo.md"))?;
+        assert!(markdown.starts_with("---\ntitle: converted"));
+        let failing = Converter {
+            command: "echo nope >&2; exit 1".into(),
+        };
+        assert!(failing.run(Path::new("tests/data/foo.md")).is_err());
+        Ok(())
+    }
+}
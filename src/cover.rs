@@ -0,0 +1,269 @@
+use crate::{article, config::Config, generate, push};
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+use resvg::{tiny_skia, usvg};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+use yaml_rust::{yaml::Hash, Yaml};
+
+/// Most characters on a line of a title in the default template
+const TITLE_WIDTH: usize = 26;
+
+/// Most lines of a title in the default template
+const TITLE_LINES: usize = 4;
+
+/// Common sans-serif fonts, in order of preference
+const SANS_SERIF: &[&str] = &[
+    "Arial",
+    "Helvetica",
+    "DejaVu Sans",
+    "Liberation Sans",
+    "Noto Sans",
+];
+
+/// A 1000x420 social card, the size dev.to displays covers at
+const TEMPLATE: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="1000" height="420" viewBox="0 0 1000 420">
+  <rect width="1000" height="420" fill="{{ background }}"/>
+  <rect width="20" height="420" fill="{{ accent }}"/>
+  <text font-family="{{ font }}" font-size="56" font-weight="bold" fill="{{ foreground }}">
+{{#each title}}
+    <tspan x="80" y="{{ y }}">{{ text }}</tspan>
+{{/each}}
+  </text>
+  <text x="80" y="370" font-family="{{ font }}" font-size="28" fill="{{ accent }}">{{ author }}{{#each tags}} #{{ this }}{{/each}}</text>
+</svg>
+"#;
+
+/// Renders a social card style cover image for an article and sets it as its cover_image
+///
+/// The image is written next to the article as <name>.cover.png. Configure colors, fonts,
+/// an author and your own svg template under [cover] in devtogo.toml
+#[derive(StructOpt, Debug)]
+pub struct Cover {
+    /// Article to render a cover for
+    file: PathBuf,
+    /// Replace a cover_image the article already has
+    #[structopt(long)]
+    force: bool,
+}
+
+/// How generated covers look
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Design {
+    /// Svg template substituting {{ title }} lines, {{ author }}, {{ tags }} and the colors
+    /// and font below. Defaults to a 1000x420 card
+    pub template: Option<PathBuf>,
+    pub author: Option<String>,
+    pub background: String,
+    pub foreground: String,
+    pub accent: String,
+    /// Font family, resolved against fonts installed on your system
+    pub font: String,
+}
+
+impl Default for Design {
+    fn default() -> Self {
+        Design {
+            template: None,
+            author: None,
+            background: "#171717".into(),
+            foreground: "#ffffff".into(),
+            accent: "#3b49df".into(),
+            font: "sans-serif".into(),
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// a title broken into lines of whole words, ending in an ellipsis when it doesn't fit
+fn wrap(title: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in title.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= TITLE_WIDTH => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    if lines.len() > TITLE_LINES {
+        lines.truncate(TITLE_LINES);
+        if let Some(last) = lines.last_mut() {
+            last.push('…');
+        }
+    }
+    lines
+}
+
+fn data(
+    design: &Design,
+    meta: &article::Frontmatter,
+) -> Yaml {
+    let string = |value: &str| Yaml::String(escape(value));
+    let mut data = Hash::new();
+    let lines = wrap(&meta.title);
+    // vertically center the title above the byline
+    let top = 200 - (lines.len() as i64 - 1) * 34;
+    let title = lines
+        .iter()
+        .enumerate()
+        .map(|(n, line)| {
+            let mut hash = Hash::new();
+            hash.insert(Yaml::String("text".into()), string(line));
+            hash.insert(Yaml::String("y".into()), Yaml::Integer(top + n as i64 * 68));
+            Yaml::Hash(hash)
+        })
+        .collect();
+    data.insert(Yaml::String("title".into()), Yaml::Array(title));
+    let tags = meta
+        .tags
+        .iter()
+        .flat_map(|tags| tags.split(','))
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(string)
+        .collect();
+    data.insert(Yaml::String("tags".into()), Yaml::Array(tags));
+    for (name, value) in [
+        ("author", design.author.as_deref().unwrap_or_default()),
+        ("background", &design.background),
+        ("foreground", &design.foreground),
+        ("accent", &design.accent),
+        ("font", &design.font),
+    ] {
+        data.insert(Yaml::String(name.into()), string(value));
+    }
+    Yaml::Hash(data)
+}
+
+/// rasterize an svg to png
+fn rasterize(svg: &str) -> anyhow::Result<Vec<u8>> {
+    let mut options = usvg::Options::default();
+    let fonts = options.fontdb_mut();
+    fonts.load_system_fonts();
+    // fontdb assumes Arial for sans-serif, which not every system has
+    let installed = |family: &str| {
+        fonts
+            .faces()
+            .any(|face| face.families.iter().any(|(name, _)| name == family))
+    };
+    if let Some(family) = SANS_SERIF.iter().find(|family| installed(family)) {
+        fonts.set_sans_serif_family(*family);
+    }
+    let tree = usvg::Tree::from_str(svg, &options)
+        .map_err(|err| anyhow!("cover template is not valid svg: {}", err))?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| anyhow!("cover template has no size"))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    Ok(pixmap.encode_png()?)
+}
+
+/// frontmatter with `cover_image` set to `image`, and `cover_image_alt` to `alt` unless
+/// already described
+fn set_cover(
+    content: &str,
+    image: &str,
+    alt: &str,
+) -> anyhow::Result<String> {
+    let content = article::set(content, "cover_image", image)?;
+    if article::position(&content, "cover_image_alt").is_some() {
+        return Ok(content);
+    }
+    article::set(&content, "cover_image_alt", &format!("{:?}", alt))
+}
+
+pub fn run(
+    config: &Config,
+    args: Cover,
+) -> anyhow::Result<()> {
+    let Cover { file, force } = args;
+    let content = fs::read_to_string(&file)?;
+    let name = file.to_string_lossy();
+    let (meta, _) = article::extract(&name, &content)?;
+    let image = format!(
+        "{}.cover.png",
+        file.file_stem().unwrap_or_default().to_string_lossy()
+    );
+    match &meta.cover_image {
+        Some(existing) if existing != &image && !force => bail!(
+            "{} already has a cover_image {}\n  ▶ Pass --force to replace it",
+            name,
+            existing
+        ),
+        _ => (),
+    }
+    let template = match &config.cover.template {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|err| anyhow!("unable to read cover template {}: {}", path.display(), err))?,
+        None => TEMPLATE.to_string(),
+    };
+    let svg = generate::render(&template, &[&data(&config.cover, &meta)])?;
+    let path = file.with_file_name(&image);
+    let status = if path.exists() {
+        "UPDATED".yellow()
+    } else {
+        "CREATED".green()
+    };
+    fs::write(&path, rasterize(&svg)?)?;
+    fs::write(&file, set_cover(&content, &image, &meta.title)?)?;
+    println!("{}", push::line(&path.to_string_lossy(), status));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_breaks_titles_between_words() {
+        assert_eq!(
+            wrap("Writing a dev.to tool for the road in Rust"),
+            vec!["Writing a dev.to tool for", "the road in Rust"]
+        );
+        assert_eq!(wrap(&"word ".repeat(30)).len(), TITLE_LINES);
+        assert!(wrap(&"word ".repeat(30))[3].ends_with('…'));
+    }
+
+    #[test]
+    fn set_cover_updates_frontmatter() -> anyhow::Result<()> {
+        assert_eq!(
+            set_cover("---\ntitle: a\n---\nbody", "a.cover.png", "a")?,
+            "---\ntitle: a\ncover_image: a.cover.png\ncover_image_alt: \"a\"\n---\nbody"
+        );
+        assert_eq!(
+            set_cover(
+                "---\ncover_image: old.png\ncover_image_alt: old\n---\n",
+                "a.cover.png",
+                "a"
+            )?,
+            "---\ncover_image: a.cover.png\ncover_image_alt: old\n---\n"
+        );
+        assert!(set_cover("body", "a.cover.png", "a").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn default_template_renders_a_card() -> anyhow::Result<()> {
+        let meta = article::Frontmatter {
+            title: "Rust & <friends>".into(),
+            tags: Some("rust, cli".into()),
+            ..article::Frontmatter::default()
+        };
+        let svg = generate::render(TEMPLATE, &[&data(&Design::default(), &meta)])?;
+        assert!(svg.contains(">Rust &amp; &lt;friends&gt;</tspan>"));
+        assert!(svg.contains(" #rust #cli</text>"));
+        let png = image::load_from_memory(&rasterize(&svg)?)?;
+        assert_eq!((png.width(), png.height()), (1000, 420));
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{
+    config::Config,
+    duration::Duration,
+    email, notify,
+    sync::{self, Sync},
+};
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+/// Stays running, syncing every --interval, for continuous mirroring without cron
+///
+/// The outcome of the last sync is served as JSON on --listen, and each sync is logged as a
+/// line of JSON
+#[derive(StructOpt, Debug)]
+pub struct Daemon {
+    /// Time between syncs, i.e. 15m or 1h
+    #[structopt(long, default_value = "15m")]
+    interval: Duration,
+    /// Address to serve the status of the last sync on
+    #[structopt(long, default_value = "127.0.0.1:7878")]
+    listen: SocketAddr,
+    /// Raise a desktop notification as each sync finishes or fails
+    #[structopt(long)]
+    notify_desktop: bool,
+    /// Email the outcome of each sync to the addresses of the configured [smtp] table
+    #[structopt(long)]
+    email_report: bool,
+    #[structopt(flatten)]
+    args: Sync,
+}
+
+/// What the daemon has done so far, served by its status endpoint
+#[derive(Debug, Default, Serialize)]
+struct Status {
+    runs: usize,
+    failures: usize,
+    last_started_at: Option<DateTime<Utc>>,
+    last_finished_at: Option<DateTime<Utc>>,
+    /// why the last sync failed, if it did
+    last_error: Option<String>,
+    next_run_at: Option<DateTime<Utc>>,
+}
+
+/// log an event as a line of JSON
+fn event(
+    name: &str,
+    fields: Value,
+) {
+    let mut line = json!({ "at": Utc::now(), "event": name });
+    if let (Some(line), Value::Object(fields)) = (line.as_object_mut(), fields) {
+        line.extend(fields);
+    }
+    println!("{}", line);
+}
+
+/// an http response for a request, serving status on / and /status
+fn respond(
+    request: &str,
+    status: &Status,
+) -> String {
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    let (code, body) = match path {
+        "/" | "/status" => ("200 OK", serde_json::to_string(status).unwrap_or_default()),
+        _ => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+    };
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        code,
+        body.len(),
+        body
+    )
+}
+
+/// answer status requests until the process exits
+async fn serve(
+    listener: TcpListener,
+    status: Arc<Mutex<Status>>,
+) {
+    loop {
+        let mut stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                event("status.failed", json!({ "error": err.to_string() }));
+                continue;
+            }
+        };
+        let status = status.clone();
+        tokio::spawn(async move {
+            let mut request = [0; 1024];
+            let read = stream.read(&mut request).await.unwrap_or_default();
+            let response = match status.lock() {
+                Ok(status) => respond(&String::from_utf8_lossy(&request[..read]), &status),
+                Err(_) => return,
+            };
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+pub async fn run(
+    config: &Config,
+    args: Daemon,
+) -> anyhow::Result<()> {
+    let Daemon {
+        interval,
+        listen,
+        notify_desktop,
+        email_report,
+        args,
+    } = args;
+    if email_report && config.smtp.is_none() {
+        bail!("--email-report needs a mail server.\n  ▶ Configure one in an [smtp] table of devtogo.toml");
+    }
+    let status = Arc::new(Mutex::new(Status::default()));
+    let listener = TcpListener::bind(listen).await?;
+    event(
+        "daemon.started",
+        json!({ "interval": interval.to_string(), "listen": listen.to_string() }),
+    );
+    tokio::spawn(serve(listener, status.clone()));
+    loop {
+        let started = Utc::now();
+        event("sync.started", json!({}));
+        let result = sync::run(config, args.clone()).await;
+        let finished = Utc::now();
+        let seconds = (finished - started).num_milliseconds() as f64 / 1000.0;
+        match &result {
+            Ok(()) => event("sync.finished", json!({ "seconds": seconds })),
+            Err(err) => event(
+                "sync.failed",
+                json!({ "seconds": seconds, "error": err.to_string() }),
+            ),
+        }
+        let summary = format!("Synced in {:.1}s", seconds);
+        if notify_desktop {
+            notify::finished("devtogo sync", &summary, &result);
+        }
+        if let (true, Some(smtp)) = (email_report, &config.smtp) {
+            email::report(smtp, "devtogo sync", &summary, &[], &result).await;
+        }
+        if let Ok(mut status) = status.lock() {
+            status.runs += 1;
+            status.last_started_at = Some(started);
+            status.last_finished_at = Some(finished);
+            status.last_error = result.err().map(|err| err.to_string());
+            if status.last_error.is_some() {
+                status.failures += 1;
+            }
+            status.next_run_at = Some(finished + interval.0);
+        }
+        tokio::time::sleep(interval.0.to_std()?).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn respond_serves_status() {
+        let status = Status {
+            runs: 2,
+            failures: 1,
+            last_error: Some("offline".into()),
+            ..Status::default()
+        };
+        let response = respond("GET /status HTTP/1.1\r\nHost: localhost\r\n\r\n", &status);
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(
+            r#"{"runs":2,"failures":1,"last_started_at":null,"last_finished_at":null,"last_error":"offline","next_run_at":null}"#
+        ));
+        assert!(respond("GET /nope HTTP/1.1\r\n\r\n", &status).starts_with("HTTP/1.1 404"));
+    }
+}
@@ -0,0 +1,256 @@
+use anyhow::anyhow;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// The timezone dates written without an offset are in
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Zone {
+    #[default]
+    Utc,
+    /// this machine's own timezone
+    Local,
+    /// a timezone of the IANA database, i.e. Europe/Berlin
+    Named(Tz),
+}
+
+impl FromStr for Zone {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "UTC" | "utc" => Ok(Zone::Utc),
+            "local" => Ok(Zone::Local),
+            name => name.parse().map(Zone::Named).map_err(|_| {
+                anyhow!(
+                    "unknown timezone {}. Expected local or a name like Europe/Berlin",
+                    name
+                )
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Zone::Utc => write!(f, "UTC"),
+            Zone::Local => write!(f, "local"),
+            Zone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl Serialize for Zone {
+    fn serialize<S>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Zone {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Zone {
+    /// a date and time in this timezone, the earlier one when clocks going back repeat it.
+    /// Times skipped by clocks going forward don't exist
+    fn at(
+        self,
+        naive: &NaiveDateTime,
+    ) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Zone::Utc => Some(Utc.from_utc_datetime(naive).fixed_offset()),
+            Zone::Local => Local
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|date| date.fixed_offset()),
+            Zone::Named(tz) => tz
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|date| date.fixed_offset()),
+        }
+    }
+}
+
+/// Layouts of dates with a time but no offset, as written by hand or by other static site
+/// generators
+const NAIVE: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y/%m/%d %H:%M:%S",
+    "%Y/%m/%d %H:%M",
+];
+
+/// Layouts of dates with a time and a numeric offset, i.e. Jekyll's 2024-05-01 10:00:00 +0200
+const OFFSET: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S %z",
+    "%Y-%m-%d %H:%M %z",
+    "%Y-%m-%d %H:%M:%S%z",
+    "%Y-%m-%dT%H:%M:%S%z",
+];
+
+/// Layouts of dates alone, taken as midnight
+const DAYS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d"];
+
+/// Hours off UTC of timezone abbreviations common enough to be written after a date. Ambiguous
+/// ones, like IST, are left out
+const ZONES: &[(&str, i32)] = &[
+    ("UTC", 0),
+    ("GMT", 0),
+    ("Z", 0),
+    ("BST", 1),
+    ("CET", 1),
+    ("CEST", 2),
+    ("EET", 2),
+    ("EEST", 3),
+    ("MSK", 3),
+    ("JST", 9),
+    ("AEST", 10),
+    ("AEDT", 11),
+    ("NZST", 12),
+    ("NZDT", 13),
+    ("EST", -5),
+    ("EDT", -4),
+    ("CST", -6),
+    ("CDT", -5),
+    ("MST", -7),
+    ("MDT", -6),
+    ("PST", -8),
+    ("PDT", -7),
+];
+
+/// a date and time without an offset, in any of the layouts devtogo accepts
+fn naive(value: &str) -> Option<NaiveDateTime> {
+    NAIVE
+        .iter()
+        .find_map(|layout| NaiveDateTime::parse_from_str(value, layout).ok())
+        .or_else(|| {
+            DAYS.iter()
+                .find_map(|layout| NaiveDate::parse_from_str(value, layout).ok())
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// a frontmatter date, in RFC 3339 or any of the layouts people commonly write dates in: a
+/// day alone, a time separated by a space, a numeric offset or a timezone abbreviation like
+/// PST. Dates without an offset are taken to be in `zone`
+pub fn parse(
+    value: &str,
+    zone: Zone,
+) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date);
+    }
+    if let Some(date) = OFFSET
+        .iter()
+        .find_map(|layout| DateTime::parse_from_str(value, layout).ok())
+    {
+        return Some(date);
+    }
+    if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+        return Some(date);
+    }
+    let abbreviated = value.rsplit_once(' ').and_then(|(rest, abbreviation)| {
+        ZONES
+            .iter()
+            .find(|(name, _)| abbreviation.eq_ignore_ascii_case(name))
+            .map(|(_, hours)| (rest, hours))
+    });
+    match abbreviated {
+        Some((rest, hours)) => FixedOffset::east_opt(hours * 3600)?
+            .from_local_datetime(&naive(rest)?)
+            .single(),
+        None => zone.at(&naive(value)?),
+    }
+}
+
+/// a frontmatter date in RFC 3339, as Forem expects, if it can be parsed
+pub fn normalize(
+    value: &str,
+    zone: Zone,
+) -> Option<String> {
+    parse(value, zone).map(|date| date.to_rfc3339())
+}
+
+/// an RFC 3339 timestamp, i.e. when an article was published, in this machine's timezone, or
+/// the timestamp as it is when it can't be read
+pub fn local(timestamp: &str) -> String {
+    match DateTime::parse_from_rfc3339(timestamp) {
+        Ok(date) => date
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        Err(_) => timestamp.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_layouts_are_normalized() {
+        for (value, expected) in [
+            ("2024-05-01T10:00:00+02:00", "2024-05-01T10:00:00+02:00"),
+            ("2024-05-01", "2024-05-01T00:00:00+00:00"),
+            ("2024-05-01 10:00", "2024-05-01T10:00:00+00:00"),
+            ("2024/05/01 10:00:30", "2024-05-01T10:00:30+00:00"),
+            ("2024-05-01 10:00:00 +0200", "2024-05-01T10:00:00+02:00"),
+            ("2024-05-01 10:00 PST", "2024-05-01T10:00:00-08:00"),
+            ("2024-05-01 10:00 utc", "2024-05-01T10:00:00+00:00"),
+            ("Wed, 01 May 2024 10:00:00 GMT", "2024-05-01T10:00:00+00:00"),
+        ] {
+            assert_eq!(
+                normalize(value, Zone::Utc).as_deref(),
+                Some(expected),
+                "{}",
+                value
+            );
+        }
+        for value in ["...", "yesterday", "2024-13-01", "2024-05-01 10:00 XYZ"] {
+            assert_eq!(normalize(value, Zone::Utc), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn dates_without_an_offset_are_in_the_default_zone() -> anyhow::Result<()> {
+        let berlin: Zone = "Europe/Berlin".parse()?;
+        assert_eq!(
+            normalize("2024-05-01 10:00", berlin).as_deref(),
+            Some("2024-05-01T10:00:00+02:00")
+        );
+        assert_eq!(
+            normalize("2024-01-01", berlin).as_deref(),
+            Some("2024-01-01T00:00:00+01:00")
+        );
+        // explicit offsets win
+        assert_eq!(
+            normalize("2024-05-01 10:00 PST", berlin).as_deref(),
+            Some("2024-05-01T10:00:00-08:00")
+        );
+        // skipped by clocks going forward
+        assert_eq!(normalize("2024-03-31 02:30", berlin), None);
+        assert!("Mars/Olympus".parse::<Zone>().is_err());
+        assert_eq!(local("soon"), "soon");
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    article,
+    config::Config,
+    search,
+    state::{self, State},
+};
+use colored::Colorize;
+use std::{collections::HashSet, path::PathBuf};
+use structopt::StructOpt;
+
+/// Words in a row compared between articles
+const SHINGLE: usize = 3;
+
+/// Flags pairs of articles with nearly the same body, across local files and your articles on
+/// Forem targets, i.e. posts created twice under different titles by a messy migration
+///
+/// Remote articles are compared as cached by the last sync, or fetched first with --refresh
+#[derive(StructOpt, Debug)]
+pub struct Dedupe {
+    /// Directory to source markdown files from. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Least share of passages two articles must have in common to be flagged, from 0 to 1
+    #[structopt(long, default_value = "0.8")]
+    threshold: f64,
+    /// Fetch articles from Forem targets before comparing, rather than comparing those cached
+    #[structopt(long)]
+    refresh: bool,
+}
+
+/// An article compared with others
+struct Item {
+    /// where the article is, its file or its target and url
+    location: String,
+    title: String,
+    shingles: HashSet<String>,
+}
+
+/// runs of words in a text, ignoring case and punctuation
+fn shingles(text: &str) -> HashSet<String> {
+    let words = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    if words.len() < SHINGLE {
+        return Some(words.join(" "))
+            .filter(|s| !s.is_empty())
+            .into_iter()
+            .collect();
+    }
+    words.windows(SHINGLE).map(|run| run.join(" ")).collect()
+}
+
+/// share of the passages of two articles they have in common
+fn similarity(
+    a: &HashSet<String>,
+    b: &HashSet<String>,
+) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// pairs of items at least as similar as a threshold, most similar first
+fn duplicates(
+    items: &[Item],
+    threshold: f64,
+) -> Vec<(f64, &Item, &Item)> {
+    let mut pairs = Vec::new();
+    for (n, a) in items.iter().enumerate() {
+        for b in &items[n + 1..] {
+            let similarity = similarity(&a.shingles, &b.shingles);
+            if similarity >= threshold {
+                pairs.push((similarity, a, b));
+            }
+        }
+    }
+    pairs.sort_by(|(a, ..), (b, ..)| b.total_cmp(a));
+    pairs
+}
+
+pub async fn run(
+    config: &Config,
+    args: Dedupe,
+) -> anyhow::Result<()> {
+    let Dedupe {
+        source,
+        threshold,
+        refresh,
+    } = args;
+    let source = config.source(source);
+    let targets = search::targets(config)?;
+    if refresh {
+        search::refresh(&source, &targets).await?;
+    }
+    let mut items = Vec::new();
+    let mut synced = 0;
+    for (name, _) in &targets {
+        let state = State::load(&source, name)?;
+        for article in search::cached(&source, name)? {
+            // a file and the article it was pushed as are the same article
+            if state.key_for(&article.id).is_some() {
+                synced += 1;
+                continue;
+            }
+            items.push(Item {
+                location: format!("{} {}", name, article.url),
+                title: article.title,
+                shingles: shingles(article::body(&article.body)),
+            });
+        }
+    }
+    for path in article::paths(&source, config) {
+        let content = article::read(config, &source, &path)?;
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let title = article::extract(file_name.as_ref(), &content)
+            .map(|(meta, _)| meta.title)
+            .unwrap_or_default();
+        items.push(Item {
+            location: state::key(&source, &path),
+            title,
+            shingles: shingles(article::body(&content)),
+        });
+    }
+    let pairs = duplicates(&items, threshold);
+    for (similarity, a, b) in &pairs {
+        println!(
+            "{}  {} {}",
+            format!("{:>3.0}%", similarity * 100.0).yellow().bold(),
+            a.title.bold(),
+            a.location.dimmed()
+        );
+        println!("      {} {}", b.title.bold(), b.location.dimmed());
+    }
+    if pairs.is_empty() {
+        println!("No duplicates among {} article(s)", items.len() + synced);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similarity_compares_passages() {
+        let a = shingles("Ownership is Rust's most unique feature, and it enables memory safety.");
+        let b = shingles("Ownership is Rust's most unique feature and it enables memory safety!");
+        let c = shingles("Lifetimes describe how long references are valid.");
+        assert_eq!(similarity(&a, &b), 1.0);
+        assert_eq!(similarity(&a, &c), 0.0);
+        assert_eq!(similarity(&shingles(""), &shingles("")), 0.0);
+    }
+
+    #[test]
+    fn duplicates_are_sorted_by_similarity() {
+        let item = |title: &str, body: &str| Item {
+            location: format!("{}.md", title),
+            title: title.into(),
+            shingles: shingles(body),
+        };
+        let items = vec![
+            item("a", "one two three four five six"),
+            item("b", "one two three four five seven"),
+            item("c", "one two three four five six"),
+            item("d", "something else entirely"),
+        ];
+        let pairs = duplicates(&items, 0.5);
+        assert_eq!(
+            pairs
+                .iter()
+                .map(|(_, a, b)| (a.title.as_str(), b.title.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("a", "c"), ("a", "b"), ("b", "c")]
+        );
+    }
+}
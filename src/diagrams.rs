@@ -0,0 +1,299 @@
+use crate::{config::Config, state};
+use anyhow::{anyhow, bail};
+use base64::{engine::general_purpose::URL_SAFE, Engine};
+use flate2::{write::ZlibEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io::Write, path::Path, process::Command};
+
+/// Code fence languages rendered as diagrams, and what kroki calls them
+const LANGUAGES: &[(&str, &str)] = &[("mermaid", "mermaid"), ("dot", "graphviz")];
+
+/// Ways of rendering diagrams
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Renderer {
+    /// Link to images a kroki server renders on request
+    Kroki,
+    /// Render with locally installed tools, publishing images at `images_url`
+    Local,
+}
+
+/// Settings for replacing mermaid and dot code fences with rendered images, which dev.to
+/// can't draw itself
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Diagrams {
+    pub renderer: Renderer,
+    /// Kroki server to render with
+    pub kroki_url: String,
+    /// Image format, i.e. png or svg
+    pub format: String,
+    /// Commands rendering diagrams locally, by fence language, with {input} and {output}
+    /// replaced by file paths
+    pub commands: BTreeMap<String, String>,
+}
+
+impl Default for Diagrams {
+    fn default() -> Self {
+        Diagrams {
+            renderer: Renderer::Kroki,
+            kroki_url: "https://kroki.io".into(),
+            format: "png".into(),
+            commands: vec![
+                (
+                    "mermaid".into(),
+                    "mmdc --input {input} --output {output}".into(),
+                ),
+                ("dot".into(), "dot -T{format} {input} -o {output}".into()),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+}
+
+/// A diagram's code fence, spanning 0-based lines `start..=end`
+#[derive(Debug, PartialEq)]
+struct Fence {
+    start: usize,
+    end: usize,
+    language: &'static str,
+    source: String,
+}
+
+/// closed diagram fences outside of frontmatter and other code
+fn fences(content: &str) -> Vec<Fence> {
+    let mut fences = Vec::new();
+    let mut open: Option<(&str, usize, Option<&'static str>)> = None;
+    let all = content.split('\n').collect::<Vec<_>>();
+    let mut lines = all.iter().enumerate();
+    if content.starts_with("---") {
+        lines.next();
+        for (_, line) in &mut lines {
+            if line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+    for (n, line) in lines {
+        let trimmed = line.trim();
+        match open {
+            None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                let marker = &trimmed[..3];
+                let info = trimmed.trim_start_matches(['`', '~']).trim();
+                let language = LANGUAGES
+                    .iter()
+                    .map(|(language, _)| *language)
+                    .find(|language| info.split_whitespace().next() == Some(*language));
+                open = Some((marker, n, language));
+            }
+            Some((marker, start, language)) if trimmed.starts_with(marker) => {
+                if let Some(language) = language {
+                    fences.push(Fence {
+                        start,
+                        end: n,
+                        language,
+                        source: all[start + 1..n].join("\n"),
+                    });
+                }
+                open = None;
+            }
+            _ => (),
+        }
+    }
+    fences
+}
+
+/// a url kroki renders a diagram at, encoding its source in the url
+fn kroki(
+    settings: &Diagrams,
+    fence: &Fence,
+) -> anyhow::Result<String> {
+    let name = LANGUAGES
+        .iter()
+        .find(|(language, _)| *language == fence.language)
+        .map(|(_, name)| *name)
+        .unwrap_or(fence.language);
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(fence.source.as_bytes())?;
+    Ok(format!(
+        "{}/{}/{}/{}",
+        settings.kroki_url.trim_end_matches('/'),
+        name,
+        settings.format,
+        URL_SAFE.encode(encoder.finish()?)
+    ))
+}
+
+/// render a diagram with a local command into `image`, unless it's been rendered before
+fn local(
+    settings: &Diagrams,
+    fence: &Fence,
+    image: &Path,
+) -> anyhow::Result<()> {
+    if image.exists() {
+        return Ok(());
+    }
+    let command = settings.commands.get(fence.language).ok_or_else(|| {
+        anyhow!(
+            "no command configured to render {} diagrams\n  ▶ Add one under [diagrams.commands] in devtogo.toml",
+            fence.language
+        )
+    })?;
+    let input = std::env::temp_dir().join(format!(
+        "devtogo-{}.{}",
+        &state::hash(&fence.source)[..8],
+        fence.language
+    ));
+    fs::write(&input, &fence.source)?;
+    if let Some(dir) = image.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let quote = |path: &Path| format!("'{}'", path.display().to_string().replace('\'', "'\\''"));
+    let command = command
+        .replace("{input}", &quote(&input))
+        .replace("{output}", &quote(image))
+        .replace("{format}", &settings.format);
+    let output = Command::new("sh")
+        .args(["-c", &command])
+        .output()
+        .map_err(|err| anyhow!("failed to run {}: {}", command, err));
+    let _ = fs::remove_file(&input);
+    let output = output?;
+    if !output.status.success() || !image.exists() {
+        bail!(
+            "failed to render a {} diagram with {}\n  ▶ {}",
+            fence.language,
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// replace diagram code fences in the article at `path` with images of them
+pub fn render(
+    config: &Config,
+    source: &Path,
+    path: &Path,
+    content: &str,
+) -> anyhow::Result<String> {
+    let settings = match &config.diagrams {
+        Some(settings) => settings,
+        None => return Ok(content.to_string()),
+    };
+    let fences = fences(content);
+    if fences.is_empty() {
+        return Ok(content.to_string());
+    }
+    let mut lines = content.split('\n').map(String::from).collect::<Vec<_>>();
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let dir = path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(config.layout.images());
+    for fence in fences.iter().rev() {
+        let url = match settings.renderer {
+            Renderer::Kroki => kroki(settings, fence)?,
+            Renderer::Local => {
+                let images_url = match &config.images_url {
+                    Some(url) => url.trim_end_matches('/'),
+                    None => bail!(
+                        "{} has diagrams, which need to be published somewhere to be displayed.\n  ▶ Set images_url in devtogo.toml to the url your source directory is published at",
+                        path.display()
+                    ),
+                };
+                let image = dir.join(format!(
+                    "{}-{}.{}",
+                    stem,
+                    &state::hash(&fence.source)[..8],
+                    settings.format
+                ));
+                local(settings, fence, &image)?;
+                format!("{}/{}", images_url, state::key(source, &image))
+            }
+        };
+        lines.splice(
+            fence.start..=fence.end,
+            Some(format!("![{} diagram]({})", fence.language, url)),
+        );
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn fences_finds_diagrams_outside_other_code() {
+        let content = "---\ntitle: a\n---\n```mermaid\ngraph TD\n  A-->B\n```\n~~~markdown\n```dot\ndigraph {}\n```\n~~~\n```dot\ndigraph {}";
+        assert_eq!(
+            fences(content),
+            vec![Fence {
+                start: 3,
+                end: 6,
+                language: "mermaid",
+                source: "graph TD\n  A-->B".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn kroki_encodes_sources_in_urls() -> anyhow::Result<()> {
+        let fence = Fence {
+            start: 0,
+            end: 2,
+            language: "dot",
+            source: "digraph { a -> b }".into(),
+        };
+        let url = kroki(&Diagrams::default(), &fence)?;
+        let encoded = url
+            .strip_prefix("https://kroki.io/graphviz/png/")
+            .unwrap_or_default();
+        let mut source = String::new();
+        ZlibDecoder::new(URL_SAFE.decode(encoded)?.as_slice()).read_to_string(&mut source)?;
+        assert_eq!(source, fence.source);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn render_replaces_fences_with_images() -> anyhow::Result<()> {
+        let source = std::env::temp_dir().join("devtogo-diagrams-test");
+        let _ = fs::remove_dir_all(&source);
+        let config = Config {
+            images_url: Some("https://example.com/blog/".into()),
+            diagrams: Some(Diagrams {
+                renderer: Renderer::Local,
+                commands: vec![("dot".into(), "cp {input} {output}".into())]
+                    .into_iter()
+                    .collect(),
+                ..Diagrams::default()
+            }),
+            ..Config::default()
+        };
+        let rendered = render(
+            &config,
+            &source,
+            &source.join("post.md"),
+            "before\n```dot\ndigraph {}\n```\nafter",
+        )?;
+        let name = format!("post-{}.png", &state::hash("digraph {}")[..8]);
+        assert_eq!(
+            rendered,
+            format!(
+                "before\n![dot diagram](https://example.com/blog/images/{})\nafter",
+                name
+            )
+        );
+        assert_eq!(
+            fs::read_to_string(source.join("images").join(name))?,
+            "digraph {}"
+        );
+        fs::remove_dir_all(&source)?;
+        Ok(())
+    }
+}
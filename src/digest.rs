@@ -0,0 +1,274 @@
+use crate::{
+    api::{self, Client, Comment},
+    comments::excerpt,
+    config::{Config, Kind},
+    console,
+    duration::Duration,
+    oauth, read, state,
+    stats::{self, Snapshot},
+};
+use anyhow::bail;
+use chrono::{DateTime, Local, Utc};
+use colored::Colorize;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Summarizes activity on your published articles: new comments, and reactions and views gained
+/// since snapshots recorded by stats --record
+#[derive(StructOpt, Debug)]
+pub struct Digest {
+    /// Directory whose .devtogo directory snapshots are kept in. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Configured target to summarize. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+    /// Summarize activity within this long, i.e. 7d
+    #[structopt(long, default_value = "7d")]
+    since: Duration,
+    /// Print the digest as markdown, i.e. for the body of an email
+    #[structopt(long)]
+    markdown: bool,
+}
+
+/// Activity on an article
+#[derive(Debug, Default)]
+struct Activity {
+    title: String,
+    url: String,
+    reactions: i64,
+    views: i64,
+    /// the snapshot metrics are compared with, if any were recorded
+    baseline: Option<Snapshot>,
+    /// authors and excerpts of new comments, oldest first
+    comments: Vec<(String, String)>,
+}
+
+impl Activity {
+    fn quiet(&self) -> bool {
+        let grown = self
+            .baseline
+            .as_ref()
+            .is_some_and(|b| self.reactions != b.reactions || self.views != b.views);
+        self.comments.is_empty() && !grown
+    }
+}
+
+/// comments and their replies made since a point in time, oldest first
+fn new_comments(
+    comments: &[Comment],
+    since: DateTime<Utc>,
+) -> Vec<(String, String)> {
+    let mut found = Vec::new();
+    let mut pending = comments.iter().collect::<Vec<_>>();
+    while let Some(comment) = pending.pop() {
+        pending.extend(&comment.children);
+        let created_at = comment
+            .created_at
+            .as_deref()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok());
+        match created_at {
+            Some(created_at) if created_at >= since => found.push((
+                created_at,
+                comment.user.name.clone(),
+                excerpt(&comment.body_html),
+            )),
+            _ => (),
+        }
+    }
+    found.sort_by_key(|(created_at, ..)| *created_at);
+    found
+        .into_iter()
+        .map(|(_, author, text)| (author, text))
+        .collect()
+}
+
+/// a change in a metric, with its current value
+fn change(
+    name: &str,
+    now: i64,
+    then: i64,
+) -> String {
+    format!("**{:+}** {} ({} in total)", now - then, name, now)
+}
+
+/// the digest of activity on articles, as markdown
+fn markdown(
+    since: DateTime<Utc>,
+    activity: &[Activity],
+) -> String {
+    let mut out = vec![format!(
+        "# Activity since {}",
+        since.with_timezone(&Local).format("%Y-%m-%d")
+    )];
+    for article in activity.iter().filter(|article| !article.quiet()) {
+        out.push(format!("\n## [{}]({})\n", article.title, article.url));
+        if let Some(baseline) = &article.baseline {
+            let mut noted = String::new();
+            if baseline.recorded_at > since {
+                noted = format!(
+                    ", since {}",
+                    baseline
+                        .recorded_at
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d")
+                );
+            }
+            out.push(format!(
+                "- {}{}",
+                change("reactions", article.reactions, baseline.reactions),
+                noted
+            ));
+            out.push(format!(
+                "- {}{}",
+                change("views", article.views, baseline.views),
+                noted
+            ));
+        }
+        if !article.comments.is_empty() {
+            out.push(format!("- {} new comment(s)", article.comments.len()));
+            for (author, text) in &article.comments {
+                out.push(format!("  - **{}**: {}", author, text));
+            }
+        }
+    }
+    if activity.iter().all(Activity::quiet) {
+        out.push("\nNo new comments, reactions or views.".into());
+    }
+    out.join("\n")
+}
+
+pub async fn run(
+    config: &Config,
+    args: Digest,
+) -> anyhow::Result<()> {
+    let Digest {
+        source,
+        target,
+        since,
+        markdown: as_markdown,
+    } = args;
+    let (name, target) = config.target(target.as_deref())?;
+    if target.kind != Kind::Forem {
+        bail!("digests are only supported with Forem targets");
+    }
+    let since = Utc::now() - since.0;
+    let path = config.source(source).join(state::DIR).join(stats::DATABASE);
+    let db = if path.exists() {
+        Some(stats::open(&path)?)
+    } else {
+        None
+    };
+    let client = Client::new(&target, oauth::auth(&name, &target).await?)?;
+    let mut activity = Vec::new();
+    for article in client.articles().await? {
+        if !article.published {
+            continue;
+        }
+        let comments = if article.comments_count > 0 {
+            let comments: Vec<Comment> =
+                api::public(&target, &format!("comments?a_id={}", article.id)).await?;
+            new_comments(&comments, since)
+        } else {
+            Vec::new()
+        };
+        let baseline = match &db {
+            Some(db) => stats::baseline(db, &name, &article.id, since)?,
+            None => None,
+        };
+        activity.push(Activity {
+            title: article.title,
+            url: article.url,
+            reactions: article.public_reactions_count,
+            views: article.page_views_count,
+            baseline,
+            comments,
+        });
+    }
+    let digest = markdown(since, &activity);
+    if as_markdown {
+        println!("{}", digest);
+        return Ok(());
+    }
+    println!("{}", console::text(&read::render(&digest)));
+    if activity.iter().any(|article| article.baseline.is_none()) {
+        println!(
+            "{}",
+            "\nRecord snapshots with stats --record to see reactions and views gained".dimmed()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::User;
+
+    #[test]
+    fn new_comments_include_recent_replies() -> anyhow::Result<()> {
+        let comment = |name: &str, at: &str, children| Comment {
+            created_at: Some(at.into()),
+            body_html: format!("<p>hi from <b>{}</b> &amp; co</p>", name),
+            user: User {
+                name: name.into(),
+                ..User::default()
+            },
+            children,
+            ..Comment::default()
+        };
+        let comments = vec![comment(
+            "old",
+            "2024-01-01T00:00:00Z",
+            vec![
+                comment("late", "2024-01-09T00:00:00Z", Vec::new()),
+                comment("new", "2024-01-08T00:00:00Z", Vec::new()),
+            ],
+        )];
+        let since = DateTime::parse_from_rfc3339("2024-01-07T00:00:00Z")?.with_timezone(&Utc);
+        assert_eq!(
+            new_comments(&comments, since),
+            vec![
+                ("new".to_string(), "hi from new & co".to_string()),
+                ("late".to_string(), "hi from late & co".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn markdown_skips_quiet_articles() -> anyhow::Result<()> {
+        let since = DateTime::parse_from_rfc3339("2024-01-07T12:00:00Z")?.with_timezone(&Utc);
+        let snapshot = |reactions, views| Snapshot {
+            recorded_at: since,
+            title: String::new(),
+            views,
+            reactions,
+            comments: 0,
+        };
+        let activity = vec![
+            Activity {
+                title: "Busy".into(),
+                url: "https://dev.to/a/busy".into(),
+                reactions: 12,
+                views: 300,
+                baseline: Some(snapshot(10, 100)),
+                comments: vec![("Ada".into(), "Great post".into())],
+            },
+            Activity {
+                title: "Quiet".into(),
+                reactions: 5,
+                views: 50,
+                baseline: Some(snapshot(5, 50)),
+                ..Activity::default()
+            },
+        ];
+        let digest = markdown(since, &activity);
+        assert!(digest.contains(
+            "## [Busy](https://dev.to/a/busy)\n\n- **+2** reactions (12 in total)\n- **+200** views (300 in total)\n- 1 new comment(s)\n  - **Ada**: Great post"
+        ));
+        assert!(!digest.contains("Quiet"));
+        assert!(markdown(since, &activity[1..]).ends_with("No new comments, reactions or views."));
+        Ok(())
+    }
+}
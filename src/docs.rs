@@ -0,0 +1,210 @@
+use anyhow::bail;
+use std::{fs, path::PathBuf};
+use structopt::{
+    clap::{App, AppSettings, ArgGroup, ErrorKind},
+    StructOpt,
+};
+
+/// Width help text is wrapped at, so generated docs don't depend on the terminal
+const WIDTH: usize = 100;
+
+/// Writes a man page or markdown reference for every subcommand, generated from their definitions
+#[derive(StructOpt, Debug)]
+#[structopt(group = ArgGroup::with_name("format").required(true))]
+pub struct Docs {
+    /// Write a man page, devtogo.1
+    #[structopt(long, group = "format")]
+    man: bool,
+    /// Write a markdown page per subcommand
+    #[structopt(long, group = "format")]
+    markdown: bool,
+    /// Directory to write docs to. Defaults to printing them
+    #[structopt(short, long)]
+    out: Option<PathBuf>,
+}
+
+/// the help text of a command, i.e. ["hook", "install"], as printed by --help
+fn help(
+    app: &App,
+    command: &[String],
+) -> anyhow::Result<String> {
+    let args = std::iter::once("devtogo")
+        .chain(command.iter().map(String::as_str))
+        .chain(std::iter::once("--help"));
+    match app.clone().get_matches_from_safe(args) {
+        Err(err) if err.kind == ErrorKind::HelpDisplayed => Ok(err.message.trim_end().into()),
+        Err(err) => Err(err.into()),
+        Ok(_) => bail!("no help for devtogo {}", command.join(" ")),
+    }
+}
+
+/// subcommands listed in help text, leaving out help itself
+fn subcommands(help: &str) -> Vec<String> {
+    help.split("SUBCOMMANDS:\n")
+        .nth(1)
+        .unwrap_or_default()
+        .lines()
+        .take_while(|line| line.starts_with("    "))
+        .filter(|line| !line.starts_with("     "))
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|name| *name != "help")
+        .map(String::from)
+        .collect()
+}
+
+/// every command, starting with devtogo itself, and its help text
+fn commands(app: &App) -> anyhow::Result<Vec<(Vec<String>, String)>> {
+    let mut commands = Vec::new();
+    let mut pending = vec![Vec::new()];
+    while let Some(command) = pending.pop() {
+        let text = help(app, &command)?;
+        for name in subcommands(&text).into_iter().rev() {
+            let mut sub = command.clone();
+            sub.push(name);
+            pending.push(sub);
+        }
+        commands.push((command, text));
+    }
+    Ok(commands)
+}
+
+/// name of a command, as typed
+fn title(command: &[String]) -> String {
+    std::iter::once("devtogo")
+        .chain(command.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// escape text for roff, so lines aren't mistaken for requests
+fn roff(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with(['.', '\'']) {
+                format!("\\&{}", line)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// a man page with a section per command
+fn man(commands: &[(Vec<String>, String)]) -> String {
+    let about = commands
+        .first()
+        .and_then(|(_, help)| help.lines().nth(1))
+        .unwrap_or_default();
+    let mut out = format!(
+        ".TH DEVTOGO 1 \"\" \"devtogo {}\" \"User Commands\"\n.SH NAME\ndevtogo \\- {}\n",
+        env!("CARGO_PKG_VERSION"),
+        roff(about)
+    );
+    for (command, help) in commands {
+        out.push_str(&format!(
+            ".SH \"{}\"\n.nf\n{}\n.fi\n",
+            title(command).to_uppercase(),
+            roff(help)
+        ));
+    }
+    out
+}
+
+/// a markdown page for a command, linking to pages of its subcommands
+fn markdown(
+    command: &[String],
+    help: &str,
+) -> String {
+    let mut out = format!("# {}\n\n```text\n{}\n```\n", title(command), help);
+    let subcommands = subcommands(help);
+    if !subcommands.is_empty() {
+        out.push_str("\n## Subcommands\n\n");
+        for name in subcommands {
+            let mut sub = command.to_vec();
+            sub.push(name);
+            out.push_str(&format!(
+                "* [{}]({}.md)\n",
+                title(&sub),
+                title(&sub).replace(' ', "-")
+            ));
+        }
+    }
+    out
+}
+
+pub fn run(
+    args: Docs,
+    app: App,
+) -> anyhow::Result<()> {
+    let app = app
+        .global_setting(AppSettings::ColorNever)
+        .set_term_width(WIDTH);
+    let Docs {
+        man: as_man,
+        markdown: as_markdown,
+        out,
+    } = args;
+    let commands = commands(&app)?;
+    let pages = if as_man {
+        vec![("devtogo.1".to_string(), man(&commands))]
+    } else if as_markdown {
+        commands
+            .iter()
+            .map(|(command, help)| {
+                (
+                    format!("{}.md", title(command).replace(' ', "-")),
+                    markdown(command, help),
+                )
+            })
+            .collect()
+    } else {
+        bail!("pass --man or --markdown");
+    };
+    match out {
+        Some(dir) => {
+            fs::create_dir_all(&dir)?;
+            for (name, page) in &pages {
+                fs::write(dir.join(name), page)?;
+            }
+            println!("Wrote {} page(s) to {}", pages.len(), dir.display());
+        }
+        None => print!(
+            "{}",
+            pages
+                .into_iter()
+                .map(|(_, page)| page)
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subcommands_are_read_from_help() {
+        let help = "devtogo\n\nUSAGE:\n    devtogo [SUBCOMMAND]\n\nSUBCOMMANDS:\n    help    Prints this message\n    hook    Manages git hooks which check content\n            before it leaves your machine\n    push    Uploads local markdown files";
+        assert_eq!(subcommands(help), vec!["hook", "push"]);
+    }
+
+    #[test]
+    fn roff_escapes_requests() {
+        assert_eq!(roff(".foo\n--bar\\"), "\\&.foo\n\\-\\-bar\\e");
+    }
+
+    #[test]
+    fn markdown_links_subcommands() {
+        assert_eq!(
+            markdown(
+                &["hook".into()],
+                "devtogo-hook\n\nSUBCOMMANDS:\n    install    Installs a git hook"
+            ),
+            "# devtogo hook\n\n```text\ndevtogo-hook\n\nSUBCOMMANDS:\n    install    Installs a git hook\n```\n\n## Subcommands\n\n* [devtogo hook install](devtogo-hook-install.md)\n"
+        );
+    }
+}
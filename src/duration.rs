@@ -0,0 +1,103 @@
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// Units a duration can be written in, and their length in seconds
+const UNITS: &[(char, i64)] = &[
+    ('w', 7 * 24 * 60 * 60),
+    ('d', 24 * 60 * 60),
+    ('h', 60 * 60),
+    ('m', 60),
+    ('s', 1),
+];
+
+/// A span of time written like 90m, 1d or 1h30m
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Duration(pub chrono::Duration);
+
+impl FromStr for Duration {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut seconds = 0;
+        let mut number = String::new();
+        for c in s.trim().chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            let unit = UNITS
+                .iter()
+                .find(|(unit, _)| *unit == c)
+                .map(|(_, seconds)| seconds)
+                .ok_or_else(|| anyhow!("{} is not a duration, i.e. 1h30m or 2d", s))?;
+            if number.is_empty() {
+                bail!("{} is not a duration, i.e. 1h30m or 2d", s);
+            }
+            seconds += number.parse::<i64>()? * unit;
+            number.clear();
+        }
+        if !number.is_empty() || seconds == 0 {
+            bail!("{} is not a duration, i.e. 1h30m or 2d", s);
+        }
+        Ok(Duration(chrono::Duration::seconds(seconds)))
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let mut rest = self.0.num_seconds();
+        if rest == 0 {
+            return f.write_str("0s");
+        }
+        for (unit, seconds) in UNITS {
+            if rest >= *seconds {
+                write!(f, "{}{}", rest / seconds, unit)?;
+                rest %= seconds;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_parse_and_display() -> anyhow::Result<()> {
+        let duration: Duration = "1h30m".parse()?;
+        assert_eq!(duration.0, chrono::Duration::minutes(90));
+        assert_eq!(duration.to_string(), "1h30m");
+        assert_eq!("2d".parse::<Duration>()?.0, chrono::Duration::days(2));
+        for invalid in &["", "1", "h", "1x", "0s"] {
+            assert!(invalid.parse::<Duration>().is_err());
+        }
+        Ok(())
+    }
+}
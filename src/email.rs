@@ -0,0 +1,106 @@
+use crate::config::Smtp;
+use colored::Colorize;
+use lettre::{
+    message::header::ContentType, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+
+/// Port mail servers accept submissions on with STARTTLS
+const SUBMISSION_PORT: u16 = 587;
+
+/// the subject and body of a summary of a command's run
+fn message(
+    command: &str,
+    summary: &str,
+    failures: &[String],
+    result: &anyhow::Result<()>,
+) -> (String, String) {
+    let subject = match result {
+        Ok(()) => format!("{}: {}", command, summary),
+        Err(err) => format!(
+            "{} failed: {}",
+            command,
+            err.to_string().lines().next().unwrap_or_default()
+        ),
+    };
+    let mut body = vec![summary.to_string()];
+    if !failures.is_empty() {
+        body.push(String::new());
+        body.push("Failures:".into());
+        body.extend(failures.iter().map(|failure| format!("- {}", failure)));
+    }
+    if let Err(err) = result {
+        body.push(String::new());
+        body.push(err.to_string());
+    }
+    (subject, body.join("\n") + "\n")
+}
+
+/// email a summary of a command's run, with its failures, to the configured addresses
+async fn send(
+    smtp: &Smtp,
+    command: &str,
+    summary: &str,
+    failures: &[String],
+    result: &anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let (subject, body) = message(command, summary, failures, result);
+    let mut email = Message::builder().from(smtp.from.parse()?).subject(subject);
+    for to in &smtp.to {
+        email = email.to(to.parse()?);
+    }
+    let email = email.header(ContentType::TEXT_PLAIN).body(body)?;
+    let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)?
+        .port(smtp.port.unwrap_or(SUBMISSION_PORT));
+    if let (Some(username), Some(password)) = (&smtp.username, smtp.password()?) {
+        transport = transport.credentials(Credentials::new(username.clone(), password));
+    }
+    transport.build().send(email).await?;
+    Ok(())
+}
+
+/// email a summary of a command's run, only warning when it can't be sent so the run's own
+/// outcome isn't masked
+pub async fn report(
+    smtp: &Smtp,
+    command: &str,
+    summary: &str,
+    failures: &[String],
+    result: &anyhow::Result<()>,
+) {
+    if let Err(err) = send(smtp, command, summary, failures, result).await {
+        eprintln!(
+            "{} couldn't email a summary to {}: {}",
+            "warning:".yellow(),
+            smtp.to.join(", "),
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn message_lists_failures() {
+        let failures = vec!["posts/a.md on devto: blocked term".to_string()];
+        assert_eq!(
+            message("devtogo push", "1 created, 1 failed", &failures, &Ok(())),
+            (
+                "devtogo push: 1 created, 1 failed".to_string(),
+                "1 created, 1 failed\n\nFailures:\n- posts/a.md on devto: blocked term\n"
+                    .to_string()
+            )
+        );
+        let (subject, body) = message(
+            "devtogo sync",
+            "Synced in 2.0s",
+            &[],
+            &Err(anyhow!("offline\n  ▶ try again")),
+        );
+        assert_eq!(subject, "devtogo sync failed: offline");
+        assert!(body.ends_with("\n\noffline\n  ▶ try again\n"));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::duration::Duration;
+
+/// Articles Forem lets an account create or update within a window of time
+const FOREM_WRITES: usize = 10;
+
+/// Seconds Forem counts writes within
+const FOREM_WINDOW: i64 = 30;
+
+/// Seconds a request is assumed to take when none were timed
+const ASSUMED_LATENCY: f64 = 1.0;
+
+/// Api requests and uploads a real push would make, estimated by --dryrun
+#[derive(Debug, Default, PartialEq)]
+pub struct Estimate {
+    /// requests listing an account's articles
+    pub lists: usize,
+    /// requests creating articles
+    pub creates: usize,
+    /// requests updating articles
+    pub updates: usize,
+    /// requests publishing articles --atomic held back as drafts
+    pub publishes: usize,
+    /// bytes of article json uploaded
+    pub bytes: usize,
+    /// creates, updates and publishes sent to Forem targets, which rate limit them
+    pub forem_writes: usize,
+}
+
+impl Estimate {
+    /// api requests a real push would make
+    pub fn requests(&self) -> usize {
+        self.lists + self.creates + self.updates + self.publishes
+    }
+
+    /// how long a real push would take, every request taking as long as those timed on average
+    /// and writes to Forem paced by its rate limit
+    pub fn duration(
+        &self,
+        timed: usize,
+        latency: std::time::Duration,
+    ) -> Duration {
+        let per_request = if timed == 0 {
+            ASSUMED_LATENCY
+        } else {
+            latency.as_secs_f64() / timed as f64
+        };
+        let sending = (self.requests() as f64 * per_request).ceil() as i64;
+        let throttled = (self.forem_writes.saturating_sub(1) / FOREM_WRITES) as i64 * FOREM_WINDOW;
+        Duration(chrono::Duration::seconds(sending.max(throttled)))
+    }
+
+    /// lines describing the estimate
+    pub fn summary(
+        &self,
+        timed: usize,
+        latency: std::time::Duration,
+        max_requests: Option<usize>,
+    ) -> Vec<String> {
+        let mut lines = vec![format!(
+            "A real push would make {} api request(s): {} listing, {} creating, {} updating and {} publishing articles, uploading {}",
+            self.requests(),
+            self.lists,
+            self.creates,
+            self.updates,
+            self.publishes,
+            size(self.bytes)
+        )];
+        lines.push(format!(
+            "  ▶ It would take about {}{}",
+            self.duration(timed, latency),
+            if self.forem_writes > FOREM_WRITES {
+                format!(
+                    ", as Forem allows {} writes every {}s",
+                    FOREM_WRITES, FOREM_WINDOW
+                )
+            } else {
+                String::new()
+            }
+        ));
+        if let Some(max) = max_requests.filter(|max| *max > 0) {
+            lines.push(format!(
+                "  ▶ With --max-requests {}, it would take {} run(s)",
+                max,
+                self.requests().div_ceil(max).max(1)
+            ));
+        }
+        lines
+    }
+}
+
+/// a number of bytes made readable
+fn size(bytes: usize) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_paces_forem_writes() {
+        let estimate = Estimate {
+            lists: 1,
+            creates: 25,
+            forem_writes: 25,
+            ..Estimate::default()
+        };
+        // 26 requests at 100ms take 3s, but 25 writes span three windows
+        let latency = std::time::Duration::from_millis(100);
+        assert_eq!(estimate.duration(1, latency).to_string(), "1m");
+        let estimate = Estimate {
+            lists: 2,
+            updates: 3,
+            ..Estimate::default()
+        };
+        assert_eq!(
+            estimate
+                .duration(2, std::time::Duration::from_secs(4))
+                .to_string(),
+            "10s"
+        );
+        assert_eq!(estimate.duration(0, latency).to_string(), "5s");
+    }
+
+    #[test]
+    fn summary_counts_runs() {
+        let estimate = Estimate {
+            lists: 1,
+            creates: 2,
+            updates: 2,
+            bytes: 2048,
+            ..Estimate::default()
+        };
+        assert_eq!(
+            estimate.summary(0, std::time::Duration::ZERO, Some(2)),
+            vec![
+                "A real push would make 5 api request(s): 1 listing, 2 creating, 2 updating and 0 publishing articles, uploading 2.0 KB",
+                "  ▶ It would take about 5s",
+                "  ▶ With --max-requests 2, it would take 3 run(s)",
+            ]
+        );
+    }
+}
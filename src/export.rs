@@ -0,0 +1,319 @@
+use crate::{
+    api::{self, Article, Client},
+    article,
+    config::{Config, Kind},
+    images, liquid, oauth, push,
+};
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use structopt::StructOpt;
+
+/// Static site generator exported content is laid out for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Hugo,
+    Zola,
+    Jekyll,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hugo" => Ok(Format::Hugo),
+            "zola" => Ok(Format::Zola),
+            "jekyll" => Ok(Format::Jekyll),
+            other => Err(anyhow!("expected hugo, zola or jekyll but got {}", other)),
+        }
+    }
+}
+
+/// Exports dev.to articles as content for a static site generator
+#[derive(StructOpt, Debug)]
+pub struct Export {
+    /// Static site generator to lay content out for
+    #[structopt(long, possible_values = &["hugo", "zola", "jekyll"])]
+    format: Format,
+    /// Directory to write content to, i.e. content/posts
+    #[structopt(short, long)]
+    out: PathBuf,
+    /// Include unpublished drafts
+    #[structopt(long)]
+    drafts: bool,
+    /// Leave dev.to liquid tags as is rather than converting them to plain markdown
+    #[structopt(long)]
+    keep_liquid: bool,
+    /// Download images referenced by articles into an images directory next to each file
+    #[structopt(long)]
+    localize_images: bool,
+    /// Configured target to export from. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+}
+
+/// json strings and arrays are valid yaml and toml values alike
+fn quote<T: serde::Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn published_at(article: &Article) -> Option<DateTime<Utc>> {
+    article
+        .published_at
+        .as_deref()
+        .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// path, relative to the output directory, an article is exported to
+fn path(
+    format: Format,
+    article: &Article,
+) -> PathBuf {
+    match format {
+        Format::Hugo => Path::new(&article.file_stem()).join("index.md"),
+        Format::Zola => format!("{}.md", article.file_stem()).into(),
+        Format::Jekyll => format!(
+            "{}-{}.md",
+            published_at(article)
+                .unwrap_or_else(Utc::now)
+                .format("%Y-%m-%d"),
+            article.file_stem()
+        )
+        .into(),
+    }
+}
+
+/// frontmatter in the dialect of a static site generator
+fn frontmatter(
+    format: Format,
+    article: &Article,
+) -> String {
+    let date = published_at(article);
+    let mut lines = vec![];
+    match format {
+        Format::Hugo => {
+            lines.push(format!("title: {}", quote(&article.title)));
+            if let Some(date) = date {
+                lines.push(format!("date: {}", date.format("%Y-%m-%dT%H:%M:%SZ")));
+            }
+            lines.push(format!("draft: {}", !article.published));
+            if !article.description.is_empty() {
+                lines.push(format!("description: {}", quote(&article.description)));
+            }
+            if !article.tag_list.is_empty() {
+                lines.push(format!("tags: {}", quote(&article.tag_list)));
+            }
+            if let Some(url) = &article.canonical_url {
+                lines.push(format!("canonicalURL: {}", quote(url)));
+            }
+            if let Some(image) = &article.cover_image {
+                lines.push(format!("images: [{}]", quote(image)));
+            }
+            format!("---\n{}\n---\n", lines.join("\n"))
+        }
+        Format::Zola => {
+            lines.push(format!("title = {}", quote(&article.title)));
+            if let Some(date) = date {
+                lines.push(format!("date = {}", date.format("%Y-%m-%dT%H:%M:%SZ")));
+            }
+            lines.push(format!("draft = {}", !article.published));
+            if !article.description.is_empty() {
+                lines.push(format!("description = {}", quote(&article.description)));
+            }
+            if !article.tag_list.is_empty() {
+                lines.push(format!(
+                    "\n[taxonomies]\ntags = {}",
+                    quote(&article.tag_list)
+                ));
+            }
+            let extra = article
+                .canonical_url
+                .iter()
+                .map(|url| format!("canonical_url = {}", quote(url)))
+                .chain(
+                    article
+                        .cover_image
+                        .iter()
+                        .map(|image| format!("cover_image = {}", quote(image))),
+                )
+                .collect::<Vec<_>>();
+            if !extra.is_empty() {
+                lines.push(format!("\n[extra]\n{}", extra.join("\n")));
+            }
+            format!("+++\n{}\n+++\n", lines.join("\n"))
+        }
+        Format::Jekyll => {
+            lines.push("layout: post".to_string());
+            lines.push(format!("title: {}", quote(&article.title)));
+            if let Some(date) = date {
+                lines.push(format!("date: {}", date.format("%Y-%m-%d %H:%M:%S %z")));
+            }
+            if !article.published {
+                lines.push("published: false".to_string());
+            }
+            if !article.description.is_empty() {
+                lines.push(format!("description: {}", quote(&article.description)));
+            }
+            if !article.tag_list.is_empty() {
+                lines.push(format!("tags: {}", quote(&article.tag_list)));
+            }
+            if let Some(url) = &article.canonical_url {
+                lines.push(format!("canonical_url: {}", quote(url)));
+            }
+            if let Some(image) = &article.cover_image {
+                lines.push(format!("image: {}", quote(image)));
+            }
+            format!("---\n{}\n---\n", lines.join("\n"))
+        }
+    }
+}
+
+/// an article's content converted for a static site generator
+fn render(
+    format: Format,
+    article: &Article,
+    keep_liquid: bool,
+) -> String {
+    let body = article::body(&article.body_markdown);
+    format!(
+        "{}{}",
+        frontmatter(format, article),
+        if keep_liquid {
+            body.to_string()
+        } else {
+            liquid::to_markdown(body)
+        }
+    )
+}
+
+pub async fn run(
+    config: &Config,
+    args: Export,
+) -> anyhow::Result<()> {
+    let Export {
+        format,
+        out,
+        drafts,
+        keep_liquid,
+        localize_images,
+        target,
+    } = args;
+    let (name, target) = config.target(target.as_deref())?;
+    if target.kind != Kind::Forem {
+        bail!("export is only supported with Forem targets");
+    }
+    let client = Client::new(&target, oauth::auth(&name, &target).await?)?;
+    let http = api::http()?;
+    for article in client
+        .articles()
+        .await?
+        .iter()
+        .filter(|a| a.published || drafts)
+    {
+        let path = out.join(path(format, article));
+        let dir = path.parent().unwrap_or(&out);
+        fs::create_dir_all(dir)?;
+        let mut content = render(format, article, keep_liquid);
+        if localize_images {
+            content = images::localize(
+                &http,
+                &content,
+                dir,
+                match format {
+                    Format::Hugo => "",
+                    Format::Zola | Format::Jekyll => "images",
+                },
+            )
+            .await?;
+        }
+        fs::write(&path, content)?;
+        println!(
+            "{}",
+            push::line(
+                &article.title,
+                format!("{} {}", "EXPORTED".green(), path.display())
+            )
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article() -> Article {
+        Article {
+            title: "Hello \"world\"".into(),
+            published: true,
+            published_at: Some("2021-03-04T05:06:07.890Z".into()),
+            tag_list: vec!["rust".into(), "cli".into()],
+            slug: "hello-world-1a2b".into(),
+            body_markdown: "---\ntitle: Hello\n---\n# hi".into(),
+            ..Article::default()
+        }
+    }
+
+    #[test]
+    fn path_follows_format_layout() {
+        let article = article();
+        assert_eq!(
+            path(Format::Hugo, &article),
+            Path::new("hello-world-1a2b").join("index.md")
+        );
+        assert_eq!(
+            path(Format::Zola, &article),
+            PathBuf::from("hello-world-1a2b.md")
+        );
+        assert_eq!(
+            path(Format::Jekyll, &article),
+            PathBuf::from("2021-03-04-hello-world-1a2b.md")
+        );
+    }
+
+    #[test]
+    fn render_hugo() {
+        assert_eq!(
+            render(Format::Hugo, &article(), false),
+            "---\ntitle: \"Hello \\\"world\\\"\"\ndate: 2021-03-04T05:06:07Z\ndraft: false\ntags: [\"rust\",\"cli\"]\n---\n# hi"
+        );
+    }
+
+    #[test]
+    fn render_zola() {
+        assert_eq!(
+            render(Format::Zola, &article(), false),
+            "+++\ntitle = \"Hello \\\"world\\\"\"\ndate = 2021-03-04T05:06:07Z\ndraft = false\n\n[taxonomies]\ntags = [\"rust\",\"cli\"]\n+++\n# hi"
+        );
+    }
+
+    #[test]
+    fn render_jekyll() {
+        assert_eq!(
+            render(Format::Jekyll, &article(), false),
+            "---\nlayout: post\ntitle: \"Hello \\\"world\\\"\"\ndate: 2021-03-04 05:06:07 +0000\ntags: [\"rust\",\"cli\"]\n---\n# hi"
+        );
+    }
+
+    #[test]
+    fn render_converts_liquid_unless_kept() {
+        let article = Article {
+            body_markdown: "{% github a/b %}".into(),
+            ..article()
+        };
+        assert!(render(Format::Zola, &article, false).ends_with("[a/b](https://github.com/a/b)"));
+        assert!(render(Format::Zola, &article, true).ends_with("{% github a/b %}"));
+    }
+
+    #[test]
+    fn format_parses() {
+        assert_eq!("zola".parse::<Format>().ok(), Some(Format::Zola));
+        assert!("gatsby".parse::<Format>().is_err());
+    }
+}
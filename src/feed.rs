@@ -0,0 +1,146 @@
+use crate::{
+    api::{self, Article},
+    config::{Config, Kind},
+    duration::Duration,
+};
+use anyhow::bail;
+use colored::Colorize;
+use serde::Serialize;
+use structopt::StructOpt;
+
+/// Lists trending or latest articles published to dev.to, i.e. to research topics and tags
+#[derive(StructOpt, Debug)]
+pub struct Feed {
+    /// Configured target to browse. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+    /// Only list articles with this tag
+    #[structopt(long)]
+    tag: Option<String>,
+    /// List the most popular articles published within this long, i.e. 7d
+    #[structopt(long, conflicts_with = "latest")]
+    top: Option<Duration>,
+    /// List the newest articles rather than trending ones
+    #[structopt(long)]
+    latest: bool,
+    /// Number of articles to list
+    #[structopt(long, default_value = "20")]
+    limit: usize,
+    /// Print articles as JSON
+    #[structopt(long)]
+    json: bool,
+}
+
+/// An article as printed with --json
+#[derive(Debug, Serialize)]
+struct Entry<'a> {
+    title: &'a str,
+    author: &'a str,
+    username: &'a str,
+    tags: &'a [String],
+    reactions: i64,
+    comments: i64,
+    url: &'a str,
+    published_at: Option<&'a str>,
+}
+
+impl<'a> From<&'a Article> for Entry<'a> {
+    fn from(article: &'a Article) -> Self {
+        Entry {
+            title: &article.title,
+            author: &article.user.name,
+            username: &article.user.username,
+            tags: &article.tag_list,
+            reactions: article.public_reactions_count,
+            comments: article.comments_count,
+            url: &article.url,
+            published_at: article.published_at.as_deref(),
+        }
+    }
+}
+
+/// the api path listing articles asked for
+fn path(
+    tag: Option<&str>,
+    top: Option<Duration>,
+    latest: bool,
+    limit: usize,
+) -> String {
+    let mut path = format!(
+        "{}?per_page={}",
+        if latest {
+            "articles/latest"
+        } else {
+            "articles"
+        },
+        limit
+    );
+    if let Some(tag) = tag {
+        path.push_str(&format!("&tag={}", tag.trim_start_matches('#')));
+    }
+    if let Some(top) = top {
+        path.push_str(&format!("&top={}", top.0.num_days().max(1)));
+    }
+    path
+}
+
+pub async fn run(
+    config: &Config,
+    args: Feed,
+) -> anyhow::Result<()> {
+    let Feed {
+        target,
+        tag,
+        top,
+        latest,
+        limit,
+        json,
+    } = args;
+    let (_, target) = config.target(target.as_deref())?;
+    if target.kind != Kind::Forem {
+        bail!("feeds are only supported with Forem targets");
+    }
+    let articles: Vec<Article> =
+        api::public(&target, &path(tag.as_deref(), top, latest, limit)).await?;
+    if json {
+        let entries = articles.iter().map(Entry::from).collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    println!(
+        "{:>10} {:>9}  {}",
+        "reactions".bold(),
+        "comments".bold(),
+        "article".bold()
+    );
+    for article in &articles {
+        println!(
+            "{:>10} {:>9}  {} {}",
+            article.public_reactions_count,
+            article.comments_count,
+            article.title,
+            format!("by {}", article.user.name).dimmed()
+        );
+        println!("{:>22}{}", "", article.url.dimmed());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_selects_endpoint() -> anyhow::Result<()> {
+        assert_eq!(path(None, None, false, 20), "articles?per_page=20");
+        assert_eq!(
+            path(Some("#rust"), Some("7d".parse()?), false, 5),
+            "articles?per_page=5&tag=rust&top=7"
+        );
+        assert_eq!(
+            path(Some("rust"), None, true, 10),
+            "articles/latest?per_page=10&tag=rust"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use regex::{Captures, Regex};
+
+/// Heading of the section footnotes are collected in
+const HEADING: &str = "## Notes";
+
+/// a footnote definition, i.e. `[^1]: text`, and its continuation lines
+struct Note {
+    label: String,
+    text: Vec<String>,
+}
+
+/// replace `[^1]` style footnotes with numbered links to a notes section at the end of an
+/// article, linking back to where they were referenced. Frontmatter and fenced code are left as is
+pub fn to_notes(content: &str) -> String {
+    let definition = Regex::new(r"^\[\^([^\]\s]+)\]:\s?(.*)$").expect("invalid footnote pattern");
+    let reference = Regex::new(r"\[\^([^\]\s]+)\]").expect("invalid footnote pattern");
+    let mut lines = content.split('\n').peekable();
+    let mut out = Vec::new();
+    if content.starts_with("---") {
+        for line in lines.by_ref() {
+            out.push(line.to_string());
+            if out.len() > 1 && line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+    let mut notes: Vec<Note> = Vec::new();
+    // lines, and whether they may reference footnotes
+    let mut prose = Vec::new();
+    let mut fenced = false;
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        }
+        if fenced {
+            prose.push((line.to_string(), false));
+            continue;
+        }
+        match definition.captures(line) {
+            Some(captures) => {
+                let mut text = vec![captures[2].to_string()];
+                while let Some(next) =
+                    lines.next_if(|l| l.starts_with("    ") || l.starts_with('\t'))
+                {
+                    text.push(next.trim_start().to_string());
+                }
+                notes.push(Note {
+                    label: captures[1].to_string(),
+                    text,
+                });
+            }
+            None => prose.push((line.to_string(), true)),
+        }
+    }
+    if notes.is_empty() {
+        return content.to_string();
+    }
+    // notes are numbered in the order they're first referenced
+    let mut order: Vec<String> = Vec::new();
+    for (line, references) in &mut prose {
+        if !*references {
+            continue;
+        }
+        *line = reference
+            .replace_all(line, |captures: &Captures| {
+                let label = &captures[1];
+                if !notes.iter().any(|n| n.label == label) {
+                    return captures[0].to_string();
+                }
+                let n = match order.iter().position(|l| l == label) {
+                    Some(i) => i + 1,
+                    None => {
+                        order.push(label.to_string());
+                        order.len()
+                    }
+                };
+                format!("<sup><a name=\"ref-{0}\"></a>[{0}](#note-{0})</sup>", n)
+            })
+            .to_string();
+    }
+    for note in &notes {
+        if !order.contains(&note.label) {
+            order.push(note.label.clone());
+        }
+    }
+    out.extend(prose.into_iter().map(|(line, _)| line));
+    while out.last().map(|l| l.trim().is_empty()) == Some(true) {
+        out.pop();
+    }
+    out.push(String::new());
+    out.push(HEADING.into());
+    out.push(String::new());
+    for (i, label) in order.iter().enumerate() {
+        if let Some(note) = notes.iter().find(|n| &n.label == label) {
+            out.push(format!(
+                "{0}. <a name=\"note-{0}\"></a>{1} [↩](#ref-{0})",
+                i + 1,
+                note.text.join(" ")
+            ));
+        }
+    }
+    out.push(String::new());
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_notes_numbers_references() {
+        let content = "---\ntitle: a\n---\nFirst[^b] then[^a] again[^b] and[^missing].\n\n```\n[^a]: code\n```\n\n[^a]: note a\n[^b]: note b\n    continued\n";
+        assert_eq!(
+            to_notes(content),
+            "---\ntitle: a\n---\nFirst<sup><a name=\"ref-1\"></a>[1](#note-1)</sup> then<sup><a name=\"ref-2\"></a>[2](#note-2)</sup> again<sup><a name=\"ref-1\"></a>[1](#note-1)</sup> and[^missing].\n\n```\n[^a]: code\n```\n\n## Notes\n\n1. <a name=\"note-1\"></a>note b continued [↩](#ref-1)\n2. <a name=\"note-2\"></a>note a [↩](#ref-2)\n"
+        );
+    }
+
+    #[test]
+    fn to_notes_leaves_articles_without_footnotes_alone() {
+        let content = "plain [link](x) and [^1] without a definition\n";
+        assert_eq!(to_notes(content), content);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{
+    article::Frontmatter, blocklist::Blocklist, config::Config, images, owners::Owners, review,
+    schema::Rules, secrets,
+};
+use std::fmt;
+
+/// A check content must pass before it's uploaded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gate {
+    NotOwned,
+    Blocked,
+    InvalidFrontmatter,
+    Unreviewed,
+    Secrets,
+    MissingAltText,
+    Checklist,
+    ExceedsLimits,
+}
+
+impl fmt::Display for Gate {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(match self {
+            Gate::NotOwned => "NOT OWNED",
+            Gate::Blocked => "BLOCKED",
+            Gate::InvalidFrontmatter => "INVALID FRONTMATTER",
+            Gate::Unreviewed => "UNREVIEWED",
+            Gate::Secrets => "SECRETS",
+            Gate::MissingAltText => "MISSING ALT TEXT",
+            Gate::Checklist => "CHECKLIST",
+            Gate::ExceedsLimits => "EXCEEDS LIMITS",
+        })
+    }
+}
+
+/// Why a gate refused content
+#[derive(Debug, PartialEq)]
+pub struct Refusal {
+    pub gate: Gate,
+    /// Each problem found, shown below a file's status
+    pub details: Vec<String>,
+    /// The problems as reports give them
+    pub reason: String,
+}
+
+impl Refusal {
+    fn new(
+        gate: Gate,
+        details: Vec<String>,
+        reason: impl Into<String>,
+    ) -> Self {
+        Refusal {
+            gate,
+            details,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// An article about to be uploaded
+pub struct Upload<'a> {
+    /// Path of its file within its source directory
+    pub key: &'a str,
+    pub meta: &'a Frontmatter,
+    /// Content as uploaded, frontmatter included
+    pub content: &'a str,
+    pub body: &'a str,
+}
+
+/// The checks content passes before any command uploads it
+pub struct Gates<'a> {
+    config: &'a Config,
+    blocklist: Blocklist,
+    rules: Rules<'a>,
+    /// Who is uploading, as git knows them
+    pusher: Option<String>,
+    allow_secrets: bool,
+    override_checklist: bool,
+}
+
+impl<'a> Gates<'a> {
+    pub fn new(
+        config: &'a Config,
+        allow_secrets: bool,
+        override_checklist: bool,
+    ) -> anyhow::Result<Self> {
+        Ok(Gates {
+            config,
+            blocklist: Blocklist::new(&config.blocklist)?,
+            rules: Rules::new(&config.frontmatter)?,
+            pusher: review::git_user(),
+            allow_secrets,
+            override_checklist,
+        })
+    }
+
+    /// check what an article says, before anything is known of its remote copy: who owns it,
+    /// blocked terms, frontmatter rules, review, secrets and alt text
+    pub fn content(
+        &self,
+        owners: &Owners,
+        profile: Option<&str>,
+        upload: &Upload<'_>,
+    ) -> Result<(), Refusal> {
+        let Upload {
+            key, meta, content, ..
+        } = upload;
+        if !owners.allows(key, profile) {
+            let owned_by = owners.owners(key).unwrap_or_default().join(", ");
+            return Err(Refusal::new(
+                Gate::NotOwned,
+                vec![format!(
+                    "{} is owned by {}. Push it as one of them with --profile",
+                    key, owned_by
+                )],
+                format!("owned by {}", owned_by),
+            ));
+        }
+        let blocked = self.blocklist.find(content);
+        if !blocked.is_empty() {
+            // each term is named once, however often it's found
+            let mut terms = Vec::new();
+            for found in &blocked {
+                if !terms.contains(&found.term.as_str()) {
+                    terms.push(found.term.as_str());
+                }
+            }
+            return Err(Refusal::new(
+                Gate::Blocked,
+                blocked
+                    .iter()
+                    .map(|found| format!("{} on line {}", found.term, found.line))
+                    .collect(),
+                format!("contains blocked terms {}", terms.join(", ")),
+            ));
+        }
+        let violations = self.rules.check(content);
+        if !violations.is_empty() {
+            let details = violations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let reason = details.join(", ");
+            return Err(Refusal::new(Gate::InvalidFrontmatter, details, reason));
+        }
+        if self.config.require_review {
+            if let Some(reason) = review::unreviewed(meta, self.pusher.as_deref()) {
+                return Err(Refusal::new(Gate::Unreviewed, vec![reason.clone()], reason));
+            }
+        }
+        let secrets = if self.allow_secrets {
+            Vec::new()
+        } else {
+            secrets::scan(content)
+        };
+        if !secrets.is_empty() {
+            return Err(Refusal::new(
+                Gate::Secrets,
+                secrets
+                    .iter()
+                    .map(|secret| format!("possible {} on line {}", secret.kind, secret.line))
+                    .collect(),
+                format!(
+                    "possible secrets on lines {}",
+                    secrets
+                        .iter()
+                        .map(|secret| secret.line.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ));
+        }
+        if self.config.required_alt_text {
+            let mut missing = images::missing_alt(content)
+                .into_iter()
+                .map(|(line, _)| format!("image on line {}", line))
+                .collect::<Vec<_>>();
+            if meta.cover_image.is_some() && meta.cover_image_alt.is_none() {
+                missing.insert(0, "cover image".into());
+            }
+            if !missing.is_empty() {
+                let reason = format!("missing alt text for {}", missing.join(", "));
+                return Err(Refusal::new(Gate::MissingAltText, missing, reason));
+            }
+        }
+        Ok(())
+    }
+
+    /// check an article about to be published against the publishing checklist and, when
+    /// `limited`, content Forem would reject against its limits
+    pub fn publish(
+        &self,
+        upload: &Upload<'_>,
+        publishing: bool,
+        limited: bool,
+    ) -> Result<(), Refusal> {
+        let unchecked = if publishing && !self.override_checklist {
+            self.config.checklist.check(upload.meta, upload.body)
+        } else {
+            Vec::new()
+        };
+        if !unchecked.is_empty() {
+            let reason = format!("failed checklist: {}", unchecked.join(", "));
+            return Err(Refusal::new(Gate::Checklist, unchecked, reason));
+        }
+        let exceeded = if limited {
+            upload.meta.limits(upload.content)
+        } else {
+            Vec::new()
+        };
+        if !exceeded.is_empty() {
+            let details = exceeded.iter().map(ToString::to_string).collect::<Vec<_>>();
+            let reason = details.join("\n");
+            return Err(Refusal::new(Gate::ExceedsLimits, details, reason));
+        }
+        Ok(())
+    }
+
+    /// every check, for an article known to be uploaded
+    pub fn check(
+        &self,
+        owners: &Owners,
+        profile: Option<&str>,
+        upload: &Upload<'_>,
+        publishing: bool,
+        limited: bool,
+    ) -> Result<(), Refusal> {
+        self.content(owners, profile, upload)?;
+        self.publish(upload, publishing, limited)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::article;
+
+    #[test]
+    fn gates_refuse_in_order() -> anyhow::Result<()> {
+        let config = Config {
+            blocklist: vec!["codename".into()],
+            ..Config::default()
+        };
+        let gates = Gates::new(&config, false, false)?;
+        let check = |content: &str| -> anyhow::Result<Result<(), Refusal>> {
+            let (meta, body) = article::extract("foo.md", content)?;
+            let upload = Upload {
+                key: "foo.md",
+                meta: &meta,
+                content,
+                body: &body,
+            };
+            Ok(gates.check(&Owners::default(), None, &upload, true, true))
+        };
+        let secret = "---\ntitle: foo\n---\nAKIAIOSFODNN7EXAMPLE codename\ncodename again";
+        let refusal = check(secret)?.unwrap_err();
+        assert_eq!(refusal.gate, Gate::Blocked);
+        assert_eq!(refusal.reason, "contains blocked terms codename");
+        assert_eq!(
+            refusal.details,
+            vec!["codename on line 4", "codename on line 5"]
+        );
+        let refusal = check("---\ntitle: foo\n---\nAKIAIOSFODNN7EXAMPLE")?.unwrap_err();
+        assert_eq!(refusal.gate, Gate::Secrets);
+        assert_eq!(refusal.reason, "possible secrets on lines 4");
+        let tagged = "---\ntitle: foo\ntags: a, b, c, d, e\n---\nhi";
+        assert_eq!(
+            check(tagged)?.map_err(|refusal| refusal.gate),
+            Err(Gate::ExceedsLimits)
+        );
+        assert_eq!(check("---\ntitle: foo\n---\nhi")?, Ok(()));
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{config::Config, push};
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+use yaml_rust::{Yaml, YamlLoader};
+
+/// Generates articles from a yaml data file rendered through a markdown template
+///
+/// The data file holds a list of entries, each with a slug naming the article it
+/// generates. Templates substitute {{ field }}, repeat {{#each list}}..{{/each}} and
+/// include {{#if field}}..{{/if}}
+#[derive(StructOpt, Debug)]
+pub struct Generate {
+    /// Markdown template, including frontmatter
+    template: PathBuf,
+    /// Yaml file with a list of entries to render
+    data: PathBuf,
+    /// Directory to write articles to. Defaults to current working directory
+    #[structopt(short, long)]
+    out: Option<PathBuf>,
+}
+
+/// a scalar as template text. Collections and missing values render as nothing
+fn text(value: &Yaml) -> String {
+    match value {
+        Yaml::String(s) | Yaml::Real(s) => s.clone(),
+        Yaml::Integer(i) => i.to_string(),
+        Yaml::Boolean(b) => b.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn truthy(value: &Yaml) -> bool {
+    match value {
+        Yaml::Null | Yaml::BadValue | Yaml::Boolean(false) => false,
+        Yaml::String(s) => !s.is_empty(),
+        Yaml::Array(items) => !items.is_empty(),
+        _ => true,
+    }
+}
+
+/// a dotted name, i.e. speaker.name, resolved against the innermost scope defining it
+fn lookup<'a>(
+    scopes: &[&'a Yaml],
+    name: &str,
+) -> &'a Yaml {
+    static MISSING: Yaml = Yaml::BadValue;
+    let mut parts = name.split('.');
+    let first = parts.next().unwrap_or_default();
+    let value = if first == "this" {
+        scopes.last().copied()
+    } else {
+        scopes
+            .iter()
+            .rev()
+            .map(|scope| &scope[first])
+            .find(|value| !value.is_badvalue())
+    };
+    parts.fold(value.unwrap_or(&MISSING), |value, part| &value[part])
+}
+
+/// the tag ending a section opened just before `template`, and where it ends
+fn close(template: &str) -> anyhow::Result<(usize, usize)> {
+    let mut depth = 0;
+    let mut offset = 0;
+    while let Some(start) = template[offset..].find("{{") {
+        let start = offset + start;
+        let end = template[start..]
+            .find("}}")
+            .map(|end| start + end + 2)
+            .ok_or_else(|| anyhow!("unclosed {{{{ in template"))?;
+        let tag = template[start + 2..end - 2].trim();
+        if tag.starts_with('#') {
+            depth += 1;
+        } else if tag.starts_with('/') {
+            if depth == 0 {
+                return Ok((start, end));
+            }
+            depth -= 1;
+        }
+        offset = end;
+    }
+    bail!("template section is never closed")
+}
+
+/// skip the line break after a section tag so sections don't leave blank lines behind
+fn trim_line(text: &str) -> &str {
+    text.strip_prefix('\n').unwrap_or(text)
+}
+
+/// render a template against data, innermost scope last
+pub fn render(
+    template: &str,
+    scopes: &[&Yaml],
+) -> anyhow::Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("}}")
+            .map(|end| start + end + 2)
+            .ok_or_else(|| anyhow!("unclosed {{{{ in template"))?;
+        let tag = rest[start + 2..end - 2].trim();
+        let after = &rest[end..];
+        if let Some(section) = tag.strip_prefix('#') {
+            let (kind, name) = section.split_once(' ').unwrap_or((section, ""));
+            let value = lookup(scopes, name.trim());
+            let after = trim_line(after);
+            let (inner_end, close_end) = close(after)?;
+            let inner = &after[..inner_end];
+            match kind {
+                "each" => {
+                    for item in value.as_vec().map(Vec::as_slice).unwrap_or_default() {
+                        let mut nested = scopes.to_vec();
+                        nested.push(item);
+                        out.push_str(&render(inner, &nested)?);
+                    }
+                }
+                "if" => {
+                    if truthy(value) {
+                        out.push_str(&render(inner, scopes)?);
+                    }
+                }
+                other => bail!("unknown template section {}", other),
+            }
+            rest = trim_line(&after[close_end..]);
+        } else if tag.starts_with('/') {
+            bail!("{} closes a section which was never opened", tag);
+        } else {
+            out.push_str(&text(lookup(scopes, tag)));
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+pub fn run(
+    config: &Config,
+    args: Generate,
+) -> anyhow::Result<()> {
+    let Generate {
+        template,
+        data,
+        out,
+    } = args;
+    let out = out.unwrap_or_else(|| ".".into());
+    let template = fs::read_to_string(&template)?;
+    let docs = YamlLoader::load_from_str(&fs::read_to_string(&data)?)?;
+    let entries = match docs.first().and_then(Yaml::as_vec) {
+        Some(entries) => entries,
+        None => bail!("{} should hold a list of entries", data.display()),
+    };
+    for entry in entries {
+        let slug = match entry["slug"].as_str() {
+            Some(slug) if !slug.is_empty() => slug,
+            _ => bail!(
+                "every entry in {} needs a slug naming the article it generates",
+                data.display()
+            ),
+        };
+        let content = render(&template, &[entry])?;
+        let path = config.layout.path(&out, slug);
+        let status = match fs::read_to_string(&path) {
+            Ok(existing) if existing == content => "UNCHANGED".dimmed(),
+            Ok(_) => "UPDATED".yellow(),
+            Err(_) => "CREATED".green(),
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, content)?;
+        println!("{}", push::line(slug, status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(yaml: &str) -> Yaml {
+        YamlLoader::load_from_str(yaml).unwrap().remove(0)
+    }
+
+    #[test]
+    fn render_substitutes_fields() -> anyhow::Result<()> {
+        let entry = data("title: Rust at scale\nspeaker:\n  name: Ferris\nyear: 2024");
+        assert_eq!(
+            render(
+                "# {{ title }} ({{year}})\nby {{ speaker.name }}{{ missing }}\n",
+                &[&entry]
+            )?,
+            "# Rust at scale (2024)\nby Ferris\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn render_repeats_sections() -> anyhow::Result<()> {
+        let entry = data(
+            "week: 12\nlinks:\n  - title: a\n    url: https://a.dev\n  - title: b\ntags: [rust]",
+        );
+        assert_eq!(
+            render(
+                "{{#each links}}\n* [{{ title }}]({{ url }}) week {{ week }}\n{{/each}}\n{{#each tags}}#{{ this }} {{/each}}{{#if none}}nope{{/if}}",
+                &[&entry]
+            )?,
+            "* [a](https://a.dev) week 12\n* [b]() week 12\n#rust "
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn render_rejects_unbalanced_sections() {
+        let entry = data("a: 1");
+        assert!(render("{{#each a}}", &[&entry]).is_err());
+        assert!(render("{{/each}}", &[&entry]).is_err());
+        assert!(render("{{ a", &[&entry]).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use crate::{
+    api::{self, Article},
+    backend::{Backend, Post, Request, REDACTED},
+    config::Target,
+    recording,
+};
+use anyhow::{anyhow, bail};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+const POSTS: &str = "query Posts($id: ObjectId!, $after: String) {
+  publication(id: $id) {
+    posts(first: 50, after: $after) {
+      edges { node { id title slug url content { markdown } } }
+      pageInfo { hasNextPage endCursor }
+    }
+  }
+}";
+
+const PUBLISH: &str = "mutation Publish($input: PublishPostInput!) {
+  publishPost(input: $input) { post { id title slug url content { markdown } } }
+}";
+
+const UPDATE: &str = "mutation Update($input: UpdatePostInput!) {
+  updatePost(input: $input) { post { id title slug url content { markdown } } }
+}";
+
+const REMOVE: &str = "mutation Remove($input: RemovePostInput!) {
+  removePost(input: $input) { post { id } }
+}";
+
+#[derive(Debug, Deserialize)]
+struct Response<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostsData {
+    publication: Option<Publication>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Publication {
+    posts: Connection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Connection {
+    edges: Vec<Edge>,
+    page_info: PageInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct Edge {
+    node: Node,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Node {
+    id: String,
+    title: String,
+    slug: String,
+    url: String,
+    content: Content,
+}
+
+#[derive(Debug, Deserialize)]
+struct Content {
+    markdown: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Payload {
+    post: Node,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishData {
+    publish_post: Payload,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateData {
+    update_post: Payload,
+}
+
+impl From<Node> for Article {
+    fn from(node: Node) -> Self {
+        Article {
+            id: node.id,
+            title: node.title,
+            published: true,
+            slug: node.slug,
+            url: node.url,
+            body_markdown: node.content.markdown,
+            ..Article::default()
+        }
+    }
+}
+
+/// Publishes to a Hashnode publication through its GraphQL api
+pub struct Hashnode {
+    http: reqwest::Client,
+    url: String,
+    token: String,
+    publication_id: String,
+}
+
+impl Hashnode {
+    pub fn new(target: &Target) -> anyhow::Result<Self> {
+        Ok(Hashnode {
+            http: api::http()?,
+            url: target.base_url().into(),
+            token: target.api_key()?,
+            publication_id: target.publication_id.clone().ok_or_else(|| {
+                anyhow!("hashnode targets require a publication_id.\n  ▶ You can find yours in your publication's dashboard url")
+            })?,
+        })
+    }
+
+    async fn query<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: Value,
+    ) -> anyhow::Result<T> {
+        let resp = recording::send(
+            self.http
+                .post(self.url.as_str())
+                .header("Authorization", self.token.as_str())
+                .json(&json!({ "query": query, "variables": variables })),
+        )
+        .await?;
+        let status = resp.status();
+        let Response { data, errors } = resp.json::<Response<T>>().await?;
+        if !errors.is_empty() || !status.is_success() {
+            bail!(
+                "Hashnode error {:#?} {}",
+                status,
+                errors
+                    .into_iter()
+                    .map(|e| e.message)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        data.ok_or_else(|| anyhow!("Hashnode error {:#?} no data returned", status))
+    }
+
+    /// the mutation publishing a post, or updating the post with an id, and its variables
+    fn mutation(
+        &self,
+        id: Option<&str>,
+        post: &Post<'_>,
+    ) -> anyhow::Result<(&'static str, Value)> {
+        let mut input = self.input(post)?;
+        Ok(match id {
+            Some(id) => {
+                input["id"] = json!(id);
+                (UPDATE, json!({ "input": input }))
+            }
+            None => (PUBLISH, json!({ "input": input })),
+        })
+    }
+
+    fn input(
+        &self,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Value> {
+        if !post.meta.published.unwrap_or_default() {
+            bail!(
+                "Hashnode targets only support published articles. Set published: true to publish"
+            );
+        }
+        Ok(json!({
+            "title": post.meta.title,
+            "contentMarkdown": post.body,
+            "publicationId": self.publication_id,
+            "tags": tags(post.meta.tags.as_deref().unwrap_or_default()),
+            "originalArticleURL": post.meta.canonical_url,
+            "coverImageOptions": post.meta.cover_image.as_ref().map(|url| json!({ "coverImageURL": url })),
+        }))
+    }
+}
+
+/// hashnode tag inputs from comma separated tags
+fn tags(tags: &str) -> Vec<Value> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| json!({ "slug": t.to_lowercase(), "name": t }))
+        .collect()
+}
+
+#[async_trait]
+impl Backend for Hashnode {
+    async fn articles(&self) -> anyhow::Result<Vec<Article>> {
+        let mut articles = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let data: PostsData = self
+                .query(POSTS, json!({ "id": self.publication_id, "after": after }))
+                .await?;
+            let posts = data
+                .publication
+                .ok_or_else(|| anyhow!("Hashnode publication {} not found", self.publication_id))?
+                .posts;
+            articles.extend(posts.edges.into_iter().map(|e| Article::from(e.node)));
+            match posts.page_info {
+                PageInfo {
+                    has_next_page: true,
+                    end_cursor: Some(cursor),
+                } => after = Some(cursor),
+                _ => return Ok(articles),
+            }
+        }
+    }
+
+    async fn create(
+        &self,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Article> {
+        let (query, variables) = self.mutation(None, post)?;
+        let data: PublishData = self.query(query, variables).await?;
+        Ok(data.publish_post.post.into())
+    }
+
+    async fn update(
+        &self,
+        id: &str,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Article> {
+        let (query, variables) = self.mutation(Some(id), post)?;
+        let data: UpdateData = self.query(query, variables).await?;
+        Ok(data.update_post.post.into())
+    }
+
+    async fn remove(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<bool> {
+        self.query::<Value>(REMOVE, json!({ "input": { "id": id } }))
+            .await?;
+        Ok(true)
+    }
+
+    fn request(
+        &self,
+        id: Option<&str>,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Request> {
+        let (query, variables) = self.mutation(id, post)?;
+        Ok(Request {
+            method: "POST",
+            url: self.url.clone(),
+            headers: vec![
+                ("user-agent".into(), api::USER_AGENT.into()),
+                ("authorization".into(), REDACTED.into()),
+                ("content-type".into(), "application/json".into()),
+            ],
+            body: json!({ "query": query, "variables": variables }),
+        })
+    }
+
+    fn markdown<'a>(
+        &self,
+        post: &Post<'a>,
+    ) -> &'a str {
+        post.body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_are_slugged() {
+        assert_eq!(
+            tags("Rust, cli,"),
+            vec![
+                json!({ "slug": "rust", "name": "Rust" }),
+                json!({ "slug": "cli", "name": "cli" })
+            ]
+        );
+    }
+
+    #[test]
+    fn response_surfaces_errors() -> anyhow::Result<()> {
+        let resp: Response<PostsData> =
+            serde_json::from_str(r#"{"data": null, "errors": [{"message": "nope"}]}"#)?;
+        assert_eq!(resp.errors[0].message, "nope");
+        Ok(())
+    }
+}
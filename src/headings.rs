@@ -0,0 +1,107 @@
+/// Level of the heading dev.to renders an article's title as
+const TITLE: usize = 1;
+
+/// level of an ATX heading line, i.e. 2 for `## Setup`
+fn level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+/// 0-based line numbers and levels of the headings in the body of an article, outside of code
+fn headings(content: &str) -> Vec<(usize, usize)> {
+    let mut headings = Vec::new();
+    let mut fenced = false;
+    let mut lines = content.split('\n').enumerate();
+    if content.starts_with("---") {
+        lines.next();
+        for (_, line) in &mut lines {
+            if line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+    for (n, line) in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        } else if !fenced {
+            if let Some(level) = level(line) {
+                headings.push((n, level));
+            }
+        }
+    }
+    headings
+}
+
+/// levels headings need demoting by so none compete with the title
+fn shift(headings: &[(usize, usize)]) -> usize {
+    headings
+        .iter()
+        .map(|(_, level)| *level)
+        .min()
+        .map(|min| (TITLE + 1).saturating_sub(min))
+        .unwrap_or_default()
+}
+
+/// demote headings so the body starts at `##`, below the title
+pub fn demote(content: &str) -> String {
+    let headings = headings(content);
+    let shift = shift(&headings);
+    if shift == 0 {
+        return content.to_string();
+    }
+    content
+        .split('\n')
+        .enumerate()
+        .map(|(n, line)| match headings.iter().find(|(h, _)| *h == n) {
+            Some((_, level)) => {
+                format!("{}{}", "#".repeat((level + shift).min(6)), &line[*level..])
+            }
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 1-based lines of headings which skip a level once demoted, with the level expected and found
+pub fn skipped(content: &str) -> Vec<(usize, usize, usize)> {
+    let headings = headings(content);
+    let shift = shift(&headings);
+    let mut previous = TITLE;
+    let mut skipped = Vec::new();
+    for (n, level) in headings {
+        let level = (level + shift).min(6);
+        if level > previous + 1 {
+            skipped.push((n + 1, previous + 1, level));
+        }
+        previous = level;
+    }
+    skipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn demote_starts_bodies_below_the_title() {
+        assert_eq!(
+            demote("---\ntitle: a\n---\n# Intro\n```\n# comment\n```\n## Setup\n#hashtag"),
+            "---\ntitle: a\n---\n## Intro\n```\n# comment\n```\n### Setup\n#hashtag"
+        );
+        let content = "## Intro\n### Setup";
+        assert_eq!(demote(content), content);
+    }
+
+    #[test]
+    fn skipped_finds_level_jumps() {
+        assert_eq!(skipped("# a\n### b\n## c\n### d"), vec![(2, 3, 4)]);
+        assert_eq!(skipped("### a"), vec![(1, 2, 3)]);
+        assert!(skipped("## a\n### b\n## c").is_empty());
+    }
+}
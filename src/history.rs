@@ -0,0 +1,278 @@
+use crate::{article, config::Config, state, vault};
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use colored::Colorize;
+use similar::{ChangeTag, TextDiff};
+use std::{
+    cmp::Reverse,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use structopt::StructOpt;
+
+const FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+const EXT: &str = "snapshot";
+
+/// Lists the versions of a file recorded each time it was pushed
+#[derive(StructOpt, Debug)]
+pub struct History {
+    /// Markdown file to list versions of
+    file: PathBuf,
+    /// Directory markdown files are sourced from. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+}
+
+/// Prints a version of a file as it was pushed
+#[derive(StructOpt, Debug)]
+pub struct Show {
+    /// File and version to show, as <file>@<n> where 1 is the most recent push
+    revision: Revision,
+    /// Directory markdown files are sourced from. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+}
+
+/// Compares a file, rendered as it would be pushed, with a version of it as it was pushed
+#[derive(StructOpt, Debug)]
+pub struct Diff {
+    /// Markdown file to compare
+    file: PathBuf,
+    /// Version to compare against, where 1 is the most recent push
+    #[structopt(default_value = "1")]
+    n: usize,
+    /// Directory markdown files are sourced from. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Configured target the file is rendered for. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Revision {
+    file: PathBuf,
+    n: usize,
+}
+
+impl FromStr for Revision {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (file, n) = s
+            .rsplit_once('@')
+            .ok_or_else(|| anyhow!("expected <file>@<n> but got {}", s))?;
+        Ok(Revision {
+            file: file.into(),
+            n: n.parse()?,
+        })
+    }
+}
+
+/// A recorded snapshot of a file
+#[derive(Debug)]
+struct Version {
+    path: PathBuf,
+    taken_at: DateTime<Utc>,
+}
+
+fn dir(
+    source: &Path,
+    key: &str,
+) -> PathBuf {
+    source.join(state::DIR).join("history").join(key)
+}
+
+/// recorded versions of a file, most recent first
+fn versions(
+    source: &Path,
+    key: &str,
+) -> anyhow::Result<Vec<Version>> {
+    let dir = dir(source, key);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut versions = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().into_iter().any(|e| e == EXT))
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?;
+            let taken_at = NaiveDateTime::parse_from_str(stem, FORMAT).ok()?;
+            Some(Version {
+                taken_at: DateTime::from_naive_utc_and_offset(taken_at, Utc),
+                path,
+            })
+        })
+        .collect::<Vec<_>>();
+    versions.sort_by_key(|v| Reverse(v.taken_at));
+    Ok(versions)
+}
+
+fn version(
+    source: &Path,
+    key: &str,
+    n: usize,
+) -> anyhow::Result<String> {
+    let versions = versions(source, key)?;
+    match n.checked_sub(1).and_then(|i| versions.get(i)) {
+        Some(version) => vault::read(&version.path),
+        None => bail!(
+            "{} has {} recorded version(s), there is no version {}",
+            key,
+            versions.len(),
+            n
+        ),
+    }
+}
+
+/// record a snapshot of pushed content, keeping at most `depth` versions
+pub fn record(
+    source: &Path,
+    key: &str,
+    content: &str,
+    depth: usize,
+) -> anyhow::Result<()> {
+    if depth == 0 {
+        return Ok(());
+    }
+    // content pushed to more than one target is only recorded once
+    if let Some(latest) = versions(source, key)?.first() {
+        if vault::read(&latest.path)? == content {
+            return Ok(());
+        }
+    }
+    let dir = dir(source, key);
+    fs::create_dir_all(&dir)?;
+    vault::write(
+        &dir.join(format!("{}.{}", Utc::now().format(FORMAT), EXT)),
+        content,
+    )?;
+    for stale in versions(source, key)?.into_iter().skip(depth) {
+        fs::remove_file(stale.path)?;
+    }
+    Ok(())
+}
+
+pub fn history(
+    config: &Config,
+    args: History,
+) -> anyhow::Result<()> {
+    let History { file, source } = args;
+    let source = config.source(source);
+    let key = state::key(&source, &file);
+    let versions = versions(&source, &key)?;
+    if versions.is_empty() {
+        println!("No recorded versions of {}", key);
+    }
+    for (n, version) in versions.iter().enumerate() {
+        let hash = state::hash(&vault::read(&version.path)?);
+        println!(
+            "{} {} {}",
+            format!("@{}", n + 1).bold(),
+            version.taken_at.to_rfc3339(),
+            hash[..8].dimmed()
+        );
+    }
+    Ok(())
+}
+
+pub fn show(
+    config: &Config,
+    args: Show,
+) -> anyhow::Result<()> {
+    let Show { revision, source } = args;
+    let source = config.source(source);
+    print!(
+        "{}",
+        version(&source, &state::key(&source, &revision.file), revision.n)?
+    );
+    Ok(())
+}
+
+/// a file as push would render it for a target, which is what versions record
+async fn rendered(
+    config: &Config,
+    source: &Path,
+    file: &Path,
+    target: Option<&str>,
+) -> anyhow::Result<String> {
+    let (_, target) = config.target(target)?;
+    article::render(config, source, file, target.kind).await
+}
+
+pub async fn diff(
+    config: &Config,
+    args: Diff,
+) -> anyhow::Result<()> {
+    let Diff {
+        file,
+        n,
+        source,
+        target,
+    } = args;
+    let source = config.source(source);
+    let key = state::key(&source, &file);
+    let old = version(&source, &key, n)?;
+    let new = rendered(config, &source, &file, target.as_deref()).await?;
+    let diff = TextDiff::from_lines(&old, &new);
+    println!("{}", format!("--- {}@{}\n+++ {}", key, n, key).bold());
+    for change in diff.iter_all_changes() {
+        let line = change.to_string_lossy();
+        let line = line.trim_end_matches('\n');
+        match change.tag() {
+            ChangeTag::Delete => println!("{}", format!("-{}", line).red()),
+            ChangeTag::Insert => println!("{}", format!("+{}", line).green()),
+            ChangeTag::Equal => println!(" {}", line),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revision_parses() -> anyhow::Result<()> {
+        assert_eq!(
+            "posts/foo@bar.md@2".parse::<Revision>()?,
+            Revision {
+                file: "posts/foo@bar.md".into(),
+                n: 2
+            }
+        );
+        assert!("foo.md".parse::<Revision>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn record_keeps_depth_most_recent_versions() -> anyhow::Result<()> {
+        let source = std::env::temp_dir().join("devtogo-history-test");
+        let _ = fs::remove_dir_all(&source);
+        for content in &["one", "two", "three"] {
+            record(&source, "foo.md", content, 2)?;
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(versions(&source, "foo.md")?.len(), 2);
+        assert_eq!(version(&source, "foo.md", 1)?, "three");
+        assert_eq!(version(&source, "foo.md", 2)?, "two");
+        assert!(version(&source, "foo.md", 3).is_err());
+        fs::remove_dir_all(&source)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn diffs_compare_files_as_pushed() -> anyhow::Result<()> {
+        let source = std::env::temp_dir().join("devtogo-history-rendered-test");
+        let _ = fs::remove_dir_all(&source);
+        fs::create_dir_all(&source)?;
+        let file = source.join("foo.md");
+        fs::write(&file, "---\ntitle: foo\nstatus: published\n---\nhi")?;
+        assert_eq!(
+            rendered(&Config::default(), &source, &file, None).await?,
+            "---\ntitle: foo\nstatus: published\npublished: true\n---\nhi"
+        );
+        fs::remove_dir_all(&source)?;
+        Ok(())
+    }
+}
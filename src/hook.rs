@@ -0,0 +1,107 @@
+use anyhow::{anyhow, bail};
+use std::{fs, path::PathBuf, process::Command};
+use structopt::StructOpt;
+
+/// Marks hooks written by devtogo so they may be safely replaced
+const MARKER: &str = "# installed by devtogo";
+
+/// Manages git hooks which check content before it leaves your machine
+#[derive(StructOpt, Debug)]
+pub enum Hook {
+    /// Installs a git hook which runs devtogo lint, or a dry run push, before committing
+    Install(Install),
+}
+
+#[derive(StructOpt, Debug)]
+pub struct Install {
+    /// Git hook to install
+    #[structopt(long, default_value = "pre-commit", possible_values = &["pre-commit", "pre-push"])]
+    kind: String,
+    /// Run a dry run push instead of lint. The hook will need DEVTO_API_KEY in its environment
+    #[structopt(short, long)]
+    dryrun: bool,
+    /// Directory to source markdown files from, relative to the repository root
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Replace an existing hook not installed by devtogo
+    #[structopt(short, long)]
+    force: bool,
+}
+
+/// shell script for a hook
+fn script(
+    dryrun: bool,
+    source: Option<&PathBuf>,
+) -> String {
+    let command = if dryrun { "push --dryrun" } else { "lint" };
+    let source = source
+        .map(|s| format!(" --source '{}'", s.display()))
+        .unwrap_or_default();
+    format!(
+        "#!/bin/sh\n{}\nexec devtogo {}{}\n",
+        MARKER, command, source
+    )
+}
+
+/// the directory git runs hooks from, respecting worktrees and core.hooksPath
+fn hooks_dir() -> anyhow::Result<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .output()
+        .map_err(|err| anyhow!("failed to run git: {}", err))?;
+    if !output.status.success() {
+        bail!(
+            "unable to locate git hooks directory. Is this a git repository?\n  ▶ {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().into())
+}
+
+pub fn run(args: Hook) -> anyhow::Result<()> {
+    match args {
+        Hook::Install(Install {
+            kind,
+            dryrun,
+            source,
+            force,
+        }) => {
+            let dir = hooks_dir()?;
+            let path = dir.join(&kind);
+            if path.exists() && !force && !fs::read_to_string(&path)?.contains(MARKER) {
+                bail!(
+                    "{} already exists.\n  ▶ Re-run with --force to replace it",
+                    path.display()
+                );
+            }
+            fs::create_dir_all(&dir)?;
+            fs::write(&path, script(dryrun, source.as_ref()))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+            }
+            println!("Installed {} hook at {}", kind, path.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn script_lints_by_default() {
+        assert_eq!(
+            script(false, None),
+            "#!/bin/sh\n# installed by devtogo\nexec devtogo lint\n"
+        );
+    }
+
+    #[test]
+    fn script_can_dryrun_push() {
+        assert!(script(true, Some(&"posts".into()))
+            .ends_with("exec devtogo push --dryrun --source 'posts'\n"));
+    }
+}
@@ -0,0 +1,332 @@
+use crate::{config::Config, recording, state};
+use anyhow::bail;
+use image::{
+    codecs::{
+        jpeg::JpegEncoder,
+        png::{CompressionType, FilterType, PngEncoder},
+        webp::WebPEncoder,
+    },
+    imageops, DynamicImage,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+/// Formats local images can be recompressed as
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Jpeg,
+    Png,
+    /// Lossless WebP
+    Webp,
+}
+
+impl Format {
+    fn of(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "jpg" | "jpeg" => Some(Format::Jpeg),
+            "png" => Some(Format::Png),
+            "webp" => Some(Format::Webp),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Jpeg => "jpg",
+            Format::Png => "png",
+            Format::Webp => "webp",
+        }
+    }
+}
+
+/// Settings for resizing and recompressing local images before they are published
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Optimize {
+    /// Widest an image may be, in pixels. Wider images are scaled down
+    pub max_width: Option<u32>,
+    /// Format to recompress images as, defaulting to each image's own
+    pub format: Option<Format>,
+    /// JPEG quality, from 1 to 100
+    pub quality: u8,
+}
+
+impl Default for Optimize {
+    fn default() -> Self {
+        Optimize {
+            max_width: None,
+            format: None,
+            quality: 80,
+        }
+    }
+}
+
+/// remote image urls referenced by markdown image syntax or html img tags
+pub fn references(markdown: &str) -> Vec<String> {
+    let pattern =
+        Regex::new(r#"!\[[^\]]*\]\((https?://[^)\s]+)|<img[^>]*\ssrc=["'](https?://[^"']+)["']"#)
+            .expect("invalid image pattern");
+    let mut urls = Vec::new();
+    for captures in pattern.captures_iter(markdown) {
+        if let Some(url) = captures.get(1).or_else(|| captures.get(2)) {
+            if !urls.iter().any(|u| u == url.as_str()) {
+                urls.push(url.as_str().to_string());
+            }
+        }
+    }
+    urls
+}
+
+/// a local file name for an image url, unique to the url but recognizable by its original name
+fn file_name(url: &str) -> String {
+    let name = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        .collect::<String>();
+    let name = if name.is_empty() { "image" } else { &name };
+    format!("{}-{}", &state::hash(url)[..8], name)
+}
+
+/// download the remote images a markdown file in `dir` references into its `images`
+/// subdirectory, returning the markdown with references rewritten to relative paths
+pub async fn localize(
+    http: &reqwest::Client,
+    markdown: &str,
+    dir: &Path,
+    images: &str,
+) -> anyhow::Result<String> {
+    let mut localized = markdown.to_string();
+    for url in references(markdown) {
+        let name = file_name(&url);
+        let path = dir.join(images).join(&name);
+        if !path.exists() {
+            let bytes = recording::send(http.get(url.as_str()))
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?;
+            fs::create_dir_all(dir.join(images))?;
+            fs::write(&path, bytes)?;
+        }
+        let relative = if images.is_empty() {
+            name
+        } else {
+            format!("{}/{}", images, name)
+        };
+        localized = localized.replace(&url, &relative);
+    }
+    Ok(localized)
+}
+
+/// local image paths referenced by markdown image syntax, html img tags or `cover_image` frontmatter
+pub fn local_references(markdown: &str) -> Vec<String> {
+    let pattern = Regex::new(
+        r#"!\[[^\]]*\]\(([^)\s]+)|<img[^>]*\ssrc=["']([^"']+)["']|(?m)^cover_image:\s*["']?([^"'\s]+)"#,
+    )
+    .expect("invalid image pattern");
+    let mut paths = Vec::new();
+    for captures in pattern.captures_iter(markdown) {
+        if let Some(path) = captures
+            .get(1)
+            .or_else(|| captures.get(2))
+            .or_else(|| captures.get(3))
+        {
+            let path = path.as_str();
+            if !path.contains("://")
+                && !path.starts_with(['/', '#'])
+                && !path.starts_with("data:")
+                && !paths.iter().any(|p| p == path)
+            {
+                paths.push(path.to_string());
+            }
+        }
+    }
+    paths
+}
+
+/// a resized and recompressed copy of an image, saved next to it as <name>.min.<ext> and only
+/// rewritten when the image changes. Images in formats which can't be recompressed are left as is
+pub fn optimize(
+    path: &Path,
+    settings: &Optimize,
+) -> anyhow::Result<PathBuf> {
+    let format = match settings.format.or_else(|| Format::of(path)) {
+        Some(format) => format,
+        None => return Ok(path.to_path_buf()),
+    };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let optimized = path.with_file_name(format!("{}.min.{}", stem, format.extension()));
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    if modified(&optimized).is_some() && modified(&optimized) >= modified(path) {
+        return Ok(optimized);
+    }
+    let mut image = image::open(path)?;
+    if let Some(max_width) = settings.max_width {
+        if image.width() > max_width {
+            let height = (image.height() as u64 * max_width as u64 / image.width() as u64).max(1);
+            image = image.resize_exact(max_width, height as u32, imageops::FilterType::Lanczos3);
+        }
+    }
+    let out = BufWriter::new(fs::File::create(&optimized)?);
+    match format {
+        Format::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(
+            JpegEncoder::new_with_quality(out, settings.quality.clamp(1, 100)),
+        )?,
+        Format::Png => image.write_with_encoder(PngEncoder::new_with_quality(
+            out,
+            CompressionType::Best,
+            FilterType::Adaptive,
+        ))?,
+        Format::Webp => DynamicImage::ImageRgba8(image.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(out))?,
+    }
+    Ok(optimized)
+}
+
+/// rewrite references to local images, relative to the article at `path`, to where they are
+/// published at the configured `images_url`, optimizing them first when configured to
+pub fn publish(
+    config: &Config,
+    source: &Path,
+    path: &Path,
+    markdown: &str,
+) -> anyhow::Result<String> {
+    let references = local_references(markdown);
+    let images_url = match &config.images_url {
+        Some(url) => url.trim_end_matches('/'),
+        None if config.optimize_images.is_some() && !references.is_empty() => bail!(
+            "{} references local images, which need to be published somewhere to be optimized.\n  ▶ Set images_url in devtogo.toml to the url your source directory is published at",
+            path.display()
+        ),
+        None => return Ok(markdown.to_string()),
+    };
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut published = markdown.to_string();
+    for reference in references {
+        let mut image = dir.join(&reference);
+        if !image.is_file() {
+            continue;
+        }
+        if let Some(settings) = &config.optimize_images {
+            image = optimize(&image, settings)?;
+        }
+        let url = format!("{}/{}", images_url, state::key(source, &image));
+        published = published
+            .replace(&format!("({})", reference), &format!("({})", url))
+            .replace(&format!("\"{}\"", reference), &format!("\"{}\"", url))
+            .replace(&format!("'{}'", reference), &format!("'{}'", url))
+            .replace(&format!(": {}\n", reference), &format!(": {}\n", url));
+    }
+    Ok(published)
+}
+
+/// 1-based lines and columns of images without alt text, outside of frontmatter and fenced code
+pub fn missing_alt(markdown: &str) -> Vec<(usize, usize)> {
+    let image = Regex::new(r#"!\[\s*\]\(|<img\b[^>]*>"#).expect("invalid image pattern");
+    let alt = Regex::new(r#"\salt\s*=\s*("\s*[^"\s][^"]*"|'\s*[^'\s][^']*')"#)
+        .expect("invalid alt pattern");
+    let mut missing = Vec::new();
+    let mut fenced = false;
+    let mut lines = markdown.lines().enumerate();
+    if markdown.starts_with("---") {
+        lines.next();
+        for (_, line) in &mut lines {
+            if line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+    for (n, line) in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced {
+            continue;
+        }
+        for found in image.find_iter(line) {
+            if found.as_str().starts_with('!') || !alt.is_match(found.as_str()) {
+                missing.push((n + 1, found.start() + 1));
+            }
+        }
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_finds_markdown_and_html_images() {
+        assert_eq!(
+            references(
+                "![a](https://a.com/x.png) [link](https://a.com) <img alt=\"b\" src=\"http://b.com/y.gif\"/> ![a](https://a.com/x.png) ![c](images/c.png)"
+            ),
+            vec!["https://a.com/x.png", "http://b.com/y.gif"]
+        );
+    }
+
+    #[test]
+    fn local_references_skips_remote_images() {
+        assert_eq!(
+            local_references(
+                "---\ncover_image: images/cover.png\n---\n![a](https://a.com/x.png) ![b](b.jpg) <img src=\"images/c.webp\"> ![d](data:image/png;base64,x) ![b](b.jpg)"
+            ),
+            vec!["images/cover.png", "b.jpg", "images/c.webp"]
+        );
+    }
+
+    #[test]
+    fn optimize_scales_down_wide_images() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join("devtogo-optimize-test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        let original = dir.join("wide.png");
+        image::RgbImage::new(400, 100).save(&original)?;
+        let settings = Optimize {
+            max_width: Some(200),
+            format: Some(Format::Jpeg),
+            ..Optimize::default()
+        };
+        let optimized = optimize(&original, &settings)?;
+        assert_eq!(optimized, dir.join("wide.min.jpg"));
+        assert_eq!(image::image_dimensions(&optimized)?, (200, 50));
+        let gif = dir.join("anim.gif");
+        assert_eq!(optimize(&gif, &Optimize::default())?, gif);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn missing_alt_finds_images_without_descriptions() {
+        assert_eq!(
+            missing_alt(
+                "---\ntitle: a\n---\n![](a.png) ![a cat](b.png)\n<img src=\"c.png\"> <img alt=\"\" src=\"d.png\"> <img alt=\"a dog\" src=\"e.png\">\n```\n![](f.png)\n```"
+            ),
+            vec![(4, 1), (5, 1), (5, 19)]
+        );
+    }
+
+    #[test]
+    fn file_name_keeps_original_name() {
+        let name =
+            file_name("https://dev-to-uploads.s3.amazonaws.com/uploads/articles/abc.png?x=1");
+        assert!(name.ends_with("-abc.png"));
+        assert_ne!(name, file_name("https://example.com/abc.png"));
+        assert!(file_name("https://example.com/").ends_with("-image"));
+    }
+}
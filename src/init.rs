@@ -0,0 +1,212 @@
+use crate::{
+    api::{Auth, Client},
+    config::{Config, DEFAULT_TARGET},
+    console, oauth,
+};
+use anyhow::bail;
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+use structopt::StructOpt;
+
+/// Instance targets are published to unless another is chosen
+const DEFAULT_INSTANCE: &str = "https://dev.to";
+
+/// Asks for an api key, source directory, canonical base url and instance, then writes devtogo.toml
+#[derive(StructOpt, Debug)]
+pub struct Init {
+    /// Replace an existing config file
+    #[structopt(short, long)]
+    force: bool,
+}
+
+/// Answers to the questions init asks
+#[derive(Debug, Default)]
+struct Answers {
+    instance: String,
+    target: String,
+    source: String,
+    canonical_base_url: String,
+}
+
+/// ask a question, returning the trimmed answer or a default when none is given
+fn ask(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+    question: &str,
+    default: &str,
+) -> anyhow::Result<String> {
+    if default.is_empty() {
+        write!(out, "{}: ", question)?;
+    } else {
+        write!(out, "{} [{}]: ", question, default)?;
+    }
+    out.flush()?;
+    let mut answer = String::new();
+    if input.read_line(&mut answer)? == 0 {
+        bail!("no answer to {}", question);
+    }
+    Ok(match answer.trim() {
+        "" => default.into(),
+        answer => answer.into(),
+    })
+}
+
+/// a toml string
+fn quote(text: &str) -> String {
+    toml::Value::String(text.into()).to_string()
+}
+
+/// devtogo.toml holding answers, leaving out ones which match defaults
+fn render(answers: &Answers) -> String {
+    let mut out = String::new();
+    if !answers.source.is_empty() && answers.source != "." {
+        out.push_str(&format!("source = {}\n", quote(&answers.source)));
+    }
+    if !answers.canonical_base_url.is_empty() {
+        out.push_str(&format!(
+            "canonical_base_url = {}\n",
+            quote(&answers.canonical_base_url)
+        ));
+    }
+    if answers.instance.trim_end_matches('/') != DEFAULT_INSTANCE {
+        out.push_str(&format!(
+            "\n[targets.{}]\nbase_url = {}\napi_key_env = {}\n",
+            answers.target,
+            quote(&answers.instance),
+            quote(&format!(
+                "{}_API_KEY",
+                answers.target.to_uppercase().replace('-', "_")
+            ))
+        ));
+    }
+    out
+}
+
+pub async fn run(
+    config_path: &Path,
+    args: Init,
+) -> anyhow::Result<()> {
+    if config_path.exists() && !args.force {
+        bail!(
+            "{} already exists.\n  ▶ Re-run with --force to replace it",
+            config_path.display()
+        );
+    }
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut out = io::stdout();
+    let instance = ask(
+        &mut input,
+        &mut out,
+        "Forem instance to publish to",
+        DEFAULT_INSTANCE,
+    )?;
+    let target = if instance.trim_end_matches('/') == DEFAULT_INSTANCE {
+        DEFAULT_TARGET.to_string()
+    } else {
+        let name = ask(&mut input, &mut out, "Name for this instance", "forem")?;
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!("names may only hold letters, numbers, - and _");
+        }
+        name
+    };
+    let source = ask(&mut input, &mut out, "Directory articles are kept in", ".")?;
+    let canonical_base_url = ask(
+        &mut input,
+        &mut out,
+        "Url articles are originally published at, if any",
+        "",
+    )?;
+    let answers = Answers {
+        instance,
+        target,
+        source,
+        canonical_base_url,
+    };
+    let rendered = render(&answers);
+    let (_, target) = toml::from_str::<Config>(&rendered)?.target(Some(&answers.target))?;
+    let key = ask(
+        &mut input,
+        &mut out,
+        &format!("Api key, from {}/settings/extensions", target.base_url()),
+        "",
+    )?;
+    if key.is_empty() {
+        bail!("an api key is required");
+    }
+    println!("{}", console::text("Checking api key…"));
+    Client::new(&target, Auth::ApiKey(key.clone()))?
+        .limits()
+        .await?;
+    let store = ask(
+        &mut input,
+        &mut out,
+        "Store the api key in the system keychain? (y/n)",
+        "y",
+    )?;
+    fs::write(config_path, &rendered)?;
+    println!("Wrote {}", config_path.display());
+    if store.eq_ignore_ascii_case("y") || store.eq_ignore_ascii_case("yes") {
+        oauth::store_api_key(&target, &key)?;
+        println!("Stored api key in the system keychain");
+    } else {
+        println!(
+            "Export your api key as {} before running devtogo",
+            target.api_key_env()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ask_falls_back_on_default() -> anyhow::Result<()> {
+        let mut out = Vec::new();
+        assert_eq!(ask(&mut &b"\n"[..], &mut out, "Directory", ".")?, ".");
+        assert_eq!(
+            ask(&mut &b" posts \n"[..], &mut out, "Directory", ".")?,
+            "posts"
+        );
+        assert_eq!(String::from_utf8(out)?, "Directory [.]: Directory [.]: ");
+        assert!(ask(&mut &b""[..], &mut Vec::new(), "Directory", ".").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn render_writes_loadable_config() -> anyhow::Result<()> {
+        let answers = Answers {
+            instance: "https://forem.example.com".into(),
+            target: "my-forem".into(),
+            source: "posts".into(),
+            canonical_base_url: "https://example.com/blog".into(),
+        };
+        let config = toml::from_str::<Config>(&render(&answers))?;
+        assert_eq!(config.source(None), Path::new("posts"));
+        assert_eq!(
+            config.canonical_base_url.as_deref(),
+            Some("https://example.com/blog")
+        );
+        let (name, target) = config.target(None)?;
+        assert_eq!(name, "my-forem");
+        assert_eq!(target.base_url(), "https://forem.example.com");
+        assert_eq!(target.api_key_env(), "MY_FOREM_API_KEY");
+
+        let answers = Answers {
+            instance: DEFAULT_INSTANCE.into(),
+            target: DEFAULT_TARGET.into(),
+            source: ".".into(),
+            ..Answers::default()
+        };
+        assert_eq!(render(&answers), "");
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+/// A single JUnit test case, passing unless it has failures
+#[derive(Debug, Default)]
+pub struct Case {
+    pub classname: String,
+    pub name: String,
+    pub failures: Vec<String>,
+    /// informational output, i.e. warnings
+    pub output: Vec<String>,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// a JUnit XML report with a single suite, as understood by Jenkins, GitLab and most CI systems
+pub fn render(
+    suite: &str,
+    cases: &[Case],
+) -> String {
+    let failures = cases.iter().filter(|c| !c.failures.is_empty()).count();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+    out.push_str(&format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"0\">\n",
+        escape(suite),
+        cases.len(),
+        failures
+    ));
+    for case in cases {
+        out.push_str(&format!(
+            "    <testcase classname=\"{}\" name=\"{}\"",
+            escape(&case.classname),
+            escape(&case.name)
+        ));
+        if case.failures.is_empty() && case.output.is_empty() {
+            out.push_str("/>\n");
+            continue;
+        }
+        out.push_str(">\n");
+        if let Some(first) = case.failures.first() {
+            out.push_str(&format!(
+                "      <failure message=\"{}\">{}</failure>\n",
+                escape(first),
+                escape(&case.failures.join("\n"))
+            ));
+        }
+        if !case.output.is_empty() {
+            out.push_str(&format!(
+                "      <system-out>{}</system-out>\n",
+                escape(&case.output.join("\n"))
+            ));
+        }
+        out.push_str("    </testcase>\n");
+    }
+    out.push_str("  </testsuite>\n</testsuites>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_reports_failures() {
+        let xml = render(
+            "devtogo lint",
+            &[
+                Case {
+                    classname: "devtogo.lint".into(),
+                    name: "a.md".into(),
+                    ..Case::default()
+                },
+                Case {
+                    classname: "devtogo.lint".into(),
+                    name: "b.md".into(),
+                    failures: vec!["1:1: missing <title>".into()],
+                    ..Case::default()
+                },
+            ],
+        );
+        assert!(xml.contains("tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<testcase classname=\"devtogo.lint\" name=\"a.md\"/>"));
+        assert!(xml.contains("<failure message=\"1:1: missing &lt;title&gt;\">"));
+    }
+}
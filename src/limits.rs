@@ -0,0 +1,74 @@
+use crate::{
+    api::Client,
+    config::{Config, Kind},
+    oauth,
+};
+use anyhow::bail;
+use chrono::{Local, TimeZone};
+use colored::Colorize;
+use structopt::StructOpt;
+
+/// Shows the api rate limits currently applied to your account
+#[derive(StructOpt, Debug)]
+pub struct Limits {
+    /// Configured target to check. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+}
+
+/// a rate limit header value made readable. Reset headers hold either a unix
+/// timestamp or a number of seconds from now
+fn describe(
+    name: &str,
+    value: &str,
+) -> String {
+    match value.parse::<i64>() {
+        Ok(secs) if name.contains("reset") || name == "retry-after" => {
+            let at = if secs > 1_000_000_000 {
+                Local.timestamp_opt(secs, 0).single()
+            } else {
+                Some(Local::now() + chrono::Duration::seconds(secs))
+            };
+            match at {
+                Some(at) => format!("{} ({})", value, at.format("%Y-%m-%d %H:%M:%S")),
+                None => value.to_string(),
+            }
+        }
+        _ => value.to_string(),
+    }
+}
+
+pub async fn run(
+    config: &Config,
+    args: Limits,
+) -> anyhow::Result<()> {
+    let (name, target) = config.target(args.target.as_deref())?;
+    if target.kind != Kind::Forem {
+        bail!("limits are only supported with Forem targets");
+    }
+    let client = Client::new(&target, oauth::auth(&name, &target).await?)?;
+    let limits = client.limits().await?;
+    if limits.is_empty() {
+        println!("{} returned no rate limit headers", name);
+    }
+    for (header, value) in limits {
+        println!("{} {}", header.bold(), describe(&header, &value));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_passes_through_counts() {
+        assert_eq!(describe("x-ratelimit-remaining", "29"), "29");
+    }
+
+    #[test]
+    fn describe_dates_resets() {
+        assert!(describe("x-ratelimit-reset", "1600000000").starts_with("1600000000 (2020-09-"));
+        assert!(describe("retry-after", "30").starts_with("30 ("));
+    }
+}
@@ -0,0 +1,779 @@
+use crate::{
+    article::{self, FieldError, Strictness},
+    blocklist::Blocklist,
+    config::Config,
+    dates::Zone,
+    headings, images,
+    junit::{self, Case},
+    prose, review, samples,
+    schema::Rules,
+};
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{fmt, fs, path::PathBuf, str::FromStr};
+use structopt::StructOpt;
+
+/// Liquid tags which wrap content and must be closed with a matching end tag
+const BLOCK_TAGS: &[&str] = &[
+    "details",
+    "spoiler",
+    "collapsible",
+    "katex",
+    "raw",
+    "runkit",
+];
+
+/// Liquid tags which embed content referenced by their arguments
+const EMBED_TAGS: &[&str] = &[
+    "asciinema",
+    "codepen",
+    "codesandbox",
+    "dotnetfiddle",
+    "embed",
+    "gist",
+    "github",
+    "glitch",
+    "instagram",
+    "jsfiddle",
+    "kotlin",
+    "link",
+    "medium",
+    "podcast",
+    "post",
+    "reddit",
+    "replit",
+    "slideshare",
+    "soundcloud",
+    "speakerdeck",
+    "spotify",
+    "stackblitz",
+    "stackexchange",
+    "tag",
+    "tweet",
+    "twitch",
+    "twitter",
+    "user",
+    "vimeo",
+    "wikipedia",
+    "youtube",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    JsonDiagnostics,
+    Junit,
+    Sarif,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json-diagnostics" => Ok(Format::JsonDiagnostics),
+            "junit" => Ok(Format::Junit),
+            "sarif" => Ok(Format::Sarif),
+            other => Err(anyhow!("unsupported format {}", other)),
+        }
+    }
+}
+
+/// Checks local markdown files for problems dev.to would reject or render poorly
+#[derive(StructOpt, Debug)]
+pub struct Lint {
+    /// Directory to source markdown files from. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Output format. json-diagnostics emits a JSON array of file/line/column/severity records for editors and tools like reviewdog.
+    /// junit emits a JUnit XML report with a test case per file for CI systems.
+    /// sarif emits a SARIF log for code scanning tools like GitHub's
+    #[structopt(
+        long,
+        alias = "output",
+        default_value = "text",
+        possible_values = &["text", "json-diagnostics", "junit", "sarif"]
+    )]
+    format: Format,
+    /// Also check spelling against configured dictionaries and style against Vale rules
+    #[structopt(long)]
+    prose: bool,
+    /// Also check fenced code blocks of these languages, i.e. rust,bash, with the commands
+    /// configured for them under [code_checks] in devtogo.toml
+    #[structopt(long, use_delimiter = true)]
+    check_code: Vec<String>,
+    /// Report frontmatter fields devtogo doesn't know as errors, so nothing is silently dropped
+    #[structopt(long, conflicts_with = "lenient")]
+    strict: bool,
+    /// Report invalid optional frontmatter fields as warnings rather than errors
+    #[structopt(long)]
+    lenient: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let s = format!(
+            "{}",
+            match self {
+                Severity::Error => "error".red(),
+                Severity::Warning => "warning".yellow(),
+            }
+        );
+        f.write_str(&s)
+    }
+}
+
+/// A single problem found in a file, positioned with 1-based lines and columns
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(
+        file: &str,
+        (line, column): (usize, usize),
+        severity: Severity,
+        code: &'static str,
+        message: impl Into<String>,
+    ) -> Self {
+        Diagnostic {
+            file: file.into(),
+            line,
+            column,
+            severity,
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}: {} {}",
+            self.file.bold(),
+            self.line,
+            self.column,
+            self.severity,
+            self.message,
+            format!("[{}]", self.code).dimmed()
+        )
+    }
+}
+
+/// check the frontmatter of a file
+fn frontmatter(
+    file: &str,
+    content: &str,
+    strictness: Strictness,
+    zone: Zone,
+) -> Vec<Diagnostic> {
+    let err = match article::parse(file, content, strictness, zone) {
+        Ok((meta, _, warnings)) => {
+            let warnings = warnings.into_iter().map(|warning| {
+                Diagnostic::new(
+                    file,
+                    article::position(content, &warning.field).unwrap_or((1, 1)),
+                    Severity::Warning,
+                    "frontmatter",
+                    warning.to_string(),
+                )
+            });
+            let limits = meta.limits(content).into_iter().map(|err| {
+                Diagnostic::new(
+                    file,
+                    article::position(content, &err.field).unwrap_or((1, 1)),
+                    Severity::Error,
+                    "limits",
+                    err.to_string(),
+                )
+            });
+            return warnings.chain(limits).collect();
+        }
+        Err(err) => err,
+    };
+    let position = if let Some(scan) = err.downcast_ref::<yaml_rust::ScanError>() {
+        // yaml starts on the line after the opening ---
+        (scan.marker().line() + 1, scan.marker().col() + 1)
+    } else if let Some(field) = err.downcast_ref::<FieldError>() {
+        article::position(content, &field.field).unwrap_or((1, 1))
+    } else {
+        (1, 1)
+    };
+    let message = err.to_string();
+    vec![Diagnostic::new(
+        file,
+        position,
+        Severity::Error,
+        "frontmatter",
+        message.lines().next().unwrap_or_default(),
+    )]
+}
+
+/// check the liquid tags within the body of a file
+fn liquid(
+    file: &str,
+    content: &str,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut open: Vec<(&str, (usize, usize))> = Vec::new();
+    let mut fenced = false;
+    let mut lines = content.lines().enumerate();
+    // skip frontmatter
+    if content.starts_with("---") {
+        lines.next();
+        for (_, line) in &mut lines {
+            if line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+    for (n, line) in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced {
+            continue;
+        }
+        let mut rest = line;
+        while let Some(start) = rest.find("{%") {
+            let column = line.len() - rest.len() + start + 1;
+            let position = (n + 1, column);
+            let after = &rest[start + 2..];
+            let end = match after.find("%}") {
+                Some(end) => end,
+                None => {
+                    diagnostics.push(Diagnostic::new(
+                        file,
+                        position,
+                        Severity::Error,
+                        "liquid",
+                        "liquid tag is missing a closing %}",
+                    ));
+                    break;
+                }
+            };
+            let mut words = after[..end].trim().trim_matches('-').split_whitespace();
+            let name = words.next().unwrap_or_default();
+            let has_args = words.next().is_some();
+            if let Some(block) = name.strip_prefix("end") {
+                match open.pop() {
+                    Some((opened, _)) if opened == block => (),
+                    Some((opened, at)) => {
+                        diagnostics.push(Diagnostic::new(
+                            file,
+                            position,
+                            Severity::Error,
+                            "liquid",
+                            format!("{} closes {} opened on line {}", name, opened, at.0),
+                        ));
+                    }
+                    None => diagnostics.push(Diagnostic::new(
+                        file,
+                        position,
+                        Severity::Error,
+                        "liquid",
+                        format!("{} has no matching {} tag", name, block),
+                    )),
+                }
+            } else if BLOCK_TAGS.contains(&name) {
+                open.push((name, position));
+            } else if EMBED_TAGS.contains(&name) {
+                if !has_args {
+                    diagnostics.push(Diagnostic::new(
+                        file,
+                        position,
+                        Severity::Error,
+                        "liquid",
+                        format!("{} tag is missing what to embed", name),
+                    ));
+                }
+            } else {
+                diagnostics.push(Diagnostic::new(
+                    file,
+                    position,
+                    Severity::Warning,
+                    "liquid",
+                    format!("unknown liquid tag {}", name),
+                ));
+            }
+            rest = &after[end + 2..];
+        }
+    }
+    for (name, position) in open {
+        diagnostics.push(Diagnostic::new(
+            file,
+            position,
+            Severity::Error,
+            "liquid",
+            format!("{} tag is never closed with end{}", name, name),
+        ));
+    }
+    diagnostics
+}
+
+/// check for headings which skip a level once demoted below the title
+fn headings(
+    file: &str,
+    content: &str,
+) -> Vec<Diagnostic> {
+    headings::skipped(content)
+        .into_iter()
+        .map(|(line, expected, found)| {
+            Diagnostic::new(
+                file,
+                (line, 1),
+                Severity::Warning,
+                "headings",
+                format!("h{} heading skips a level, expected h{}", found, expected),
+            )
+        })
+        .collect()
+}
+
+/// check for images without alt text, errors when alt text is required
+fn alt_text(
+    file: &str,
+    content: &str,
+    required: bool,
+) -> Vec<Diagnostic> {
+    let severity = if required {
+        Severity::Error
+    } else {
+        Severity::Warning
+    };
+    let mut diagnostics = Vec::new();
+    if let Ok((meta, _)) = article::extract(file, content) {
+        if meta.cover_image.is_some() && meta.cover_image_alt.is_none() {
+            diagnostics.push(Diagnostic::new(
+                file,
+                article::position(content, "cover_image").unwrap_or((1, 1)),
+                severity,
+                "alt-text",
+                "cover image is missing cover_image_alt",
+            ));
+        }
+    }
+    diagnostics.extend(images::missing_alt(content).into_iter().map(|position| {
+        Diagnostic::new(
+            file,
+            position,
+            severity,
+            "alt-text",
+            "image is missing alt text",
+        )
+    }));
+    diagnostics
+}
+
+/// all problems found in a file
+pub fn check(
+    file: &str,
+    content: &str,
+    strictness: Strictness,
+    zone: Zone,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = frontmatter(file, content, strictness, zone);
+    diagnostics.extend(liquid(file, content));
+    diagnostics
+}
+
+/// a SARIF 2.1.0 log of diagnostics
+fn sarif(diagnostics: &[Diagnostic]) -> Value {
+    let mut rules = diagnostics.iter().map(|d| d.code).collect::<Vec<_>>();
+    rules.sort_unstable();
+    rules.dedup();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "devtogo",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_HOMEPAGE"),
+                    "rules": rules.iter().map(|code| json!({ "id": code })).collect::<Vec<_>>(),
+                }
+            },
+            "results": diagnostics.iter().map(|d| json!({
+                "ruleId": d.code,
+                "level": match d.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                },
+                "message": { "text": d.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": d.file.replace('\\', "/").trim_start_matches("./"),
+                        },
+                        "region": {
+                            "startLine": d.line,
+                            "startColumn": d.column,
+                        }
+                    }
+                }]
+            })).collect::<Vec<_>>(),
+        }]
+    })
+}
+
+/// a test case per file, failing with its errors
+fn cases(
+    files: &[String],
+    diagnostics: &[Diagnostic],
+) -> Vec<Case> {
+    files
+        .iter()
+        .map(|file| {
+            let mut case = Case {
+                classname: "devtogo.lint".into(),
+                name: file.clone(),
+                ..Case::default()
+            };
+            for d in diagnostics.iter().filter(|d| &d.file == file) {
+                let message = format!("{}:{}: {} [{}]", d.line, d.column, d.message, d.code);
+                match d.severity {
+                    Severity::Error => case.failures.push(message),
+                    Severity::Warning => case.output.push(message),
+                }
+            }
+            case
+        })
+        .collect()
+}
+
+pub fn run(
+    config: &Config,
+    args: Lint,
+) -> anyhow::Result<()> {
+    let Lint {
+        source,
+        format,
+        prose,
+        check_code,
+        strict,
+        lenient,
+    } = args;
+    let strictness = Strictness::from_flags(strict, lenient);
+    let source = config.source(source);
+    for language in &check_code {
+        if !config.code_checks.contains_key(language) {
+            bail!(
+                "no command configured to check {} code\n  ▶ Add one under [code_checks] in devtogo.toml, i.e. {} = \"<command> {{file}}\"",
+                language,
+                language
+            );
+        }
+    }
+    let scratch = std::env::temp_dir().join(format!("devtogo-samples-{}", std::process::id()));
+    let blocklist = Blocklist::new(&config.blocklist)?;
+    let rules = Rules::new(&config.frontmatter)?;
+    let pusher = review::git_user();
+    let checker = if prose {
+        Some(prose::Checker::load(&config.prose)?)
+    } else {
+        None
+    };
+    let mut files = Vec::new();
+    let mut diagnostics = Vec::new();
+    for path in article::paths(&source, config) {
+        let content = article::read(config, &source, &path)?;
+        let file = path.to_string_lossy().to_string();
+        if config.title_from_heading {
+            // the title is added as the last line of frontmatter, so later lines are one down
+            let end = article::frontmatter_end(&content).unwrap_or_default() + 1;
+            let titled = article::title_from_heading(&content, false)?;
+            let shift = titled.len() != content.len();
+            diagnostics.extend(
+                check(&file, &titled, strictness, config.timezone)
+                    .into_iter()
+                    .map(|mut diagnostic| {
+                        if shift && diagnostic.line > end {
+                            diagnostic.line -= 1;
+                        }
+                        diagnostic
+                    }),
+            );
+        } else {
+            diagnostics.extend(check(&file, &content, strictness, config.timezone));
+        }
+        // listing too many tags is a limits error already, so only tags of directories pushing an
+        // article over the limit are reported here
+        let merged = article::merged_tags(config, &source, &path, &content).len();
+        if merged > article::MAX_TAGS && article::tags(&content).len() <= article::MAX_TAGS {
+            diagnostics.push(Diagnostic::new(
+                &file,
+                article::position(&content, "tags").unwrap_or((1, 1)),
+                Severity::Error,
+                "limits",
+                format!(
+                    "{} tags are listed with those of its directories. Forem allows at most {}",
+                    merged,
+                    article::MAX_TAGS
+                ),
+            ));
+        }
+        diagnostics.extend(rules.check(&content).into_iter().map(|violation| {
+            Diagnostic::new(
+                &file,
+                article::position(&content, &violation.field).unwrap_or((1, 1)),
+                Severity::Error,
+                "schema",
+                violation.to_string(),
+            )
+        }));
+        if config.require_review {
+            let unreviewed = article::extract(&file, &content)
+                .ok()
+                .and_then(|(meta, _)| review::unreviewed(&meta, pusher.as_deref()));
+            if let Some(reason) = unreviewed {
+                diagnostics.push(Diagnostic::new(
+                    &file,
+                    article::position(&content, "reviewed_by")
+                        .or_else(|| article::position(&content, "published"))
+                        .unwrap_or((1, 1)),
+                    Severity::Error,
+                    "review",
+                    reason,
+                ));
+            }
+        }
+        diagnostics.extend(alt_text(&file, &content, config.required_alt_text));
+        if config.normalize_headings {
+            diagnostics.extend(headings(&file, &content));
+        }
+        diagnostics.extend(blocklist.find(&content).into_iter().map(|found| {
+            Diagnostic::new(
+                &file,
+                (found.line, found.column),
+                Severity::Error,
+                "blocklist",
+                format!("{} is blocked from being published", found.term),
+            )
+        }));
+        if let Some(checker) = &checker {
+            diagnostics.extend(checker.check(&content).into_iter().map(|finding| {
+                Diagnostic::new(
+                    &file,
+                    (finding.line, finding.column),
+                    if finding.error {
+                        Severity::Error
+                    } else {
+                        Severity::Warning
+                    },
+                    finding.code,
+                    finding.message,
+                )
+            }));
+        }
+        for sample in samples::extract(&content, &check_code) {
+            let command = &config.code_checks[&sample.language];
+            if let Some(reported) = sample.check(command, &scratch)? {
+                diagnostics.push(Diagnostic::new(
+                    &file,
+                    (sample.line, 1),
+                    Severity::Error,
+                    "code",
+                    format!(
+                        "{} sample fails `{}`: {}",
+                        sample.language,
+                        command,
+                        reported.lines().next().unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+        files.push(file);
+    }
+    if scratch.exists() {
+        fs::remove_dir_all(&scratch)?;
+    }
+    match format {
+        Format::Text => {
+            for diagnostic in &diagnostics {
+                println!("{}", diagnostic);
+            }
+        }
+        Format::JsonDiagnostics => println!("{}", serde_json::to_string_pretty(&diagnostics)?),
+        Format::Sarif => println!("{}", serde_json::to_string_pretty(&sarif(&diagnostics))?),
+        Format::Junit => println!(
+            "{}",
+            junit::render("devtogo lint", &cases(&files, &diagnostics))
+        ),
+    }
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        bail!("{} error(s) found", errors);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alt_text_errors_when_required() {
+        let content = "---\ntitle: foo\ncover_image: a.png\n---\n![](b.png)\n";
+        let diagnostics = alt_text("foo.md", content, false);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].line, 3);
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+        assert!(alt_text("foo.md", content, true)
+            .iter()
+            .all(|d| d.severity == Severity::Error));
+    }
+
+    #[test]
+    fn headings_warns_about_skipped_levels() {
+        let diagnostics = headings("foo.md", "---\ntitle: foo\n---\n## a\n#### b\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 5);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn check_passes_valid_files() {
+        assert!(check(
+            "foo.md",
+            "---\ntitle: foo\n---\n{% youtube abc %}\n",
+            Strictness::Normal,
+            Zone::Utc
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn check_positions_invalid_frontmatter_fields() {
+        let diagnostics = check(
+            "foo.md",
+            "---\ntitle: foo\ndate: nope\n---\n",
+            Strictness::Normal,
+            Zone::Utc,
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 7));
+    }
+
+    #[test]
+    fn check_reports_missing_frontmatter() {
+        let diagnostics = check("foo.md", "# hello", Strictness::Normal, Zone::Utc);
+        assert_eq!(diagnostics[0].code, "frontmatter");
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (1, 1));
+    }
+
+    #[test]
+    fn liquid_reports_unbalanced_blocks() {
+        let diagnostics = liquid(
+            "foo.md",
+            "---\ntitle: foo\n---\n{% details hi %}\n{% endspoiler %}\n{% katex %}",
+        );
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (5, 1));
+        assert_eq!(diagnostics[1].line, 6);
+    }
+
+    #[test]
+    fn liquid_reports_embeds_without_arguments() {
+        let diagnostics = liquid("foo.md", "see {% github %}");
+        assert_eq!(diagnostics[0].column, 5);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn liquid_ignores_fenced_code() {
+        assert!(liquid("foo.md", "```\n{% nope\n```").is_empty());
+    }
+
+    #[test]
+    fn check_reports_strictness() {
+        let content = "---\ntitle: foo\nlayout: post\ncollection_id: nope\n---\n";
+        let diagnostics = check("foo.md", content, Strictness::Strict, Zone::Utc);
+        assert_eq!(
+            (diagnostics[0].line, diagnostics[0].code),
+            (3, "frontmatter")
+        );
+        let diagnostics = check("foo.md", content, Strictness::Lenient, Zone::Utc);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].line, 4);
+    }
+
+    #[test]
+    fn check_positions_limits() {
+        let diagnostics = check(
+            "foo.md",
+            "---\ntitle: foo\ntags: a, b, c, d, e\n---\n",
+            Strictness::Normal,
+            Zone::Utc,
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "limits");
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 7));
+    }
+
+    #[test]
+    fn sarif_locates_results() {
+        let log = sarif(&check(
+            "./posts/b.md",
+            "---\ntitle: foo\n---\n{% unknown %}",
+            Strictness::Normal,
+            Zone::Utc,
+        ));
+        let result = &log["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "liquid");
+        assert_eq!(result["level"], "warning");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "posts/b.md");
+        assert_eq!(location["region"]["startLine"], 4);
+        assert_eq!(log["runs"][0]["tool"]["driver"]["rules"][0]["id"], "liquid");
+    }
+
+    #[test]
+    fn cases_fail_files_with_errors() {
+        let files = vec!["a.md".to_string(), "b.md".to_string()];
+        let diagnostics = check(
+            "b.md",
+            "{% github %}\n{% unknown %}",
+            Strictness::Normal,
+            Zone::Utc,
+        );
+        let cases = cases(&files, &diagnostics);
+        assert!(cases[0].failures.is_empty());
+        assert_eq!(cases[1].failures.len(), 2);
+        assert_eq!(cases[1].output.len(), 1);
+    }
+
+    #[test]
+    fn liquid_warns_on_unknown_tags() {
+        let diagnostics = liquid("foo.md", "{% unknown %}");
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+    }
+}
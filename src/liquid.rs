@@ -0,0 +1,125 @@
+/// plain markdown or html for a single liquid tag, or none when it has no equivalent
+fn convert(
+    name: &str,
+    args: &[&str],
+) -> Option<String> {
+    let arg = args.first().copied().unwrap_or_default();
+    let url = |base: &str| {
+        if arg.starts_with("http") {
+            arg.to_string()
+        } else {
+            format!("{}{}", base, arg)
+        }
+    };
+    Some(match name {
+        "youtube" => format!(
+            "<iframe width=\"710\" height=\"399\" src=\"https://www.youtube.com/embed/{}\" allowfullscreen></iframe>",
+            arg
+        ),
+        "vimeo" => format!(
+            "<iframe width=\"710\" height=\"399\" src=\"https://player.vimeo.com/video/{}\" allowfullscreen></iframe>",
+            arg
+        ),
+        "github" => {
+            let url = url("https://github.com/");
+            format!(
+                "[{}]({})",
+                url.trim_start_matches("https://github.com/"),
+                url
+            )
+        }
+        "tweet" | "twitter" => format!("[tweet]({})", url("https://twitter.com/i/status/")),
+        "details" | "spoiler" | "collapsible" => {
+            format!("<details><summary>{}</summary>\n", args.join(" "))
+        }
+        "enddetails" | "endspoiler" | "endcollapsible" => "</details>".into(),
+        "katex" | "endkatex" => "$$".into(),
+        "raw" | "endraw" => String::new(),
+        _ if arg.starts_with("http") => format!("<{}>", arg),
+        _ => return None,
+    })
+}
+
+/// replace dev.to liquid tags with plain markdown so content renders outside of Forem.
+/// Tags without a plain equivalent, frontmatter and fenced code are left as is
+pub fn to_markdown(content: &str) -> String {
+    let mut converted = Vec::new();
+    let mut fenced = false;
+    let mut lines = content.split('\n');
+    if content.starts_with("---") {
+        for line in &mut lines {
+            converted.push(line.to_string());
+            if converted.len() > 1 && line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+    for line in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        }
+        if fenced || !line.contains("{%") {
+            converted.push(line.to_string());
+            continue;
+        }
+        let mut out = String::new();
+        let mut rest = line;
+        while let Some(start) = rest.find("{%") {
+            let after = &rest[start + 2..];
+            let end = match after.find("%}") {
+                Some(end) => end,
+                None => break,
+            };
+            let mut words = after[..end].trim_matches('-').split_whitespace();
+            let name = words.next().unwrap_or_default();
+            let args = words.collect::<Vec<_>>();
+            out.push_str(&rest[..start]);
+            match convert(name, &args) {
+                Some(replacement) => out.push_str(&replacement),
+                None => out.push_str(&rest[start..start + 2 + end + 2]),
+            }
+            rest = &after[end + 2..];
+        }
+        out.push_str(rest);
+        converted.push(out);
+    }
+    converted.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_markdown_converts_embeds() {
+        assert_eq!(
+            to_markdown("see {% github softprops/devtogo %}"),
+            "see [softprops/devtogo](https://github.com/softprops/devtogo)"
+        );
+        assert_eq!(
+            to_markdown("{% tweet 123 %}"),
+            "[tweet](https://twitter.com/i/status/123)"
+        );
+        assert_eq!(
+            to_markdown("{% embed https://example.com %}"),
+            "<https://example.com>"
+        );
+        assert!(to_markdown("{% youtube abc %}").contains("youtube.com/embed/abc"));
+    }
+
+    #[test]
+    fn to_markdown_converts_blocks() {
+        assert_eq!(
+            to_markdown("{% details more %}\nhi\n{% enddetails %}"),
+            "<details><summary>more</summary>\n\nhi\n</details>"
+        );
+    }
+
+    #[test]
+    fn to_markdown_leaves_frontmatter_code_and_unknown_tags() {
+        let content =
+            "---\ntitle: {% github a/b %}\n---\n```\n{% github a/b %}\n```\n{% unknown %}";
+        assert_eq!(to_markdown(content), content);
+    }
+}
@@ -0,0 +1,311 @@
+use crate::template::{self, Fields, Lines};
+use anyhow::anyhow;
+use colored::Colorize;
+use std::{fmt, str::FromStr};
+
+/// How a push orders files' status lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sort {
+    Title,
+    Date,
+    Status,
+}
+
+/// Names of orders, as written in --sort
+pub const SORTS: &[&str] = &["title", "date", "status"];
+
+impl FromStr for Sort {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(Sort::Title),
+            "date" => Ok(Sort::Date),
+            "status" => Ok(Sort::Status),
+            other => Err(anyhow!("unsupported sort {}", other)),
+        }
+    }
+}
+
+/// What a push groups files' status lines under
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Status,
+}
+
+/// Names of groupings, as written in --group-by
+pub const GROUPS: &[&str] = &["status"];
+
+impl FromStr for GroupBy {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "status" => Ok(GroupBy::Status),
+            other => Err(anyhow!("unsupported grouping {}", other)),
+        }
+    }
+}
+
+/// Statuses needing action, listed first and in this order when grouping or sorting by status
+const ACTIONS: &[&str] = &["POSTING", "SYNCING"];
+
+/// Status of files already in sync, listed last and collapsed to a count when grouping
+const UNCHANGED: &str = "UPLOADED";
+
+/// A file's status line with the lines detailing it
+struct Entry {
+    status: String,
+    title: String,
+    date: Option<String>,
+    lines: Vec<String>,
+}
+
+/// Prints files' status lines as they come, or holds them back to print grouped or sorted
+pub struct Listing {
+    lines: Lines,
+    group_by: Option<GroupBy>,
+    sort: Option<Sort>,
+    /// whether to count files already in sync rather than list them
+    quiet_unchanged: bool,
+    entries: Vec<Entry>,
+    unchanged: usize,
+}
+
+/// the uppercase words of a status, i.e. SYNCING of "SYNCING published"
+fn status_name(status: &str) -> String {
+    template::plain(status)
+        .split_whitespace()
+        .filter(|word| !word.chars().any(char::is_lowercase))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// where a status is listed relative to others
+fn rank(status: &str) -> usize {
+    match ACTIONS.iter().position(|action| *action == status) {
+        Some(position) => position,
+        None if status == UNCHANGED => ACTIONS.len() + 1,
+        None => ACTIONS.len(),
+    }
+}
+
+impl Listing {
+    pub fn new(
+        lines: Lines,
+        group_by: Option<GroupBy>,
+        sort: Option<Sort>,
+        quiet_unchanged: bool,
+    ) -> Self {
+        Listing {
+            lines,
+            group_by,
+            sort,
+            quiet_unchanged,
+            entries: Vec::new(),
+            unchanged: 0,
+        }
+    }
+
+    /// the header of a table, if printing one
+    pub fn header(&self) -> Option<String> {
+        self.lines.header()
+    }
+
+    /// a file's status line, printed straight away
+    pub fn line(
+        &self,
+        fields: &Fields<'_>,
+        status: impl fmt::Display,
+    ) -> String {
+        self.lines.line(fields, status)
+    }
+
+    /// lines to print for a file's status, none while they're held back
+    pub fn status(
+        &mut self,
+        fields: &Fields<'_>,
+        status: impl fmt::Display,
+    ) -> Vec<String> {
+        let status = status.to_string();
+        if self.quiet_unchanged && status_name(&status) == UNCHANGED {
+            self.unchanged += 1;
+            return Vec::new();
+        }
+        let line = self.lines.line(fields, &status);
+        if self.group_by.is_none() && self.sort.is_none() {
+            return vec![line];
+        }
+        self.entries.push(Entry {
+            status: status_name(&status),
+            title: fields.title.to_lowercase(),
+            date: fields.date.map(String::from),
+            lines: vec![line],
+        });
+        Vec::new()
+    }
+
+    /// lines to print for a detail of the last file's status, none while it's held back
+    pub fn detail(
+        &mut self,
+        text: impl fmt::Display,
+    ) -> Vec<String> {
+        match self.entries.last_mut() {
+            Some(entry) => {
+                entry.lines.push(text.to_string());
+                Vec::new()
+            }
+            None => vec![text.to_string()],
+        }
+    }
+
+    /// the lines held back, grouped and sorted, followed by a count of files left out as unchanged
+    pub fn flush(&mut self) -> Vec<String> {
+        let mut out = self.held();
+        if self.unchanged > 0 {
+            out.push(format!("{} file(s) up to date", self.unchanged));
+            self.unchanged = 0;
+        }
+        out
+    }
+
+    /// the lines held back, grouped and sorted
+    fn held(&mut self) -> Vec<String> {
+        let mut entries = std::mem::take(&mut self.entries);
+        match self.sort {
+            Some(Sort::Title) => entries.sort_by(|a, b| a.title.cmp(&b.title)),
+            // undated files last
+            Some(Sort::Date) => {
+                entries.sort_by_key(|entry| (entry.date.is_none(), entry.date.clone()))
+            }
+            Some(Sort::Status) => entries.sort_by_key(|entry| rank(&entry.status)),
+            None => (),
+        }
+        match self.group_by {
+            None => entries.into_iter().flat_map(|entry| entry.lines).collect(),
+            Some(GroupBy::Status) => {
+                let mut groups: Vec<(String, Vec<Entry>)> = Vec::new();
+                for entry in entries {
+                    match groups
+                        .iter_mut()
+                        .find(|(status, _)| *status == entry.status)
+                    {
+                        Some((_, group)) => group.push(entry),
+                        None => groups.push((entry.status.clone(), vec![entry])),
+                    }
+                }
+                groups.sort_by_key(|(status, _)| rank(status));
+                let mut out = Vec::new();
+                for (status, group) in groups {
+                    out.push(format!("▶ {} ({})", status, group.len()).bold().to_string());
+                    if status != UNCHANGED {
+                        out.extend(group.into_iter().flat_map(|entry| entry.lines));
+                    }
+                }
+                out
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields<'a>(
+        title: &'a str,
+        date: Option<&'a str>,
+    ) -> Fields<'a> {
+        Fields {
+            target: "devto",
+            file: "posts/a.md",
+            title,
+            url: None,
+            date,
+            published: None,
+        }
+    }
+
+    #[test]
+    fn groups_put_actions_first_and_collapse_unchanged() -> anyhow::Result<()> {
+        let mut listing = Listing::new(
+            Lines::new(Some("{title}".parse()?), Vec::new()),
+            Some(GroupBy::Status),
+            None,
+            false,
+        );
+        assert!(listing
+            .status(&fields("a", None), "UPLOADED draft")
+            .is_empty());
+        assert!(listing
+            .status(&fields("b", None), "SYNCING published")
+            .is_empty());
+        assert!(listing
+            .status(&fields("c", None), "NOT OWNED".red())
+            .is_empty());
+        assert!(listing.detail("  ▶ owned by ada").is_empty());
+        assert!(listing
+            .status(&fields("d", None), "POSTING draft")
+            .is_empty());
+        assert!(listing
+            .status(&fields("e", None), "UPLOADED published")
+            .is_empty());
+        assert_eq!(
+            listing
+                .flush()
+                .iter()
+                .map(|line| template::plain(line))
+                .collect::<Vec<_>>(),
+            vec![
+                "▶ POSTING (1)",
+                "d",
+                "▶ SYNCING (1)",
+                "b",
+                "▶ NOT OWNED (1)",
+                "c",
+                "  ▶ owned by ada",
+                "▶ UPLOADED (2)",
+            ]
+        );
+        assert!(listing.flush().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn sorts_by_date_with_undated_last() -> anyhow::Result<()> {
+        let mut listing = Listing::new(
+            Lines::new(Some("{title}".parse()?), Vec::new()),
+            None,
+            Some(Sort::Date),
+            false,
+        );
+        listing.status(&fields("c", None), "POSTING");
+        listing.status(&fields("b", Some("2024-02-01")), "POSTING");
+        listing.status(&fields("a", Some("2023-12-24")), "POSTING");
+        assert_eq!(listing.flush(), vec!["a", "b", "c"]);
+        let mut listing = Listing::new(Lines::new(None, Vec::new()), None, None, false);
+        assert_eq!(listing.detail("shown"), vec!["shown"]);
+        Ok(())
+    }
+
+    #[test]
+    fn quiet_unchanged_counts_files_in_sync() -> anyhow::Result<()> {
+        let mut listing = Listing::new(
+            Lines::new(Some("{status} {title}".parse()?), Vec::new()),
+            None,
+            None,
+            true,
+        );
+        assert!(listing
+            .status(&fields("a", None), "UPLOADED".green())
+            .is_empty());
+        assert_eq!(
+            listing.status(&fields("b", None), "POSTING draft"),
+            vec!["POSTING draft b"]
+        );
+        assert!(listing
+            .status(&fields("c", None), "UPLOADED published")
+            .is_empty());
+        assert_eq!(listing.flush(), vec!["2 file(s) up to date"]);
+        assert!(listing.flush().is_empty());
+        Ok(())
+    }
+}
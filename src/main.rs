@@ -1,20 +1,246 @@
+mod api;
+mod article;
+mod audit;
+mod backend;
+mod blocklist;
+mod board;
+mod breaker;
+mod canonicals;
+mod changelog;
+mod checklist;
+mod comments;
+mod config;
+mod console;
+mod convert;
+mod cover;
+mod daemon;
+mod dates;
+mod dedupe;
+mod diagrams;
+mod digest;
+mod docs;
+mod duration;
+mod email;
+mod estimate;
+mod export;
+mod feed;
+mod footnotes;
+mod gate;
+mod generate;
+mod hashnode;
+mod headings;
+mod history;
+mod hook;
+mod images;
+mod init;
+mod junit;
+mod limits;
+mod lint;
+mod liquid;
+mod listing;
+mod math;
+mod mdx;
+mod medium;
+mod metrics;
+mod ndjson;
+mod notebook;
+mod notify;
+mod oauth;
+mod org;
+mod owners;
+mod preflight;
+mod profile;
+mod prose;
 mod push;
+mod read;
+mod recording;
+mod release;
+mod report;
+mod retry;
+mod review;
+mod samples;
+mod schema;
+mod search;
+mod secrets;
+mod service;
+mod settings;
+mod snippets;
+mod state;
+mod stats;
+mod sync;
+mod takeout;
+mod template;
+mod translations;
+mod typography;
+mod update;
+mod vault;
+mod verify;
+mod webhooks;
+mod yaml;
 
+use config::Config;
 use push::Push;
-use std::env;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
+/// A dev.to tool for the road 👩🏽‍💻🎒
+///
+/// Uploads local markdown files with dev.to
+#[derive(StructOpt, Debug)]
+struct Opts {
+    /// Path to a devtogo.toml config file
+    #[structopt(long, global = true, default_value = "devtogo.toml")]
+    config: PathBuf,
+    /// Forem api version to request, i.e. 1. Overrides api_version in devtogo.toml
+    #[structopt(long, global = true)]
+    api_version: Option<String>,
+    /// Print ascii in place of symbols, i.e. for terminals without unicode fonts
+    #[structopt(long, global = true)]
+    ascii: bool,
+    /// Record every http request and response to this directory, with secrets redacted, i.e. to
+    /// share when reporting a failed sync
+    #[structopt(long, global = true, conflicts_with = "replay")]
+    record_http: Option<PathBuf>,
+    /// Replay http responses recorded with --record-http in place of sending requests, offline and
+    /// without credentials
+    #[structopt(long, global = true)]
+    replay: Option<PathBuf>,
+    #[structopt(flatten)]
+    push: Push,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    Push(Push),
+    Sync(sync::Sync),
+    History(history::History),
+    Show(history::Show),
+    Diff(history::Diff),
+    Export(export::Export),
+    Lint(lint::Lint),
+    Hook(hook::Hook),
+    Verify(verify::Verify),
+    Limits(limits::Limits),
+    Login(oauth::Login),
+    Translations(translations::Translations),
+    Generate(generate::Generate),
+    /// Renders a social card style cover image for an article and sets it as its cover_image
+    Cover(cover::Cover),
+    /// Writes a release announcement article from a release's section of a changelog
+    FromChangelog(changelog::FromChangelog),
+    /// Publishes drafts one at a time, spaced out so followers aren't flooded with new articles
+    Release(release::Release),
+    /// Shows views, reactions and comments for your published articles
+    Stats(stats::Stats),
+    /// Stays running, syncing every --interval, for continuous mirroring without cron
+    Daemon(daemon::Daemon),
+    Service(service::Service),
+    SelfUpdate(update::SelfUpdate),
+    Docs(docs::Docs),
+    /// Sets up devtogo.toml, asking for an api key, source directory and where to publish
+    Init(init::Init),
+    Config(settings::Settings),
+    Board(board::Board),
+    Audit(audit::Audit),
+    Feed(feed::Feed),
+    Read(read::Read),
+    Search(search::Search),
+    Dedupe(dedupe::Dedupe),
+    AuditCanonicals(canonicals::AuditCanonicals),
+    RewriteCanonicals(canonicals::RewriteCanonicals),
+    Org(org::Org),
+    Digest(digest::Digest),
+    Comments(comments::Comments),
+    Webhooks(webhooks::Webhooks),
+    Profile(profile::Profile),
+    Takeout(takeout::Takeout),
+    /// Pushes again only the files which failed to push before, taking the same options as push
+    Retry(retry::Retry),
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     env_logger::init();
-    let args = Push::from_args();
-    push::run(
-        env::var("DEVTO_API_KEY")
-            .map_err(|_| anyhow::anyhow!(
-                "Please export a DEVTO_API_KEY env variable.\n  ▶ You can generate one by visiting https://dev.to/settings/account"
-            ))?,
-        args,
-    )
-    .await?;
+    let Opts {
+        config,
+        api_version,
+        ascii,
+        record_http,
+        replay,
+        push,
+        command,
+    } = Opts::from_args();
+    console::init(ascii);
+    let config_path = config;
+    let mut config = Config::load(&config_path)?;
+    if let Some(version) = api_version {
+        config.set_api_version(version);
+    }
+    vault::init(config.encrypt_state);
+    recording::init(record_http, replay)?;
+    let command = command.unwrap_or(Command::Push(push));
+    let notify = config.update_check
+        && !recording::replaying()
+        && !matches!(command, Command::SelfUpdate(_));
+    let result = run(&config, &config_path, command).await;
+    if notify {
+        update::notify().await;
+    }
+    match result {
+        // errors are printed by the runtime, so their symbols are replaced here
+        Err(err) if ascii => Err(anyhow::anyhow!("{}", console::plain(&format!("{:#}", err)))),
+        result => result,
+    }
+}
+
+async fn run(
+    config: &Config,
+    config_path: &Path,
+    command: Command,
+) -> anyhow::Result<()> {
+    match command {
+        Command::Push(args) => push::run(config, args).await?,
+        Command::Sync(args) => sync::run(config, args).await?,
+        Command::History(args) => history::history(config, args)?,
+        Command::Show(args) => history::show(config, args)?,
+        Command::Diff(args) => history::diff(config, args).await?,
+        Command::Export(args) => export::run(config, args).await?,
+        Command::Lint(args) => lint::run(config, args)?,
+        Command::Hook(args) => hook::run(args)?,
+        Command::Verify(args) => verify::run(config, args).await?,
+        Command::Limits(args) => limits::run(config, args).await?,
+        Command::Login(args) => oauth::login(config, args).await?,
+        Command::Translations(args) => translations::run(config, args)?,
+        Command::Generate(args) => generate::run(config, args)?,
+        Command::Cover(args) => cover::run(config, args)?,
+        Command::FromChangelog(args) => changelog::run(config, args).await?,
+        Command::Release(args) => release::run(config, args).await?,
+        Command::Stats(args) => stats::run(config, args).await?,
+        Command::Daemon(args) => daemon::run(config, args).await?,
+        Command::Service(args) => service::run(config, config_path, args)?,
+        Command::SelfUpdate(args) => update::run(args).await?,
+        Command::Docs(args) => docs::run(args, Opts::clap())?,
+        Command::Init(args) => init::run(config_path, args).await?,
+        Command::Config(args) => settings::run(config, config_path, args)?,
+        Command::Board(args) => board::run(config, args)?,
+        Command::Audit(args) => audit::run(config, args)?,
+        Command::Feed(args) => feed::run(config, args).await?,
+        Command::Read(args) => read::run(config, args).await?,
+        Command::Search(args) => search::run(config, args).await?,
+        Command::Dedupe(args) => dedupe::run(config, args).await?,
+        Command::AuditCanonicals(args) => canonicals::run(config, args).await?,
+        Command::RewriteCanonicals(args) => {
+            canonicals::rewrite_canonicals(config, config_path, args).await?
+        }
+        Command::Org(args) => org::run(config, args).await?,
+        Command::Digest(args) => digest::run(config, args).await?,
+        Command::Comments(args) => comments::run(config, args).await?,
+        Command::Webhooks(args) => webhooks::run(config, args).await?,
+        Command::Profile(args) => profile::run(config, args).await?,
+        Command::Takeout(args) => takeout::run(config, args).await?,
+        Command::Retry(args) => retry::run(config, args).await?,
+    }
     Ok(())
 }
@@ -0,0 +1,188 @@
+use crate::{config::Kind, typography};
+use serde::{Deserialize, Serialize};
+
+/// Settings for converting `$..$` and `$$..$$` math into something publishing services display
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Math {
+    /// Url of a service rendering LaTeX to images, with {tex} replaced by an equation. Used
+    /// where the katex liquid tag isn't supported
+    pub image_url: String,
+}
+
+impl Default for Math {
+    fn default() -> Self {
+        Math {
+            image_url: "https://latex.codecogs.com/png.image?{tex}".into(),
+        }
+    }
+}
+
+/// percent encode all but unreserved url characters
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+impl Math {
+    /// an equation as markup a kind of service displays
+    fn equation(
+        &self,
+        kind: Kind,
+        tex: &str,
+        display: bool,
+    ) -> String {
+        match (kind, display) {
+            (Kind::Forem, true) => format!("{{% katex %}}\n{}\n{{% endkatex %}}", tex),
+            (Kind::Forem, false) => format!("{{% katex inline %}}{}{{% endkatex %}}", tex),
+            _ => {
+                let tex = tex.split_whitespace().collect::<Vec<_>>().join(" ");
+                let alt = tex.replace('[', "\\[").replace(']', "\\]");
+                let url = self.image_url.replace("{tex}", &encode(&tex));
+                format!("![{}]({})", alt, url)
+            }
+        }
+    }
+}
+
+/// inline `$..$` math in a line, skipping code, markup and dollar amounts. Openings are followed
+/// by, and closings preceded by, something other than a space, and closings aren't followed by
+/// a digit, as in pandoc
+fn inline(
+    math: &Math,
+    kind: Kind,
+    line: &str,
+) -> String {
+    let mut out = String::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if let Some(end) = typography::protected(rest) {
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        if rest.starts_with("\\$") {
+            out.push_str("\\$");
+            rest = &rest[2..];
+            continue;
+        }
+        if c == '$' && !rest.starts_with("$$") {
+            // the next unescaped dollar closes, if anything does
+            let closing = rest[1..]
+                .char_indices()
+                .find(|(i, c)| *c == '$' && !rest[1..1 + i].ends_with('\\'))
+                .map(|(i, _)| i);
+            if let Some(i) = closing {
+                let tex = &rest[1..1 + i];
+                if !tex.is_empty()
+                    && !tex.starts_with(' ')
+                    && !tex.ends_with(' ')
+                    && !rest[2 + i..].starts_with(|c: char| c.is_ascii_digit())
+                {
+                    out.push_str(&math.equation(kind, tex, false));
+                    rest = &rest[2 + i..];
+                    continue;
+                }
+            }
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// convert math outside of frontmatter and code for a kind of service: the katex liquid tag
+/// for Forem and images everywhere else
+pub fn render(
+    math: &Math,
+    kind: Kind,
+    content: &str,
+) -> String {
+    let mut out = Vec::new();
+    let mut fenced = false;
+    let mut lines = content.split('\n');
+    if content.starts_with("---") {
+        for line in &mut lines {
+            out.push(line.to_string());
+            if out.len() > 1 && line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+    let lines = lines.collect::<Vec<_>>();
+    let mut n = 0;
+    while n < lines.len() {
+        let line = lines[n];
+        let trimmed = line.trim();
+        n += 1;
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            out.push(line.to_string());
+        } else if fenced {
+            out.push(line.to_string());
+        } else if let Some(open) = trimmed.strip_prefix("$$") {
+            if let Some(tex) = open.strip_suffix("$$") {
+                out.push(math.equation(kind, tex.trim(), true));
+                continue;
+            }
+            let close = lines[n..]
+                .iter()
+                .position(|line| line.trim_end().ends_with("$$"));
+            match close {
+                Some(close) => {
+                    let mut tex = vec![open.trim()];
+                    tex.extend(lines[n..n + close].iter().map(|line| line.trim_end()));
+                    tex.push(lines[n + close].trim_end().trim_end_matches("$$").trim());
+                    let tex = tex
+                        .into_iter()
+                        .filter(|line| !line.is_empty())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    out.push(math.equation(kind, &tex, true));
+                    n += close + 1;
+                }
+                None => out.push(line.to_string()),
+            }
+        } else {
+            out.push(inline(math, kind, line));
+        }
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_uses_katex_on_forem() {
+        assert_eq!(
+            render(
+                &Math::default(),
+                Kind::Forem,
+                "---\ntitle: $x$\n---\nwhere $e^{i\\pi} + 1 = 0$ and\n$$\n\\sum_{n=1}^\\infty n\n$$\n`$x$`"
+            ),
+            "---\ntitle: $x$\n---\nwhere {% katex inline %}e^{i\\pi} + 1 = 0{% endkatex %} and\n{% katex %}\n\\sum_{n=1}^\\infty n\n{% endkatex %}\n`$x$`"
+        );
+    }
+
+    #[test]
+    fn render_uses_images_elsewhere() {
+        assert_eq!(
+            render(&Math::default(), Kind::Medium, "$$a^2 + b^2$$"),
+            "![a^2 + b^2](https://latex.codecogs.com/png.image?a%5E2%20%2B%20b%5E2)"
+        );
+    }
+
+    #[test]
+    fn render_leaves_dollar_amounts_alone() {
+        let content = "It costs $5 or $10, and $ y $ or \\$x\n```\n$$\n```";
+        assert_eq!(render(&Math::default(), Kind::Forem, content), content);
+    }
+}
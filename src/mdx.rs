@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// MDX file extension
+pub const EXTENSION: &str = "mdx";
+
+/// How MDX articles are turned into plain markdown
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Mdx {
+    /// markdown to replace each JSX component with, by component name. `{children}` and
+    /// `{<prop>}` are replaced with the component's content and props. Components without
+    /// one are replaced with their children
+    #[serde(default)]
+    pub components: BTreeMap<String, String>,
+}
+
+/// a JSX tag starting at the beginning of `text`: its name, props, whether it closes itself
+/// and its length
+fn tag(text: &str) -> Option<(String, BTreeMap<String, String>, bool, usize)> {
+    let rest = text.strip_prefix('<')?;
+    if !rest.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return None;
+    }
+    let name_len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+        .unwrap_or(rest.len());
+    let name = rest[..name_len].to_string();
+    let mut props = BTreeMap::new();
+    let mut chars = rest[name_len..].char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => (),
+            '>' => return Some((name, props, false, 1 + name_len + i + 1)),
+            '/' if matches!(chars.peek(), Some((_, '>'))) => {
+                return Some((name, props, true, 1 + name_len + i + 2))
+            }
+            _ => {
+                let mut prop = c.to_string();
+                while let Some((_, c)) =
+                    chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+                {
+                    prop.push(c);
+                }
+                if chars.next_if(|(_, c)| *c == '=').is_none() {
+                    props.insert(prop, "true".into());
+                    continue;
+                }
+                let mut value = String::new();
+                match chars.next()? {
+                    (_, quote @ ('"' | '\'')) => {
+                        for (_, c) in chars.by_ref() {
+                            if c == quote {
+                                break;
+                            }
+                            value.push(c);
+                        }
+                    }
+                    (_, '{') => {
+                        let mut depth = 1;
+                        for (_, c) in chars.by_ref() {
+                            depth += match c {
+                                '{' => 1,
+                                '}' => -1,
+                                _ => 0,
+                            };
+                            if depth == 0 {
+                                break;
+                            }
+                            value.push(c);
+                        }
+                        let trimmed = value.trim();
+                        value = trimmed
+                            .strip_prefix(['"', '\'', '`'])
+                            .and_then(|v| v.strip_suffix(['"', '\'', '`']))
+                            .unwrap_or(trimmed)
+                            .to_string();
+                    }
+                    _ => return None,
+                }
+                props.insert(prop, value);
+            }
+        }
+    }
+    None
+}
+
+/// where the closing tag matching a component opened before `text` starts and ends
+fn closing(
+    text: &str,
+    name: &str,
+) -> Option<(usize, usize)> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut depth = 0;
+    let mut offset = 0;
+    while let Some(i) = text[offset..].find('<') {
+        let at = offset + i;
+        let rest = &text[at..];
+        if rest.starts_with(&close) {
+            if depth == 0 {
+                return Some((at, at + close.len()));
+            }
+            depth -= 1;
+        } else if rest.starts_with(&open) {
+            if let Some((tag_name, _, self_closing, _)) = tag(rest) {
+                if tag_name == name && !self_closing {
+                    depth += 1;
+                }
+            }
+        }
+        offset = at + 1;
+    }
+    None
+}
+
+fn replace(
+    mdx: &Mdx,
+    name: &str,
+    props: &BTreeMap<String, String>,
+    children: &str,
+) -> String {
+    match mdx.components.get(name) {
+        Some(template) => {
+            let mut out = template.replace("{children}", children.trim());
+            for (prop, value) in props {
+                out = out.replace(&format!("{{{}}}", prop), value);
+            }
+            out
+        }
+        None => children.trim().to_string(),
+    }
+}
+
+/// replace JSX components in prose, leaving inline code alone
+fn components(
+    mdx: &Mdx,
+    text: &str,
+) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find(['<', '`']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with('`') {
+            let end = rest[1..].find('`').map(|e| e + 2).unwrap_or(rest.len());
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        match tag(rest) {
+            Some((name, props, true, len)) => {
+                out.push_str(&replace(mdx, &name, &props, ""));
+                rest = &rest[len..];
+            }
+            Some((name, props, false, len)) => match closing(&rest[len..], &name) {
+                Some((start, end)) => {
+                    let children = components(mdx, &rest[len..len + start]);
+                    out.push_str(&replace(mdx, &name, &props, &children));
+                    rest = &rest[len + end..];
+                }
+                None => {
+                    out.push('<');
+                    rest = &rest[1..];
+                }
+            },
+            None => {
+                out.push('<');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// markdown for an MDX article, dropping import and export statements and replacing JSX
+/// components. Frontmatter and fenced code are left as is
+pub fn to_markdown(
+    mdx: &Mdx,
+    content: &str,
+) -> String {
+    let mut out = Vec::new();
+    let mut prose = Vec::new();
+    let mut fenced = false;
+    let mut lines = content.split('\n');
+    if content.starts_with("---") {
+        for line in &mut lines {
+            out.push(line.to_string());
+            if out.len() > 1 && line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+    for line in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            if !fenced && !prose.is_empty() {
+                out.push(components(mdx, &prose.join("\n")));
+                prose.clear();
+            }
+            fenced = !fenced;
+            out.push(line.to_string());
+            continue;
+        }
+        if fenced {
+            out.push(line.to_string());
+        } else if !(line.starts_with("import ") || line.starts_with("export ")) {
+            prose.push(line);
+        }
+    }
+    if !prose.is_empty() {
+        out.push(components(mdx, &prose.join("\n")));
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_markdown_replaces_components() {
+        let mdx = Mdx {
+            components: vec![
+                ("Callout".to_string(), "> **{type}** {children}".to_string()),
+                ("YouTube".to_string(), "{% youtube {id} %}".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let content = "---\ntitle: <Foo />\n---\nimport Chart from './chart'\n\n<Callout type=\"tip\">\n  Use <Kbd>Ctrl</Kbd> `<Kbd />`\n</Callout>\n\n<YouTube id={\"abc\"} />\n<Chart data={[1, 2]} />\n```jsx\n<Chart />\n```\n";
+        assert_eq!(
+            to_markdown(&mdx, content),
+            "---\ntitle: <Foo />\n---\n\n> **tip** Use Ctrl `<Kbd />`\n\n{% youtube abc %}\n\n```jsx\n<Chart />\n```\n"
+        );
+    }
+
+    #[test]
+    fn to_markdown_leaves_html_alone() {
+        let content = "a <b>bold</b> 1 < 2 <Unclosed>";
+        assert_eq!(to_markdown(&Mdx::default(), content), content);
+    }
+}
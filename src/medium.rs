@@ -0,0 +1,155 @@
+use crate::{
+    api::{self, Article},
+    backend::{Backend, Post, Request, REDACTED},
+    config::Target,
+    recording,
+};
+use anyhow::{anyhow, bail};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+/// Medium wraps every response in a data field
+#[derive(Debug, Deserialize)]
+struct Data<T> {
+    data: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Created {
+    id: String,
+    title: String,
+    url: String,
+}
+
+/// Publishes to a Medium account. Medium's api can create posts but can neither list nor update them
+pub struct Medium {
+    http: reqwest::Client,
+    url: String,
+    token: String,
+}
+
+impl Medium {
+    pub fn new(target: &Target) -> anyhow::Result<Self> {
+        Ok(Medium {
+            http: api::http()?,
+            url: target.base_url().into(),
+            token: target.api_key()?,
+        })
+    }
+
+    async fn parse<T: DeserializeOwned>(resp: reqwest::Response) -> anyhow::Result<T> {
+        let status = resp.status();
+        if !status.is_success() {
+            bail!("Medium error {:#?} {}", status, resp.text().await?);
+        }
+        Ok(resp.json::<Data<T>>().await?.data)
+    }
+}
+
+/// the error updating a post fails with, as Medium's api can't
+fn unsupported(id: &str) -> anyhow::Error {
+    anyhow!(
+        "Medium's api does not support updating posts. Please edit post {} on medium.com",
+        id
+    )
+}
+
+/// the post Medium creates for a local one
+fn body(post: &Post<'_>) -> Value {
+    let tags = post
+        .meta
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .take(5)
+        .collect::<Vec<_>>();
+    json!({
+        "title": post.meta.title,
+        "contentFormat": "markdown",
+        "content": format!("# {}\n\n{}", post.meta.title, post.body),
+        "tags": tags,
+        "canonicalUrl": post.meta.canonical_url,
+        "publishStatus": if post.meta.published.unwrap_or_default() { "public" } else { "draft" },
+    })
+}
+
+#[async_trait]
+impl Backend for Medium {
+    async fn articles(&self) -> anyhow::Result<Vec<Article>> {
+        Ok(Vec::new())
+    }
+
+    async fn create(
+        &self,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Article> {
+        let user: User = Medium::parse(
+            recording::send(
+                self.http
+                    .get(format!("{}/me", self.url).as_str())
+                    .bearer_auth(&self.token),
+            )
+            .await?,
+        )
+        .await?;
+        let created: Created = Medium::parse(
+            recording::send(
+                self.http
+                    .post(format!("{}/users/{}/posts", self.url, user.id).as_str())
+                    .bearer_auth(&self.token)
+                    .json(&body(post)),
+            )
+            .await?,
+        )
+        .await?;
+        Ok(Article {
+            id: created.id,
+            title: created.title,
+            published: post.meta.published.unwrap_or_default(),
+            url: created.url,
+            ..Article::default()
+        })
+    }
+
+    async fn update(
+        &self,
+        id: &str,
+        _: &Post<'_>,
+    ) -> anyhow::Result<Article> {
+        Err(unsupported(id))
+    }
+
+    fn request(
+        &self,
+        id: Option<&str>,
+        post: &Post<'_>,
+    ) -> anyhow::Result<Request> {
+        if let Some(id) = id {
+            return Err(unsupported(id));
+        }
+        Ok(Request {
+            method: "POST",
+            // the user id is only known once fetched from /me, which a dry run doesn't
+            url: format!("{}/users/{{user id}}/posts", self.url),
+            headers: vec![
+                ("user-agent".into(), api::USER_AGENT.into()),
+                ("authorization".into(), format!("Bearer {}", REDACTED)),
+                ("content-type".into(), "application/json".into()),
+            ],
+            body: body(post),
+        })
+    }
+
+    fn lists(&self) -> bool {
+        false
+    }
+}
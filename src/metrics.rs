@@ -0,0 +1,143 @@
+use crate::{
+    estimate::Estimate,
+    report::{Action, Report},
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Api usage during a push, exposed along with its report as Prometheus metrics
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// api requests timed
+    pub requests: usize,
+    /// time spent waiting on timed requests, including retries
+    pub latency: Duration,
+    /// requests made again after failing
+    pub retries: usize,
+    /// requests a real push would make, estimated by --dryrun
+    pub estimate: Estimate,
+}
+
+impl Metrics {
+    /// account for an api request which started at `start`, returning how long it took
+    pub fn request(
+        &mut self,
+        start: Instant,
+    ) -> std::time::Duration {
+        let took = start.elapsed();
+        self.requests += 1;
+        self.latency += took;
+        took
+    }
+
+    /// metrics in the Prometheus text exposition format, also valid OpenMetrics
+    pub fn render(
+        &self,
+        report: &Report,
+        timestamp: i64,
+    ) -> String {
+        let mut pushed = BTreeMap::<&str, usize>::new();
+        let mut failed = BTreeMap::<&str, usize>::new();
+        for row in &report.rows {
+            pushed.entry(&row.target).or_default();
+            failed.entry(&row.target).or_default();
+            match row.action {
+                Action::Create | Action::Update if !report.dryrun => {
+                    *pushed.entry(&row.target).or_default() += 1
+                }
+                Action::Failed => *failed.entry(&row.target).or_default() += 1,
+                _ => (),
+            }
+        }
+        let mut out = String::new();
+        for (name, help, counts) in [
+            (
+                "devtogo_articles_pushed",
+                "Articles created or updated.",
+                pushed,
+            ),
+            (
+                "devtogo_articles_failed",
+                "Articles which could not be published.",
+                failed,
+            ),
+        ] {
+            out.push_str(&format!("# HELP {0} {1}\n# TYPE {0} counter\n", name, help));
+            for (target, count) in counts {
+                out.push_str(&format!(
+                    "{}_total{{target=\"{}\"}} {}\n",
+                    name,
+                    target.replace('\\', "\\\\").replace('"', "\\\""),
+                    count
+                ));
+            }
+        }
+        out.push_str(&format!(
+            "# HELP devtogo_api_retries Api requests made again after failing.\n# TYPE devtogo_api_retries counter\ndevtogo_api_retries_total {}\n",
+            self.retries
+        ));
+        out.push_str(&format!(
+            "# HELP devtogo_api_request_duration_seconds Time spent on api requests.\n# TYPE devtogo_api_request_duration_seconds summary\ndevtogo_api_request_duration_seconds_sum {}\ndevtogo_api_request_duration_seconds_count {}\n",
+            self.latency.as_secs_f64(),
+            self.requests
+        ));
+        out.push_str(&format!(
+            "# HELP devtogo_last_run_timestamp_seconds When the push finished.\n# TYPE devtogo_last_run_timestamp_seconds gauge\ndevtogo_last_run_timestamp_seconds {}\n# EOF\n",
+            timestamp
+        ));
+        out
+    }
+}
+
+/// write metrics to a file, replacing it at once so collectors never read a partial file
+pub fn write(
+    path: &Path,
+    metrics: &str,
+) -> anyhow::Result<()> {
+    let partial = path.with_extension("partial");
+    fs::write(&partial, metrics)?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Row;
+
+    #[test]
+    fn render_counts_by_target() {
+        let row = |target: &str, action| Row {
+            target: target.into(),
+            file: "a.md".into(),
+            title: "a".into(),
+            action,
+            changes: None,
+            url: None,
+            error: None,
+        };
+        let report = Report {
+            dryrun: false,
+            rows: vec![
+                row("devto", Action::Create),
+                row("devto", Action::Unchanged),
+                row("devto", Action::Failed),
+                row("hashnode", Action::Update),
+            ],
+        };
+        let metrics = Metrics {
+            requests: 4,
+            latency: Duration::from_millis(1500),
+            retries: 1,
+            ..Metrics::default()
+        };
+        assert_eq!(
+            metrics.render(&report, 1_600_000_000),
+            "# HELP devtogo_articles_pushed Articles created or updated.\n# TYPE devtogo_articles_pushed counter\ndevtogo_articles_pushed_total{target=\"devto\"} 1\ndevtogo_articles_pushed_total{target=\"hashnode\"} 1\n# HELP devtogo_articles_failed Articles which could not be published.\n# TYPE devtogo_articles_failed counter\ndevtogo_articles_failed_total{target=\"devto\"} 1\ndevtogo_articles_failed_total{target=\"hashnode\"} 0\n# HELP devtogo_api_retries Api requests made again after failing.\n# TYPE devtogo_api_retries counter\ndevtogo_api_retries_total 1\n# HELP devtogo_api_request_duration_seconds Time spent on api requests.\n# TYPE devtogo_api_request_duration_seconds summary\ndevtogo_api_request_duration_seconds_sum 1.5\ndevtogo_api_request_duration_seconds_count 4\n# HELP devtogo_last_run_timestamp_seconds When the push finished.\n# TYPE devtogo_last_run_timestamp_seconds gauge\ndevtogo_last_run_timestamp_seconds 1600000000\n# EOF\n"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use crate::report::{Action, Row};
+use chrono::Utc;
+use serde::Serialize;
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+/// A step of pushing a file, streamed as it happens
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    /// a markdown file was found in a source directory
+    FileDiscovered,
+    /// a file was left as it is
+    Skipped { reason: &'a str },
+    /// a file is being sent to a target
+    Uploading { title: &'a str, action: &'a str },
+    /// a file was sent to a target
+    Uploaded {
+        title: &'a str,
+        url: Option<&'a str>,
+        seconds: f64,
+    },
+    /// a file couldn't be pushed
+    Error { title: &'a str, message: &'a str },
+}
+
+/// An event of a file pushed to a target, as a line of json
+#[derive(Serialize)]
+struct Record<'a> {
+    at: String,
+    target: &'a str,
+    file: &'a str,
+    #[serde(flatten)]
+    event: Event<'a>,
+}
+
+/// Where a push streams its events to, if anywhere
+#[derive(Default)]
+pub struct Stream {
+    out: Option<Box<dyn Write>>,
+    stdout: bool,
+}
+
+impl Stream {
+    /// a stream to a file, or to stdout when `path` is -
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        if path == Path::new("-") {
+            return Ok(Stream {
+                out: Some(Box::new(io::stdout())),
+                stdout: true,
+            });
+        }
+        Ok(Stream {
+            out: Some(Box::new(fs::File::create(path)?)),
+            stdout: false,
+        })
+    }
+
+    /// true if events are streamed to stdout
+    pub fn is_stdout(&self) -> bool {
+        self.stdout
+    }
+
+    /// write an event as a line, straight away so others can follow along
+    pub fn emit(
+        &mut self,
+        target: &str,
+        file: &str,
+        event: Event<'_>,
+    ) -> anyhow::Result<()> {
+        if let Some(out) = &mut self.out {
+            let record = Record {
+                at: Utc::now().to_rfc3339(),
+                target,
+                file,
+                event,
+            };
+            writeln!(out, "{}", serde_json::to_string(&record)?)?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+
+    /// emit the event a finished row of a push report stands for, if any, passing the row on
+    pub fn row(
+        &mut self,
+        row: Row,
+    ) -> anyhow::Result<Row> {
+        let event = match row.action {
+            Action::Failed => Event::Error {
+                title: &row.title,
+                message: row.error.as_deref().unwrap_or_default(),
+            },
+            Action::Unchanged => Event::Skipped {
+                reason: "up to date",
+            },
+            Action::Deferred => Event::Skipped {
+                reason: "cooling down",
+            },
+            // uploads are streamed as they're made
+            Action::Create | Action::Update => return Ok(row),
+        };
+        self.emit(&row.target, &row.file, event)?;
+        Ok(row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_written_as_lines() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join("devtogo-events.ndjson");
+        let mut stream = Stream::open(&path)?;
+        assert!(!stream.is_stdout());
+        stream.emit("devto", "posts/a.md", Event::FileDiscovered)?;
+        stream.row(Row::failed("devto", "posts/a.md", "A", "blocked term"))?;
+        drop(stream);
+        let lines = fs::read_to_string(&path)?
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<serde_json::Value>, _>>()?;
+        assert_eq!(lines[0]["event"], "file_discovered");
+        assert_eq!(lines[0]["file"], "posts/a.md");
+        assert_eq!(lines[1]["event"], "error");
+        assert_eq!(lines[1]["message"], "blocked term");
+        assert_eq!(lines[1]["target"], "devto");
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
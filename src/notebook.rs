@@ -0,0 +1,155 @@
+use crate::{config::Config, state};
+use anyhow::{anyhow, bail};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{fs, path::Path};
+
+/// Jupyter notebook file extension
+pub const EXTENSION: &str = "ipynb";
+
+/// The parts of a Jupyter notebook which make up an article
+#[derive(Debug, Deserialize)]
+struct Notebook {
+    cells: Vec<Cell>,
+    #[serde(default)]
+    metadata: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cell {
+    cell_type: String,
+    #[serde(deserialize_with = "text")]
+    source: String,
+    #[serde(default)]
+    outputs: Vec<Output>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Output {
+    #[serde(default, deserialize_with = "text")]
+    text: String,
+    #[serde(default)]
+    data: serde_json::Map<String, Value>,
+}
+
+/// notebooks store multiline text as either a string or a list of lines
+fn text<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(join(&Value::deserialize(deserializer)?))
+}
+
+fn join(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn fence(
+    language: &str,
+    code: &str,
+) -> String {
+    format!("```{}\n{}\n```", language, code.trim_end_matches('\n'))
+}
+
+/// markdown for a notebook. A leading raw cell holds frontmatter, code cells are fenced in
+/// the kernel's language and their output follows them. `image` saves an embedded png and
+/// returns the url to reference it by
+fn convert(
+    notebook: &Notebook,
+    mut image: impl FnMut(&str) -> anyhow::Result<String>,
+) -> anyhow::Result<String> {
+    let language = notebook.metadata["language_info"]["name"]
+        .as_str()
+        .or_else(|| notebook.metadata["kernelspec"]["language"].as_str())
+        .unwrap_or("python");
+    let mut blocks = Vec::new();
+    for cell in &notebook.cells {
+        if cell.source.trim().is_empty() && cell.outputs.is_empty() {
+            continue;
+        }
+        match cell.cell_type.as_str() {
+            "markdown" | "raw" => blocks.push(cell.source.trim_end().to_string()),
+            "code" => {
+                blocks.push(fence(language, &cell.source));
+                for output in &cell.outputs {
+                    if let Some(png) = output.data.get("image/png") {
+                        blocks.push(format!("![output]({})", image(&join(png))?));
+                    } else if let Some(markdown) = output.data.get("text/markdown") {
+                        blocks.push(join(markdown).trim_end().to_string());
+                    } else if let Some(plain) = output.data.get("text/plain") {
+                        blocks.push(fence("text", &join(plain)));
+                    } else if !output.text.is_empty() {
+                        blocks.push(fence("text", &output.text));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(format!("{}\n", blocks.join("\n\n")))
+}
+
+/// an article's markdown from a notebook file, saving embedded images next to it where they
+/// can be published at the configured `images_url`
+pub fn to_markdown(
+    config: &Config,
+    source: &Path,
+    path: &Path,
+) -> anyhow::Result<String> {
+    let notebook: Notebook = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|err| anyhow!("{} is not a valid notebook: {}", path.display(), err))?;
+    let dir = path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(config.layout.images());
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    convert(&notebook, |png| {
+        let images_url = match &config.images_url {
+            Some(url) => url.trim_end_matches('/'),
+            None => bail!(
+                "{} has embedded images, which need to be published somewhere to be displayed.\n  ▶ Set images_url in devtogo.toml to the url your source directory is published at",
+                path.display()
+            ),
+        };
+        let image = dir.join(format!("{}-{}.png", stem, &state::hash(png)[..8]));
+        if !image.exists() {
+            let bytes = STANDARD.decode(png.split_whitespace().collect::<String>())?;
+            fs::create_dir_all(&dir)?;
+            fs::write(&image, bytes)?;
+        }
+        Ok(format!("{}/{}", images_url, state::key(source, &image)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_fences_code_and_outputs() -> anyhow::Result<()> {
+        let notebook: Notebook = serde_json::from_str(
+            r##"{
+                "metadata": {"language_info": {"name": "python"}},
+                "cells": [
+                    {"cell_type": "raw", "source": ["---\n", "title: plots\n", "---"]},
+                    {"cell_type": "markdown", "source": "# Plots"},
+                    {"cell_type": "code", "source": ["print(1)\n", "plot()"], "outputs": [
+                        {"output_type": "stream", "text": ["1\n"]},
+                        {"output_type": "display_data", "data": {"image/png": "aGk=\n", "text/plain": "<Figure>"}}
+                    ]},
+                    {"cell_type": "code", "source": "", "outputs": []}
+                ]
+            }"##,
+        )?;
+        assert_eq!(
+            convert(&notebook, |png| Ok(format!("https://img/{}", png.trim())))?,
+            "---\ntitle: plots\n---\n\n# Plots\n\n```python\nprint(1)\nplot()\n```\n\n```text\n1\n```\n\n![output](https://img/aGk=)\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use colored::Colorize;
+use notify_rust::Notification;
+
+/// the title and body of a notification that a command finished
+fn message(
+    command: &str,
+    summary: &str,
+    result: &anyhow::Result<()>,
+) -> (String, String) {
+    match result {
+        Ok(()) => (format!("{} finished", command), summary.to_string()),
+        Err(err) => (
+            format!("{} failed", command),
+            // the first line of an error, without hints on how to fix it
+            err.to_string()
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        ),
+    }
+}
+
+/// raise a desktop notification that a command finished, so it can be left running in the
+/// background. Failing to raise one only warns, i.e. on headless machines
+pub fn finished(
+    command: &str,
+    summary: &str,
+    result: &anyhow::Result<()>,
+) {
+    let (title, body) = message(command, summary, result);
+    if let Err(err) = Notification::new()
+        .appname("devtogo")
+        .summary(&title)
+        .body(&body)
+        .show()
+    {
+        eprintln!(
+            "{} couldn't raise a desktop notification: {}",
+            "warning:".yellow(),
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn message_leaves_out_hints() {
+        assert_eq!(
+            message("devtogo push", "2 created, 1 updated", &Ok(())),
+            (
+                "devtogo push finished".to_string(),
+                "2 created, 1 updated".to_string()
+            )
+        );
+        assert_eq!(
+            message(
+                "devtogo push",
+                "",
+                &Err(anyhow!("1 article(s) failed.\n  ▶ Fix them and run again"))
+            ),
+            (
+                "devtogo push failed".to_string(),
+                "1 article(s) failed.".to_string()
+            )
+        );
+    }
+}
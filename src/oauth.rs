@@ -0,0 +1,273 @@
+use crate::{
+    api::{self, Auth},
+    backend::REDACTED,
+    config::{Config, Kind, Target},
+    recording,
+};
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+use structopt::StructOpt;
+
+/// Service name refresh tokens are kept under in the system keychain
+const SERVICE: &str = "devtogo";
+
+const DEVICE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Signs in to a Forem instance
+#[derive(StructOpt, Debug)]
+pub struct Login {
+    /// Sign in through the OAuth device flow rather than with an api key
+    #[structopt(long)]
+    oauth: bool,
+    /// Configured target to sign in to. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceCode {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    #[serde(default = "interval")]
+    interval: u64,
+}
+
+fn interval() -> u64 {
+    5
+}
+
+#[derive(Debug, Deserialize)]
+struct Token {
+    access_token: String,
+    refresh_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenError {
+    error: String,
+    error_description: Option<String>,
+}
+
+#[derive(Debug)]
+enum Poll {
+    Pending,
+    SlowDown,
+    Done(Token),
+}
+
+/// interpret a token endpoint response while waiting for the user to authorize a device
+fn poll(
+    success: bool,
+    body: &str,
+) -> anyhow::Result<Poll> {
+    if success {
+        return Ok(Poll::Done(serde_json::from_str(body)?));
+    }
+    let err: TokenError =
+        serde_json::from_str(body).map_err(|_| anyhow!("OAuth error {}", body))?;
+    match err.error.as_str() {
+        "authorization_pending" => Ok(Poll::Pending),
+        "slow_down" => Ok(Poll::SlowDown),
+        "access_denied" => bail!("Sign in was denied"),
+        "expired_token" => bail!("The code expired before sign in completed. Please try again"),
+        other => bail!(
+            "OAuth error {} {}",
+            other,
+            err.error_description.unwrap_or_default()
+        ),
+    }
+}
+
+fn client_id<'a>(
+    name: &str,
+    target: &'a Target,
+) -> anyhow::Result<&'a str> {
+    target.oauth_client_id.as_deref().ok_or_else(|| {
+        anyhow!(
+            "{} has no oauth_client_id configured.\n  ▶ Register an OAuth application with {} and add its client id to devtogo.toml",
+            name,
+            target.base_url()
+        )
+    })
+}
+
+async fn post<T: DeserializeOwned>(
+    http: &reqwest::Client,
+    url: &str,
+    form: &[(&str, &str)],
+) -> anyhow::Result<T> {
+    let resp = recording::send(http.post(url).form(form)).await?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if !status.is_success() {
+        bail!("OAuth error {:#?} {}", status, body);
+    }
+    Ok(serde_json::from_str(&body)?)
+}
+
+fn keychain(name: &str) -> anyhow::Result<keyring::Entry> {
+    Ok(keyring::Entry::new(SERVICE, name)?)
+}
+
+/// store an api key for a target in the system keychain, for when it isn't exported
+pub fn store_api_key(
+    target: &Target,
+    key: &str,
+) -> anyhow::Result<()> {
+    Ok(keychain(target.api_key_env())?.set_password(key)?)
+}
+
+/// an api key for a target stored by `init`, if any
+pub fn stored_api_key(target: &Target) -> Option<String> {
+    keychain(target.api_key_env()).ok()?.get_password().ok()
+}
+
+/// credentials for a Forem target. An api key exported in the environment wins over one
+/// stored by `init`, which wins over a refresh token stored by `login --oauth`
+pub async fn auth(
+    name: &str,
+    target: &Target,
+) -> anyhow::Result<Auth> {
+    if recording::replaying() {
+        return Ok(Auth::ApiKey(REDACTED.into()));
+    }
+    if let Ok(key) = env::var(target.api_key_env()) {
+        return Ok(Auth::ApiKey(key));
+    }
+    if let Some(key) = stored_api_key(target) {
+        return Ok(Auth::ApiKey(key));
+    }
+    let refresh_token = match target.oauth_client_id {
+        Some(_) => keychain(name)?.get_password().ok(),
+        None => None,
+    };
+    let refresh_token = match refresh_token {
+        Some(token) => token,
+        None => return target.api_key().map(Auth::ApiKey),
+    };
+    let token: Token = post(
+        &api::http()?,
+        &target.oauth_token_url(),
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", &refresh_token),
+            ("client_id", client_id(name, target)?),
+        ],
+    )
+    .await
+    .map_err(|err| anyhow!("{}\n  ▶ Sign in again with devtogo login --oauth", err))?;
+    if let Some(rotated) = &token.refresh_token {
+        keychain(name)?.set_password(rotated)?;
+    }
+    Ok(Auth::Bearer(token.access_token))
+}
+
+pub async fn login(
+    config: &Config,
+    args: Login,
+) -> anyhow::Result<()> {
+    let Login { oauth, target } = args;
+    let (name, target) = config.target(target.as_deref())?;
+    if target.kind != Kind::Forem {
+        bail!("login is only supported with Forem targets");
+    }
+    if !oauth {
+        bail!(
+            "devtogo reads api keys from the {} env variable, or the keychain once stored by devtogo init.\n  ▶ Use --oauth to sign in without one",
+            target.api_key_env()
+        );
+    }
+    let client_id = client_id(&name, &target)?;
+    let http = api::http()?;
+    let code: DeviceCode = post(
+        &http,
+        &target.oauth_device_url(),
+        &[("client_id", client_id)],
+    )
+    .await?;
+    println!(
+        "To sign in to {}, visit {} and enter the code {}",
+        name,
+        code.verification_uri_complete
+            .as_deref()
+            .unwrap_or(&code.verification_uri),
+        code.user_code.bold()
+    );
+    let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+    let mut interval = code.interval;
+    loop {
+        if Instant::now() >= deadline {
+            bail!("The code expired before sign in completed. Please try again");
+        }
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        let resp = recording::send(http.post(target.oauth_token_url().as_str()).form(&[
+            ("grant_type", DEVICE_GRANT),
+            ("device_code", &code.device_code),
+            ("client_id", client_id),
+        ]))
+        .await?;
+        let success = resp.status().is_success();
+        match poll(success, &resp.text().await?)? {
+            Poll::Pending => (),
+            Poll::SlowDown => interval += 5,
+            Poll::Done(token) => {
+                let refresh_token = token
+                    .refresh_token
+                    .ok_or_else(|| anyhow!("{} did not issue a refresh token", name))?;
+                keychain(&name)?.set_password(&refresh_token)?;
+                println!("Signed in to {}", name);
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poll_waits_while_pending() -> anyhow::Result<()> {
+        assert!(matches!(
+            poll(false, r#"{"error": "authorization_pending"}"#)?,
+            Poll::Pending
+        ));
+        assert!(matches!(
+            poll(false, r#"{"error": "slow_down"}"#)?,
+            Poll::SlowDown
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn poll_returns_tokens() -> anyhow::Result<()> {
+        match poll(true, r#"{"access_token": "a", "refresh_token": "r"}"#)? {
+            Poll::Done(token) => assert_eq!(token.refresh_token.as_deref(), Some("r")),
+            other => panic!("unexpected {:?}", other),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn poll_fails_when_denied() {
+        assert!(poll(false, r#"{"error": "access_denied"}"#).is_err());
+        assert!(poll(false, "<html>").is_err());
+    }
+
+    #[test]
+    fn device_code_defaults_interval() -> anyhow::Result<()> {
+        let code: DeviceCode = serde_json::from_str(
+            r#"{"device_code": "d", "user_code": "U", "verification_uri": "https://dev.to/device", "expires_in": 600}"#,
+        )?;
+        assert_eq!(code.interval, 5);
+        Ok(())
+    }
+}
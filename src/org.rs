@@ -0,0 +1,258 @@
+use crate::{
+    api::{self, Article, User},
+    config::{Config, Kind, Target},
+    dates,
+};
+use anyhow::bail;
+use colored::Colorize;
+use serde::Serialize;
+use std::{collections::HashMap, io};
+use structopt::StructOpt;
+
+/// Articles fetched per page of an organization's articles or members
+const PER_PAGE: usize = 1000;
+
+/// Lists an organization's published articles or members with their stats, i.e. for editorial
+/// reporting
+#[derive(StructOpt, Debug)]
+pub enum Org {
+    /// Lists articles published under an organization, with their reactions and comments
+    Articles(Options),
+    /// Lists members of an organization, with the articles they published under it and the
+    /// reactions and comments those got
+    Members(Options),
+}
+
+#[derive(StructOpt, Debug)]
+pub struct Options {
+    /// Username of the organization, as in its url, i.e. dev.to/<organization>
+    organization: String,
+    /// Configured target the organization is on. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+    /// Print rows as CSV, i.e. to open in a spreadsheet
+    #[structopt(long)]
+    csv: bool,
+}
+
+/// An article published under an organization, as listed
+#[derive(Debug, Serialize)]
+struct ArticleRow<'a> {
+    title: &'a str,
+    author: &'a str,
+    published_at: &'a str,
+    reactions: i64,
+    comments: i64,
+    url: &'a str,
+}
+
+impl<'a> From<&'a Article> for ArticleRow<'a> {
+    fn from(article: &'a Article) -> Self {
+        ArticleRow {
+            title: &article.title,
+            author: &article.user.username,
+            published_at: article.published_at.as_deref().unwrap_or_default(),
+            reactions: article.public_reactions_count,
+            comments: article.comments_count,
+            url: &article.url,
+        }
+    }
+}
+
+/// A member of an organization, as listed
+#[derive(Debug, Serialize, PartialEq)]
+struct MemberRow<'a> {
+    username: &'a str,
+    name: &'a str,
+    articles: usize,
+    reactions: i64,
+    comments: i64,
+}
+
+/// every page of a list from a public endpoint
+async fn all<T: serde::de::DeserializeOwned>(
+    target: &Target,
+    path: &str,
+) -> anyhow::Result<Vec<T>> {
+    let mut items = Vec::new();
+    for page in 1.. {
+        let batch: Vec<T> = api::public(
+            target,
+            &format!("{}?per_page={}&page={}", path, PER_PAGE, page),
+        )
+        .await?;
+        let last = batch.len() < PER_PAGE;
+        items.extend(batch);
+        if last {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+/// members with the stats of the articles they published under an organization, most reacted to
+/// first
+fn members<'a>(
+    users: &'a [User],
+    articles: &[Article],
+) -> Vec<MemberRow<'a>> {
+    let mut totals = HashMap::<&str, (usize, i64, i64)>::new();
+    for article in articles {
+        let total = totals.entry(article.user.username.as_str()).or_default();
+        total.0 += 1;
+        total.1 += article.public_reactions_count;
+        total.2 += article.comments_count;
+    }
+    let mut rows = users
+        .iter()
+        .map(|user| {
+            let (articles, reactions, comments) = totals
+                .get(user.username.as_str())
+                .copied()
+                .unwrap_or_default();
+            MemberRow {
+                username: &user.username,
+                name: &user.name,
+                articles,
+                reactions,
+                comments,
+            }
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by_key(|row| (-row.reactions, -(row.articles as i64)));
+    rows
+}
+
+/// print rows as CSV
+fn write_csv<T: Serialize>(rows: &[T]) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(io::stdout());
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub async fn run(
+    config: &Config,
+    args: Org,
+) -> anyhow::Result<()> {
+    let (members_listed, options) = match args {
+        Org::Articles(options) => (false, options),
+        Org::Members(options) => (true, options),
+    };
+    let Options {
+        organization,
+        target,
+        csv,
+    } = options;
+    let (_, target) = config.target(target.as_deref())?;
+    if target.kind != Kind::Forem {
+        bail!("organizations are only supported with Forem targets");
+    }
+    let path = format!("organizations/{}", organization.trim_start_matches('@'));
+    let mut articles: Vec<Article> = all(&target, &format!("{}/articles", path)).await?;
+    if members_listed {
+        let users: Vec<User> = all(&target, &format!("{}/users", path)).await?;
+        let rows = members(&users, &articles);
+        if csv {
+            return write_csv(&rows);
+        }
+        println!(
+            "{:>8} {:>10} {:>9}  {}",
+            "articles".bold(),
+            "reactions".bold(),
+            "comments".bold(),
+            "member".bold()
+        );
+        for row in &rows {
+            println!(
+                "{:>8} {:>10} {:>9}  {} {}",
+                row.articles,
+                row.reactions,
+                row.comments,
+                row.name,
+                row.username.dimmed()
+            );
+        }
+        return Ok(());
+    }
+    articles.sort_by_key(|article| -article.public_reactions_count);
+    if csv {
+        return write_csv(&articles.iter().map(ArticleRow::from).collect::<Vec<_>>());
+    }
+    println!(
+        "{:>10} {:>9}  {}",
+        "reactions".bold(),
+        "comments".bold(),
+        "article".bold()
+    );
+    for article in &articles {
+        let published = article
+            .published_at
+            .as_deref()
+            .map(|published| format!(" on {}", dates::local(published)))
+            .unwrap_or_default();
+        println!(
+            "{:>10} {:>9}  {} {}",
+            article.public_reactions_count,
+            article.comments_count,
+            article.title,
+            format!("by {}{}", article.user.name, published).dimmed()
+        );
+        println!("{:>22}{}", "", article.url.dimmed());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn members_total_their_articles() {
+        let user = |username: &str| User {
+            name: username.to_uppercase(),
+            username: username.into(),
+        };
+        let article = |username: &str, reactions, comments| Article {
+            user: user(username),
+            public_reactions_count: reactions,
+            comments_count: comments,
+            ..Article::default()
+        };
+        let users = vec![user("ada"), user("grace"), user("linus")];
+        let articles = vec![
+            article("ada", 3, 1),
+            article("grace", 10, 2),
+            article("ada", 4, 0),
+            article("former", 50, 5),
+        ];
+        assert_eq!(
+            members(&users, &articles),
+            vec![
+                MemberRow {
+                    username: "grace",
+                    name: "GRACE",
+                    articles: 1,
+                    reactions: 10,
+                    comments: 2
+                },
+                MemberRow {
+                    username: "ada",
+                    name: "ADA",
+                    articles: 2,
+                    reactions: 7,
+                    comments: 1
+                },
+                MemberRow {
+                    username: "linus",
+                    name: "LINUS",
+                    articles: 0,
+                    reactions: 0,
+                    comments: 0
+                },
+            ]
+        );
+    }
+}
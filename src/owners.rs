@@ -0,0 +1,128 @@
+use crate::state;
+use anyhow::anyhow;
+use regex::Regex;
+use std::{fs, io, path::Path};
+
+/// Name of the file, within a source directory's .devtogo directory, owners are kept in
+pub const FILE: &str = "owners";
+
+/// Name of the profile pushing without --profile, for owners files to list
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Profiles allowed to push articles, by path, read from lines of a glob and the profiles owning
+/// matching files, like CODEOWNERS. Later lines take precedence
+#[derive(Debug, Default)]
+pub struct Owners {
+    rules: Vec<(Regex, Vec<String>)>,
+}
+
+/// a regex matching article keys a glob matches. Globs without a leading or inner / match at any
+/// depth and those naming a directory match everything within it
+fn pattern(glob: &str) -> anyhow::Result<Regex> {
+    let anchored = glob.trim_end_matches('/').contains('/');
+    let mut glob = glob.trim_start_matches('/').to_string();
+    if glob.ends_with('/') {
+        glob.push_str("**");
+    }
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push_str("(?:/.*)?$");
+    Ok(Regex::new(&regex)?)
+}
+
+impl Owners {
+    /// owners of a source directory, which has none unless it has an owners file
+    pub fn load(source: &Path) -> anyhow::Result<Owners> {
+        let path = source.join(state::DIR).join(FILE);
+        match fs::read_to_string(&path) {
+            Ok(text) => Owners::parse(&text)
+                .map_err(|err| anyhow!("{} is invalid: {}", path.display(), err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Owners::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn parse(text: &str) -> anyhow::Result<Owners> {
+        let mut rules = Vec::new();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace();
+            if let Some(glob) = words.next() {
+                rules.push((pattern(glob)?, words.map(String::from).collect()));
+            }
+        }
+        Ok(Owners { rules })
+    }
+
+    /// profiles owning an article, keyed by its path within its source directory, if any do
+    pub fn owners(
+        &self,
+        key: &str,
+    ) -> Option<&[String]> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(regex, _)| regex.is_match(key))
+            .map(|(_, profiles)| profiles.as_slice())
+    }
+
+    /// true if a profile may push an article. Articles no line matches may be pushed by anyone
+    pub fn allows(
+        &self,
+        key: &str,
+        profile: Option<&str>,
+    ) -> bool {
+        let profile = profile.unwrap_or(DEFAULT_PROFILE);
+        self.owners(key)
+            .map(|owners| owners.iter().any(|owner| owner == profile))
+            .unwrap_or(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_matches_like_codeowners() -> anyhow::Result<()> {
+        assert!(pattern("*.md")?.is_match("posts/hello.md"));
+        assert!(pattern("ada/")?.is_match("posts/ada/hello.md"));
+        assert!(!pattern("/ada/")?.is_match("posts/ada/hello.md"));
+        assert!(pattern("posts/*.md")?.is_match("posts/hello.md"));
+        assert!(!pattern("posts/*.md")?.is_match("posts/ada/hello.md"));
+        assert!(pattern("posts/**/hello.md")?.is_match("posts/hello.md"));
+        assert!(pattern("posts/**/hello.md")?.is_match("posts/ada/2021/hello.md"));
+        assert!(pattern("posts/ada")?.is_match("posts/ada/hello.md"));
+        Ok(())
+    }
+
+    #[test]
+    fn later_lines_take_precedence() -> anyhow::Result<()> {
+        let owners = Owners::parse(
+            "# team blog\n* default ada grace\nposts/ada/ ada\nposts/ada/shared.md ada grace # pairing\n",
+        )?;
+        assert!(owners.allows("about.md", None));
+        assert!(owners.allows("posts/ada/hello.md", Some("ada")));
+        assert!(!owners.allows("posts/ada/hello.md", Some("grace")));
+        assert!(!owners.allows("posts/ada/hello.md", None));
+        assert!(owners.allows("posts/ada/shared.md", Some("grace")));
+        assert!(Owners::default().allows("posts/ada/hello.md", Some("grace")));
+        Ok(())
+    }
+}
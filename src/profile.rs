@@ -0,0 +1,108 @@
+use crate::{
+    api::{Client, UserProfile, UserProfileInput},
+    config::{Config, Kind},
+    oauth,
+};
+use anyhow::bail;
+use colored::Colorize;
+use structopt::StructOpt;
+
+/// Shows and updates your account's profile, i.e. to freshen it along with publishing content
+#[derive(StructOpt, Debug)]
+pub enum Profile {
+    /// Shows your account's profile
+    Show(Account),
+    /// Changes fields of your account's profile, where the instance's api permits it, leaving
+    /// those not passed as they are
+    Update {
+        /// Name displayed on your profile
+        #[structopt(long)]
+        name: Option<String>,
+        /// Bio displayed on your profile
+        #[structopt(long)]
+        summary: Option<String>,
+        /// Url of your website
+        #[structopt(long)]
+        website_url: Option<String>,
+        /// Where you're based
+        #[structopt(long)]
+        location: Option<String>,
+        #[structopt(flatten)]
+        account: Account,
+    },
+}
+
+/// The account whose profile is shown or updated
+#[derive(StructOpt, Debug)]
+pub struct Account {
+    /// Configured target the account is on. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+    /// Configured profile whose api key to authenticate with, rather than the target's own
+    #[structopt(long)]
+    profile: Option<String>,
+}
+
+impl Account {
+    async fn client(
+        &self,
+        config: &Config,
+    ) -> anyhow::Result<Client> {
+        let (name, target) = config.target(self.target.as_deref())?;
+        if target.kind != Kind::Forem {
+            bail!("profiles are only supported with Forem targets");
+        }
+        let target = match &self.profile {
+            Some(profile) => target.with_profile(config.profile(profile)?),
+            None => target,
+        };
+        Client::new(&target, oauth::auth(&name, &target).await?)
+    }
+}
+
+fn print(profile: &UserProfile) {
+    println!("{} {}", profile.name.bold(), profile.username.dimmed());
+    for (field, value) in [
+        ("summary", &profile.summary),
+        ("website_url", &profile.website_url),
+        ("location", &profile.location),
+        ("joined_at", &profile.joined_at),
+    ] {
+        if let Some(value) = value.as_deref().filter(|value| !value.is_empty()) {
+            println!("{:<12} {}", field.dimmed(), value);
+        }
+    }
+}
+
+pub async fn run(
+    config: &Config,
+    args: Profile,
+) -> anyhow::Result<()> {
+    match args {
+        Profile::Show(account) => print(&account.client(config).await?.profile().await?),
+        Profile::Update {
+            name,
+            summary,
+            website_url,
+            location,
+            account,
+        } => {
+            let input = UserProfileInput {
+                name: name.as_deref(),
+                summary: summary.as_deref(),
+                website_url: website_url.as_deref(),
+                location: location.as_deref(),
+            };
+            if serde_json::to_value(&input)?
+                .as_object()
+                .is_some_and(|fields| fields.is_empty())
+            {
+                bail!("nothing to update.\n  ▶ Pass the fields to change, i.e. --summary");
+            }
+            let profile = account.client(config).await?.update_profile(&input).await?;
+            println!("Updated profile");
+            print(&profile);
+        }
+    }
+    Ok(())
+}
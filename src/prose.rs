@@ -0,0 +1,291 @@
+use crate::typography;
+use anyhow::anyhow;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fs, path::PathBuf};
+use yaml_rust::{Yaml, YamlLoader};
+
+/// Word list most unix systems ship with
+const SYSTEM_DICTIONARY: &str = "/usr/share/dict/words";
+
+/// Settings for checking the prose of articles with `lint --prose`
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Prose {
+    /// Word lists, one word per line. Hunspell .dic files work too
+    pub dictionaries: Vec<PathBuf>,
+    /// Words specific to this project, i.e. product names
+    pub words: Vec<String>,
+    /// Directory of Vale style rule files
+    pub styles: Option<PathBuf>,
+}
+
+impl Default for Prose {
+    fn default() -> Self {
+        Prose {
+            dictionaries: vec![SYSTEM_DICTIONARY.into()],
+            words: Vec::new(),
+            styles: None,
+        }
+    }
+}
+
+/// A problem with the prose of an article, positioned with 1-based lines and columns
+#[derive(Debug, PartialEq)]
+pub struct Finding {
+    pub line: usize,
+    pub column: usize,
+    pub error: bool,
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// A Vale `existence` or `substitution` rule
+#[derive(Debug)]
+struct Rule {
+    name: String,
+    message: String,
+    error: bool,
+    /// patterns and, for substitutions, what to use instead
+    patterns: Vec<(Regex, Option<String>)>,
+}
+
+impl Rule {
+    fn parse(
+        name: &str,
+        yaml: &Yaml,
+    ) -> anyhow::Result<Rule> {
+        let ignorecase = yaml["ignorecase"].as_bool().unwrap_or_default();
+        let pattern = |token: &str| {
+            RegexBuilder::new(&format!(r"\b(?:{})\b", token))
+                .case_insensitive(ignorecase)
+                .build()
+                .map_err(|err| anyhow!("{} has an invalid pattern {}: {}", name, token, err))
+        };
+        let patterns = match yaml["extends"].as_str() {
+            Some("existence") => yaml["tokens"]
+                .as_vec()
+                .into_iter()
+                .flatten()
+                .filter_map(Yaml::as_str)
+                .map(|token| Ok((pattern(token)?, None)))
+                .collect::<anyhow::Result<Vec<_>>>()?,
+            Some("substitution") => yaml["swap"]
+                .as_hash()
+                .into_iter()
+                .flatten()
+                .filter_map(|(from, to)| Some((from.as_str()?, to.as_str()?)))
+                .map(|(from, to)| Ok((pattern(from)?, Some(to.to_string()))))
+                .collect::<anyhow::Result<Vec<_>>>()?,
+            other => anyhow::bail!(
+                "{} extends {}, only existence and substitution rules are supported",
+                name,
+                other.unwrap_or("nothing")
+            ),
+        };
+        Ok(Rule {
+            name: name.into(),
+            message: yaml["message"].as_str().unwrap_or("'%s'").into(),
+            error: yaml["level"].as_str() == Some("error"),
+            patterns,
+        })
+    }
+
+    /// the rule's message for a match, filling in %s placeholders as Vale does
+    fn message(
+        &self,
+        found: &str,
+        replacement: Option<&str>,
+    ) -> String {
+        let mut message = self.message.clone();
+        for value in replacement.into_iter().chain(Some(found)) {
+            message = message.replacen("%s", value, 1);
+        }
+        format!("{}: {}", self.name, message)
+    }
+}
+
+/// Checks spelling against word lists and style against Vale rules
+#[derive(Debug, Default)]
+pub struct Checker {
+    words: HashSet<String>,
+    rules: Vec<Rule>,
+}
+
+impl Checker {
+    pub fn load(prose: &Prose) -> anyhow::Result<Checker> {
+        let mut words = HashSet::new();
+        for path in &prose.dictionaries {
+            let list = fs::read_to_string(path).map_err(|err| {
+                anyhow!(
+                    "unable to read dictionary {}: {}\n  ▶ Configure word lists with dictionaries under [prose] in devtogo.toml",
+                    path.display(),
+                    err
+                )
+            })?;
+            words.extend(
+                list.lines()
+                    .filter_map(|line| line.split('/').next())
+                    .map(|word| word.trim().to_lowercase())
+                    .filter(|word| !word.is_empty()),
+            );
+        }
+        words.extend(prose.words.iter().map(|word| word.to_lowercase()));
+        let mut rules = Vec::new();
+        if let Some(dir) = &prose.styles {
+            let mut paths = fs::read_dir(dir)?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().into_iter().any(|e| e == "yml" || e == "yaml"))
+                .collect::<Vec<_>>();
+            paths.sort();
+            for path in paths {
+                let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                for yaml in YamlLoader::load_from_str(&fs::read_to_string(&path)?)? {
+                    rules.push(Rule::parse(&name, &yaml)?);
+                }
+            }
+        }
+        Ok(Checker { words, rules })
+    }
+
+    fn known(
+        &self,
+        word: &str,
+    ) -> bool {
+        let possessive = word.trim_end_matches("'s").trim_end_matches("’s");
+        // acronyms and identifiers, i.e. HTTP or camelCase, aren't prose
+        word.chars().skip(1).any(|c| c.is_uppercase())
+            || self.words.contains(&word.to_lowercase())
+            || self.words.contains(&possessive.to_lowercase())
+    }
+
+    /// spelling and style findings in the prose of an article
+    pub fn check(
+        &self,
+        content: &str,
+    ) -> Vec<Finding> {
+        let word = Regex::new(r"[\p{L}]+(?:['’][\p{L}]+)*").expect("invalid word pattern");
+        let mut findings = Vec::new();
+        for (n, line) in prose(content) {
+            for found in word.find_iter(&line) {
+                if !self.known(found.as_str()) {
+                    findings.push(Finding {
+                        line: n + 1,
+                        column: found.start() + 1,
+                        error: false,
+                        code: "spelling",
+                        message: format!("unknown word {}", found.as_str()),
+                    });
+                }
+            }
+            for rule in &self.rules {
+                for (pattern, replacement) in &rule.patterns {
+                    for found in pattern.find_iter(&line) {
+                        findings.push(Finding {
+                            line: n + 1,
+                            column: found.start() + 1,
+                            error: rule.error,
+                            code: "style",
+                            message: rule.message(found.as_str(), replacement.as_deref()),
+                        });
+                    }
+                }
+            }
+        }
+        findings
+    }
+}
+
+/// 0-based numbers of prose lines in an article, with code, markup and urls blanked out so
+/// columns still line up
+fn prose(content: &str) -> Vec<(usize, String)> {
+    let url = Regex::new(r"https?://\S+").expect("invalid url pattern");
+    let mut lines = content.split('\n').enumerate();
+    if content.starts_with("---") {
+        lines.next();
+        for (_, line) in &mut lines {
+            if line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+    let mut fenced = false;
+    let mut prose = Vec::new();
+    for (n, line) in lines {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced {
+            continue;
+        }
+        let mut masked = String::new();
+        let mut rest = line;
+        while let Some(c) = rest.chars().next() {
+            match typography::protected(rest) {
+                Some(end) => {
+                    masked.push_str(&" ".repeat(end));
+                    rest = &rest[end..];
+                }
+                None => {
+                    masked.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        let masked = url
+            .replace_all(&masked, |c: &regex::Captures| " ".repeat(c[0].len()))
+            .to_string();
+        prose.push((n, masked));
+    }
+    prose
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checker() -> anyhow::Result<Checker> {
+        let rules = YamlLoader::load_from_str(
+            "extends: substitution\nmessage: Use '%s' instead of '%s'\nlevel: error\nignorecase: true\nswap:\n  project falcon: the new release\n",
+        )?;
+        Ok(Checker {
+            words: ["the", "is", "out", "run", "it's", "see"]
+                .iter()
+                .map(|w| w.to_string())
+                .collect(),
+            rules: vec![Rule::parse("Acme", &rules[0])?],
+        })
+    }
+
+    #[test]
+    fn check_finds_unknown_words() -> anyhow::Result<()> {
+        let findings = checker()?.check(
+            "---\ntitle: teh\n---\nThe HTTP teh is out, it's Ferris's\n```\nnot checked\n```\nrun `cargo` see https://example.com/wrods",
+        );
+        assert_eq!(
+            findings
+                .iter()
+                .map(|f| (f.line, f.column, f.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (4, 10, "unknown word teh"),
+                (4, 27, "unknown word Ferris's")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_applies_style_rules() -> anyhow::Result<()> {
+        let findings = checker()?.check("Project Falcon is out");
+        let style = findings.iter().find(|f| f.code == "style").unwrap();
+        assert_eq!(
+            style.message,
+            "Acme: Use 'the new release' instead of 'Project Falcon'"
+        );
+        assert!(style.error);
+        Ok(())
+    }
+}
@@ -1,13 +1,40 @@
+use crate::{
+    api::Article,
+    article::{self, PublishStatus, Strictness},
+    audit::{self, Event},
+    backend::{self, Account, Accounts, Backend, Post},
+    breaker::{Breaker, Trip},
+    config::{Config, Kind},
+    console,
+    duration::Duration,
+    email,
+    gate::{Gate, Gates, Upload},
+    history,
+    junit::{self, Case},
+    listing::{self, GroupBy, Listing, Sort},
+    metrics::{self, Metrics},
+    ndjson::{Event as Progressed, Stream},
+    notify,
+    owners::Owners,
+    preflight,
+    report::{self, Action, Report, Row},
+    state::{self, Entry, Progress, Retries, State},
+    template::{self, Column, Fields, LineFormat, Lines},
+};
 use anyhow::{anyhow, bail};
-use chrono::DateTime;
+use chrono::{DateTime, Local, Utc};
 use colored::Colorize;
-use frontmatter::Yaml;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
-use std::{fmt, fs, path::PathBuf};
+use std::{
+    fmt, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Instant,
+};
 use structopt::StructOpt;
-use walkdir::WalkDir;
+
+/// Name audit records give articles read from stdin, which have no file
+const STDIN: &str = "<stdin>";
 
 enum UploadStatus<'a> {
     Uploaded,
@@ -25,66 +52,60 @@ impl fmt::Display for UploadStatus<'_> {
             match self {
                 UploadStatus::Uploaded => "UPLOADED".green(),
                 UploadStatus::Posting => "POSTING".yellow(),
-                UploadStatus::Syncing(ref _remote) => "SYNCING".yellow(),
+                UploadStatus::Syncing(_) => "SYNCING".yellow(),
             }
         );
         f.write_str(&s)
     }
 }
 
-#[derive(PartialEq, Debug)]
-enum PublishStatus {
-    Published,
-    Draft,
+/// Where push results are written
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Output {
+    Text,
+    Junit,
+    /// progress moved to stderr for --events-ndjson - to stream events to stdout
+    Ndjson,
 }
 
-impl fmt::Display for PublishStatus {
-    fn fmt(
-        &self,
-        f: &mut fmt::Formatter<'_>,
-    ) -> fmt::Result {
-        let s = format!(
-            "{}",
-            match self {
-                PublishStatus::Published => "published".dimmed(),
-                PublishStatus::Draft => "draft".dimmed(),
-            }
-        );
-        f.write_str(&s)
+impl FromStr for Output {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Output::Text),
+            "junit" => Ok(Output::Junit),
+            other => Err(anyhow!("unsupported output {}", other)),
+        }
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct DevtoError {
-    error: String,
-}
-
-#[derive(Debug, Serialize)]
-struct CreateArticleInput {
-    body_markdown: String,
-}
+impl Output {
+    /// report progress, kept off stdout when it carries machine readable output
+    fn say(
+        self,
+        message: impl fmt::Display,
+    ) {
+        match self {
+            Output::Text => println!("{}", console::text(&message.to_string())),
+            Output::Junit | Output::Ndjson => {
+                eprintln!("{}", console::text(&message.to_string()))
+            }
+        }
+    }
 
-#[derive(Debug, Deserialize)]
-struct Article {
-    id: u32,
-    title: String,
-    description: String,
-    cover_image: Option<String>,
-    published: bool,
-    published_at: Option<String>,
-    tag_list: Vec<String>,
-    slug: String,
-    path: String,
-    url: String,
-    canonical_url: String,
-    published_timestamp: String,
-    body_markdown: String,
+    /// report each of the lines a listing let through
+    fn say_all(
+        self,
+        lines: Vec<String>,
+    ) {
+        for line in lines {
+            self.say(line);
+        }
+    }
 }
 
-/// A dev.to tool for the road 👩🏽‍💻🎒
-///
-/// Uploads local markdown files with dev.to
-#[derive(StructOpt, Debug)]
+/// Uploads local markdown files to dev.to and other configured targets
+#[derive(StructOpt, Debug, Clone)]
 pub struct Push {
     /// Directory to source markdown files from. Defaults to current working directory
     #[structopt(short, long)]
@@ -92,350 +113,1574 @@ pub struct Push {
     /// Run without actually updating account
     #[structopt(short, long)]
     dryrun: bool,
+    /// With --dryrun, print the api requests each article would be sent with: -v for their
+    /// endpoints and -vv for their headers and json bodies too, with credentials redacted.
+    /// Otherwise, -v prints how long each upload took
+    #[structopt(short, long, parse(from_occurrences))]
+    verbose: u8,
+    /// Warn about api requests taking longer than this, i.e. giant articles or a slow network
+    /// holding up scheduled pushes
+    #[structopt(long, default_value = "10s")]
+    slow_request: Duration,
+    /// Number of pushed versions of each file to keep locally. 0 disables history
+    #[structopt(long, default_value = "10")]
+    history_depth: usize,
+    /// Stop once this many api requests were made, recording progress for --resume
+    #[structopt(long)]
+    max_requests: Option<usize>,
+    /// Skip files a previous push stopped by --max-requests already processed
+    #[structopt(long)]
+    resume: bool,
+    /// Skip files which weren't modified since they were last pushed, without reading them
+    #[structopt(long)]
+    incremental: bool,
+    /// Write a markdown report of planned or performed actions to this file, i.e. to share in a pull request
+    #[structopt(long)]
+    report: Option<PathBuf>,
+    /// Write counters of articles pushed, failures, retries and api latency to this file in the
+    /// Prometheus text format, i.e. for node exporter's textfile collector
+    #[structopt(long)]
+    metrics_file: Option<PathBuf>,
+    /// Output format. junit prints a JUnit XML report with a test case per article for CI systems, moving progress to stderr
+    #[structopt(long, default_value = "text", possible_values = &["text", "junit"])]
+    output: Output,
+    /// Configured target to publish to. May be repeated. Defaults to all targets
+    #[structopt(short, long)]
+    target: Vec<String>,
+    /// Push files which look like they contain api keys, tokens or private keys
+    #[structopt(long)]
+    allow_secrets: bool,
+    /// Update articles even if they were updated more recently than update_cooldown allows
+    #[structopt(long)]
+    force: bool,
+    /// Refuse articles with frontmatter fields devtogo doesn't know, so nothing is silently dropped
+    #[structopt(long, conflicts_with = "lenient")]
+    strict: bool,
+    /// Only warn about invalid optional frontmatter fields, pushing articles without them
+    #[structopt(long)]
+    lenient: bool,
+    /// Profile you're pushing as, which publishes articles without an api_key_ref and must own
+    /// the files pushed when .devtogo/owners lists owners. Defaults to a source's profile
+    #[structopt(long)]
+    profile: Option<String>,
+    /// Read markdown for a new article from stdin rather than files, printing its url once created
+    #[structopt(long)]
+    stdin: bool,
+    /// Title of an article read from stdin. Required unless it has title frontmatter
+    #[structopt(long, requires = "stdin")]
+    title: Option<String>,
+    /// Push every article or none, i.e. for release day launches: every article is checked
+    /// first, new ones are created as drafts and articles are only published once every upload
+    /// succeeded. Forem targets only
+    #[structopt(long, conflicts_with_all = &["stdin", "max-requests", "resume"])]
+    atomic: bool,
+    /// Publish articles failing items of the configured checklist
+    #[structopt(long)]
+    override_checklist: bool,
+    /// How to print each file's status: a template like "{status}\t{title}\t{url}", with
+    /// {status}, {title}, {file}, {target}, {url} and {published}, in local time, replaced, or
+    /// table
+    #[structopt(long)]
+    format: Option<LineFormat>,
+    /// Columns of --format table, separated by commas
+    #[structopt(
+        long,
+        use_delimiter = true,
+        possible_values = template::COLUMNS,
+        default_value = "status,title,url"
+    )]
+    columns: Vec<Column>,
+    /// Group files' status lines by status, files needing action first and files already in
+    /// sync collapsed to a count
+    #[structopt(long, possible_values = listing::GROUPS)]
+    group_by: Option<GroupBy>,
+    /// Order files' status lines by title, date (frontmatter, else modification time) or status
+    #[structopt(long, possible_values = listing::SORTS)]
+    sort: Option<Sort>,
+    /// Only print files needing action, counting those already up to date, i.e. to keep logs of
+    /// pushes over large sources readable
+    #[structopt(long)]
+    quiet_unchanged: bool,
+    /// Stream a line of json for each file discovered, skipped, uploading, uploaded or failing
+    /// to this file as the push progresses, i.e. for dashboards. - streams to stdout, moving
+    /// progress to stderr
+    #[structopt(long)]
+    events_ndjson: Option<PathBuf>,
+    /// Raise a desktop notification once the push finished or failed, i.e. to switch windows
+    /// during long migrations
+    #[structopt(long)]
+    notify_desktop: bool,
+    /// Email a summary of the push and its failures to the addresses of the configured [smtp]
+    /// table, i.e. to monitor scheduled pushes
+    #[structopt(long)]
+    email_report: bool,
+    /// Only push files queued for retrying by earlier pushes, as `retry` does
+    #[structopt(skip)]
+    retry: bool,
+    /// Pause, backing off exponentially, once this many api requests in a row failed with 5xx
+    /// responses or timeouts, as the api is likely down. 0 never pauses
+    #[structopt(long, default_value = "5")]
+    circuit_breaker: usize,
+    /// Skip checking each target can be reached before pushing, i.e. behind proxies answering
+    /// HEAD requests oddly
+    #[structopt(long)]
+    skip_preflight: bool,
 }
 
-fn extract(
-    name: &str,
-    content: &str,
-) -> anyhow::Result<(Frontmatter, String)> {
-    let (front, back) = match frontmatter::parse_and_find_content(&content) {
-        Ok((front, back)) => (front, back),
-        Err(err) => {
-            eprintln!("Error extracting front matter from {}", name);
-            Err(err)?
-        }
-    };
-    let metadata = front.ok_or_else(
-        || {
-            anyhow!(
-                "file {} is missing required markdown frontmatter.\n  ▶ Please see https://dev.to/p/editor_guide more information on what frontmatter is expected", name
-            )
+/// a single status line for an article, title padded with dots to a fixed width
+pub fn line(
+    title: &str,
+    status: impl fmt::Display,
+) -> String {
+    format!(
+        "{}{}{}",
+        title.chars().take(50).collect::<String>().bold(),
+        String::from(".")
+            .repeat(50_usize.saturating_sub(title.len()))
+            .dimmed(),
+        format!("[{}]", status).bold(),
+    )
+}
+
+impl Push {
+    /// directory markdown files are sourced from
+    pub fn source(
+        &self,
+        config: &Config,
+    ) -> PathBuf {
+        config.source(self.source.clone())
+    }
+
+    /// true if accounts are left as they are
+    pub fn dryrun(&self) -> bool {
+        self.dryrun
+    }
+
+    /// the push, only of files which failed to push before
+    pub fn retrying(self) -> Push {
+        Push {
+            retry: true,
+            ..self
         }
-    )?;
+    }
+}
 
-    Ok((Frontmatter::from_file(&name, metadata)?, back.into()))
+/// An article an --atomic push publishes once every upload succeeded
+struct Pending {
+    /// source directory of the article
+    dir: PathBuf,
+    key: String,
+    id: String,
+    profile: Option<String>,
+    meta: article::Frontmatter,
+    content: String,
+    body: String,
+    created: bool,
+    was_published: bool,
+    /// Content of an article already live, put back should publishing another fail
+    previous: Option<String>,
+    /// index of the article's row in the report
+    row: usize,
 }
 
-/// Markdown frontmatter dev.to api documents as acceptable input
-#[derive(Debug, PartialEq, Default)]
-struct Frontmatter {
-    title: String,
-    published: Option<bool>,
-    tags: Option<String>,
-    date: Option<String>,
-    series: Option<String>,
-    canonical_url: Option<String>,
-    cover_image: Option<String>,
+/// Api requests a push may make, unlimited unless a maximum is given
+struct Budget {
+    max: Option<usize>,
+    used: usize,
 }
 
-impl Frontmatter {
-    fn publish_status(&self) -> PublishStatus {
-        if self.published.unwrap_or_default() {
-            PublishStatus::Published
-        } else {
-            PublishStatus::Draft
+impl Budget {
+    /// account for a request, false when none remain
+    fn spend(&mut self) -> bool {
+        match self.max {
+            Some(max) if self.used >= max => false,
+            _ => {
+                self.used += 1;
+                true
+            }
         }
     }
-    /// extract and validate raw yaml frontmatter
-    fn from_file(
-        name: &str,
-        metadata: Yaml,
-    ) -> anyhow::Result<Frontmatter> {
-        let hash = metadata
-            .into_hash()
-            .ok_or_else(|| anyhow!("file {} contains frontmatter that not well formatted", name))?;
-        let string = |name: &str| -> Option<String> {
-            hash.get(&Yaml::String(name.into()))
-                .and_then(|v| v.as_str().map(|s| s.into()))
+}
+
+pub async fn run(
+    config: &Config,
+    mut args: Push,
+) -> anyhow::Result<()> {
+    let path = args.report.clone();
+    let metrics_file = args.metrics_file.clone();
+    let mut events = match &args.events_ndjson {
+        Some(path) => Stream::open(path)?,
+        None => Stream::default(),
+    };
+    if events.is_stdout() {
+        if args.output == Output::Junit {
+            bail!("--events-ndjson - and --output junit can't both write to stdout");
+        }
+        args.output = Output::Ndjson;
+    }
+    let output = args.output;
+    let notify_desktop = args.notify_desktop;
+    let email_report = args.email_report;
+    if email_report && config.smtp.is_none() {
+        bail!("--email-report needs a mail server.\n  ▶ Configure one in an [smtp] table of devtogo.toml");
+    }
+    let mut report = Report {
+        dryrun: args.dryrun,
+        ..Report::default()
+    };
+    if args.stdin {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        return push_stdin(config, args, &input).await;
+    }
+    let mut metrics = Metrics::default();
+    if args.atomic && !args.dryrun {
+        output.say("Checking every article before pushing any".bold());
+        let mut checked = Report {
+            dryrun: true,
+            ..Report::default()
         };
-        let boolean = |name: &str| -> Option<bool> {
-            hash.get(&Yaml::String(name.into()))
-                .and_then(|v| v.as_bool())
+        let check = Push {
+            dryrun: true,
+            ..args.clone()
         };
-        let title = string("title")
-            .ok_or_else(|| anyhow!("file {} contains frontmatter missing a string title", name))?;
-        let published = boolean("published");
-        let tags = string("tags");
-        let date = string("date");
-        if let Some(value) = &date {
-            if DateTime::parse_from_rfc3339(&value).is_err() {
-                bail!(
-                    "file {} contains frontmatter with and invalid date: {}",
-                    name,
-                    value
+        push(
+            config,
+            check,
+            &mut checked,
+            &mut Metrics::default(),
+            &mut Stream::default(),
+        )
+        .await?;
+        let failed = checked
+            .rows
+            .iter()
+            .filter(|row| row.action == Action::Failed)
+            .count();
+        if failed > 0 {
+            bail!(
+                "{} article(s) can't be pushed, so none were.\n  ▶ Fix them and run again",
+                failed
+            );
+        }
+    }
+    let max_requests = args.max_requests;
+    let result = push(config, args, &mut report, &mut metrics, &mut events).await;
+    if report.dryrun && result.is_ok() {
+        for line in metrics
+            .estimate
+            .summary(metrics.requests, metrics.latency, max_requests)
+        {
+            output.say(line);
+        }
+    }
+    if let Some(path) = path {
+        fs::write(path, report.render())?;
+    }
+    if let Some(path) = metrics_file {
+        metrics::write(&path, &metrics.render(&report, Utc::now().timestamp()))?;
+    }
+    if output == Output::Junit {
+        println!("{}", junit::render("devtogo push", &cases(&report)));
+    }
+    if notify_desktop {
+        notify::finished("devtogo push", &report.summary(), &result);
+    }
+    if let (true, Some(smtp)) = (email_report, &config.smtp) {
+        email::report(
+            smtp,
+            "devtogo push",
+            &report.summary(),
+            &report.failures(),
+            &result,
+        )
+        .await;
+    }
+    result
+}
+
+async fn push(
+    config: &Config,
+    args: Push,
+    report: &mut Report,
+    metrics: &mut Metrics,
+    events: &mut Stream,
+) -> anyhow::Result<()> {
+    let Push {
+        source,
+        dryrun,
+        history_depth,
+        max_requests,
+        resume,
+        incremental,
+        output,
+        target,
+        allow_secrets,
+        force,
+        strict,
+        lenient,
+        profile,
+        verbose,
+        atomic,
+        override_checklist,
+        format,
+        columns,
+        group_by,
+        sort,
+        quiet_unchanged,
+        slow_request,
+        retry,
+        circuit_breaker,
+        skip_preflight,
+        ..
+    } = args;
+    let mut listing = Listing::new(Lines::new(format, columns), group_by, sort, quiet_unchanged);
+    if let Some(header) = listing.header() {
+        output.say(header.bold());
+    }
+    let strictness = Strictness::from_flags(strict, lenient);
+    let roots = config.roots(source);
+    let targets = config.targets(&target)?;
+    let announce = targets.len() > 1;
+    let gates = Gates::new(config, allow_secrets, override_checklist)?;
+    let mut budget = Budget {
+        max: max_requests,
+        used: 0,
+    };
+    let mut breaker = Breaker::new(circuit_breaker);
+    if atomic {
+        if let Some((name, _)) = targets
+            .iter()
+            .find(|(_, target)| target.kind != Kind::Forem)
+        {
+            bail!(
+                "--atomic pushes articles as drafts first, which target {} doesn't support",
+                name
+            );
+        }
+    }
+    if !skip_preflight {
+        for (name, target) in &targets {
+            preflight::check(name, target).await?;
+        }
+    }
+    let mut secret_files = 0;
+    let mut launches = Vec::new();
+    for (name, target) in &targets {
+        if announce {
+            output.say(format!("▶ {}", name).bold());
+        }
+        let mut accounts = Accounts::new(config, name, target);
+        let mut pending = Vec::new();
+        for root in &roots {
+            if roots.len() > 1 {
+                output.say(format!("▶ {}", root.dir.display()));
+            }
+            let mut progress = Progress::load(&root.dir, name)?;
+            if !resume {
+                progress.done.clear();
+            }
+            let mut state = State::load(&root.dir, name)?;
+            let mut retries = Retries::load(&root.dir, name)?;
+            if retry && retries.failed.is_empty() {
+                output.say("No failed files to retry");
+            }
+            let first_row = report.rows.len();
+            let owners = Owners::load(&root.dir)?;
+            let pusher_profile = profile.as_deref().or(root.profile.as_deref());
+            let mut unmodified_files = 0;
+            for path in article::paths(&root.dir, config) {
+                let key = state::key(&root.dir, &path);
+                if retry && !retries.failed.contains_key(&key) {
+                    continue;
+                }
+                events.emit(name, &key, Progressed::FileDiscovered)?;
+                if progress.done.contains(&key) {
+                    let reason = "pushed before --max-requests stopped";
+                    events.emit(name, &key, Progressed::Skipped { reason })?;
+                    continue;
+                }
+                if incremental && unmodified(&path, state.get(&key)) {
+                    unmodified_files += 1;
+                    let reason = "unmodified since last pushed";
+                    events.emit(name, &key, Progressed::Skipped { reason })?;
+                    continue;
+                }
+                let content = article::render(config, &root.dir, &path, target.kind).await?;
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                let (meta, body, warnings) =
+                    article::parse(file_name.as_ref(), &content, strictness, config.timezone)?;
+                for warning in &warnings {
+                    output.say(format!("{} {}", "warning:".yellow(), warning));
+                }
+                let date = meta.date.clone().or_else(|| modified(&path));
+                let fields = Fields {
+                    target: name,
+                    file: &key,
+                    title: &meta.title,
+                    url: None,
+                    date: date.as_deref(),
+                    published: None,
+                };
+                let language = article::language(&path, &config.languages)
+                    .map(|(language, _)| language)
+                    .or_else(|| config.languages.first().cloned());
+                if !meta.targets(name) || !meta.pushed() || !target.publishes(language.as_deref()) {
+                    let reason = "not pushed to this target";
+                    events.emit(name, &key, Progressed::Skipped { reason })?;
+                    continue;
+                }
+                let upload = Upload {
+                    key: &key,
+                    meta: &meta,
+                    content: &content,
+                    body: &body,
+                };
+                if let Err(refusal) = gates.content(&owners, pusher_profile, &upload) {
+                    output.say_all(listing.status(&fields, refusal.gate.to_string().red()));
+                    for detail in &refusal.details {
+                        output.say_all(listing.detail(format!("  ▶ {}", detail)));
+                    }
+                    report.rows.push(events.row(Row::failed(
+                        name,
+                        &key,
+                        &meta.title,
+                        refusal.reason,
+                    ))?);
+                    if refusal.gate == Gate::Secrets {
+                        secret_files += 1;
+                    }
+                    progress.done.insert(key);
+                    continue;
+                }
+                let profile = meta.api_key_ref.as_deref().or(pusher_profile);
+                let connected = accounts.connected(profile);
+                if !connected && !budget.spend() {
+                    metrics.retries += accounts.retries();
+                    output.say_all(listing.flush());
+                    if !dryrun {
+                        queue(&mut retries, &report.rows[first_row..]);
+                        retries.save()?;
+                    }
+                    return stop(&progress, budget.used, dryrun, output);
+                }
+                let start = Instant::now();
+                let Account { backend, articles } = accounts.get(profile).await?;
+                if !connected {
+                    let took = metrics.request(start);
+                    if let Some(line) = timing("Listing articles", took, slow_request, 0) {
+                        output.say(line);
+                    }
+                    metrics.estimate.lists += 1;
+                }
+                let post = Post {
+                    meta: &meta,
+                    content: &content,
+                    body: &body,
+                };
+                let remote = state
+                    .get(&key)
+                    .and_then(|entry| articles.iter().find(|a| a.id == entry.id))
+                    .or_else(|| articles.iter().find(|a| a.title == meta.title));
+                let fields = Fields {
+                    url: remote
+                        .map(|remote| remote.url.as_str())
+                        .filter(|url| !url.is_empty()),
+                    published: remote.and_then(|remote| remote.published_at.as_deref()),
+                    ..fields
+                };
+                let status = match remote {
+                    // without a way to list articles, what was recorded when pushing is all there is to go on
+                    None if !backend.lists() && state.get(&key).is_some() => UploadStatus::Uploaded,
+                    None => UploadStatus::Posting,
+                    Some(remote) if untouched(&content, state.get(&key), remote) => {
+                        UploadStatus::Uploaded
+                    }
+                    Some(remote) => {
+                        if state::hash(backend.markdown(&post))
+                            != state::hash(&remote.body_markdown)
+                        {
+                            UploadStatus::Syncing(remote)
+                        } else {
+                            UploadStatus::Uploaded
+                        }
+                    }
+                };
+                let publishing = meta.publish_status() == PublishStatus::Published
+                    && match &status {
+                        UploadStatus::Posting => true,
+                        UploadStatus::Syncing(remote) => !remote.published,
+                        UploadStatus::Uploaded => false,
+                    };
+                // refuse content Forem would reject before spending a request on it
+                let limited = match (&status, target.kind) {
+                    (UploadStatus::Uploaded, _) | (_, Kind::Hashnode) | (_, Kind::Medium) => false,
+                    (_, Kind::Forem) => true,
+                };
+                if let Err(refusal) = gates.publish(&upload, publishing, limited) {
+                    output.say_all(listing.status(&fields, refusal.gate.to_string().red()));
+                    for detail in &refusal.details {
+                        output.say_all(listing.detail(format!("  ▶ {}", detail)));
+                    }
+                    if refusal.gate == Gate::Checklist {
+                        output.say_all(
+                            listing.detail("  ▶ Publish it anyway with --override-checklist"),
+                        );
+                    }
+                    report.rows.push(events.row(Row::failed(
+                        name,
+                        &key,
+                        &meta.title,
+                        refusal.reason,
+                    ))?);
+                    progress.done.insert(key);
+                    continue;
+                }
+                if let (UploadStatus::Syncing(remote), Some(cooldown), false) =
+                    (&status, config.update_cooldown, force)
+                {
+                    if let Some(next) = cooling_down(state.get(&key), cooldown, Utc::now()) {
+                        output.say_all(listing.status(&fields, "COOLING DOWN".yellow()));
+                        output.say_all(listing.detail(format!(
+                            "  ▶ updated less than {} ago, next update after {}",
+                            cooldown,
+                            next.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                        )));
+                        report.rows.push(events.row(Row {
+                            target: name.clone(),
+                            file: key.clone(),
+                            title: meta.title.clone(),
+                            action: Action::Deferred,
+                            changes: Some(report::changes(
+                                &remote.body_markdown,
+                                backend.markdown(&post),
+                            )),
+                            url: Some(remote.url.clone()).filter(|url| !url.is_empty()),
+                            error: None,
+                        })?);
+                        progress.done.insert(key);
+                        continue;
+                    }
+                }
+                if !dryrun && !matches!(status, UploadStatus::Uploaded) && !budget.spend() {
+                    metrics.retries += accounts.retries();
+                    output.say_all(listing.flush());
+                    queue(&mut retries, &report.rows[first_row..]);
+                    retries.save()?;
+                    return stop(&progress, budget.used, dryrun, output);
+                }
+                output.say_all(
+                    listing.status(&fields, format!("{} {}", status, meta.publish_status())),
                 );
+                let mut row = Row {
+                    target: name.clone(),
+                    file: key.clone(),
+                    title: meta.title.clone(),
+                    action: match status {
+                        UploadStatus::Uploaded => Action::Unchanged,
+                        UploadStatus::Posting => Action::Create,
+                        UploadStatus::Syncing(_) => Action::Update,
+                    },
+                    changes: match status {
+                        UploadStatus::Uploaded => None,
+                        UploadStatus::Posting => Some(report::changes("", backend.markdown(&post))),
+                        UploadStatus::Syncing(remote) => Some(report::changes(
+                            &remote.body_markdown,
+                            backend.markdown(&post),
+                        )),
+                    },
+                    url: remote.map(|r| r.url.clone()).filter(|url| !url.is_empty()),
+                    error: None,
+                };
+                let mut trip = Trip::Closed;
+                let launching = atomic
+                    && !matches!(status, UploadStatus::Uploaded)
+                    && meta.publish_status() == PublishStatus::Published;
+                let id = match &status {
+                    UploadStatus::Uploaded => None,
+                    UploadStatus::Posting => Some(None),
+                    UploadStatus::Syncing(remote) => Some(Some(remote.id.as_str())),
+                };
+                if let (true, Some(id)) = (dryrun, id) {
+                    let request = backend.request(id, &post);
+                    if let Ok(request) = &request {
+                        metrics.estimate.bytes += serde_json::to_vec(&request.body)?.len();
+                    }
+                    match id {
+                        Some(_) => metrics.estimate.updates += 1,
+                        None => metrics.estimate.creates += 1,
+                    }
+                    // drafts held back are published by another update
+                    let held_back = launching
+                        && !matches!(&status, UploadStatus::Syncing(remote) if remote.published);
+                    if held_back {
+                        metrics.estimate.publishes += 1;
+                    }
+                    if target.kind == Kind::Forem {
+                        metrics.estimate.forem_writes += 1 + usize::from(held_back);
+                    }
+                    match request {
+                        Ok(request) if verbose > 1 => output.say_all(listing.detail(request)),
+                        Ok(request) if verbose > 0 => output
+                            .say_all(listing.detail(format!("{} {}", request.method, request.url))),
+                        Err(err) if verbose > 0 => output.say_all(listing.detail(err)),
+                        _ => (),
+                    }
+                }
+                if launching && !dryrun {
+                    let (created, was_published) = match &status {
+                        UploadStatus::Syncing(remote) => (false, remote.published),
+                        _ => (true, false),
+                    };
+                    let previous = match &status {
+                        UploadStatus::Syncing(remote) if remote.published => {
+                            Some(remote.body_markdown.clone())
+                        }
+                        _ => None,
+                    };
+                    // articles already live are updated once everything else succeeded, and
+                    // others are uploaded as drafts until then
+                    let id = match status {
+                        UploadStatus::Syncing(remote) if remote.published => Ok(remote.id.clone()),
+                        _ => {
+                            let draft = article::set(&content, "published", "false")?;
+                            let post = Post {
+                                content: &draft,
+                                ..post
+                            };
+                            events.emit(name, &key, uploading(&meta.title, &status))?;
+                            let start = Instant::now();
+                            let uploaded = match status {
+                                UploadStatus::Syncing(remote) => {
+                                    backend.update(&remote.id, &post).await
+                                }
+                                _ => backend.create(&post).await,
+                            };
+                            let took = metrics.request(start);
+                            trip = breaker.record(uploaded.as_ref().err());
+                            if let Some(line) = timing("Upload", took, slow_request, verbose) {
+                                output.say_all(listing.detail(line));
+                            }
+                            if let Ok(article) = &uploaded {
+                                events.emit(
+                                    name,
+                                    &key,
+                                    uploaded_event(&meta.title, article, took),
+                                )?;
+                            }
+                            uploaded.map(|article| article.id)
+                        }
+                    };
+                    match id {
+                        Ok(id) => {
+                            output
+                                .say_all(listing.detail("Held back until every article is pushed"));
+                            pending.push(Pending {
+                                dir: root.dir.clone(),
+                                key: key.clone(),
+                                id,
+                                profile: profile.map(String::from),
+                                meta: meta.clone(),
+                                content: content.clone(),
+                                body: body.clone(),
+                                created,
+                                was_published,
+                                previous,
+                                row: report.rows.len(),
+                            });
+                        }
+                        Err(err) => {
+                            output.say_all(listing.detail(&err));
+                            row.action = Action::Failed;
+                            row.error = Some(err.to_string());
+                        }
+                    }
+                } else if !dryrun {
+                    let (created, was_published) = match &status {
+                        UploadStatus::Syncing(remote) => (false, remote.published),
+                        _ => (true, false),
+                    };
+                    if !matches!(status, UploadStatus::Uploaded) {
+                        events.emit(name, &key, uploading(&meta.title, &status))?;
+                    }
+                    let mut failure = None;
+                    let start = Instant::now();
+                    let uploaded = match status {
+                        UploadStatus::Syncing(remote) => {
+                            match backend.update(&remote.id, &post).await {
+                                Ok(article) => {
+                                    output.say_all(listing.detail("Update was successful"));
+                                    Some(article)
+                                }
+                                Err(err) => {
+                                    output.say_all(listing.detail(&err));
+                                    row.action = Action::Failed;
+                                    row.error = Some(err.to_string());
+                                    failure = Some(err);
+                                    None
+                                }
+                            }
+                        }
+                        UploadStatus::Posting => match backend.create(&post).await {
+                            Ok(article) => {
+                                output.say_all(listing.detail("Post was successful"));
+                                Some(article)
+                            }
+                            Err(err) => {
+                                output.say_all(listing.detail(&err));
+                                row.action = Action::Failed;
+                                row.error = Some(err.to_string());
+                                failure = Some(err);
+                                None
+                            }
+                        },
+                        UploadStatus::Uploaded => None,
+                    };
+                    if !matches!(status, UploadStatus::Uploaded) {
+                        let took = metrics.request(start);
+                        trip = breaker.record(failure.as_ref());
+                        if let Some(line) = timing("Upload", took, slow_request, verbose) {
+                            output.say_all(listing.detail(line));
+                        }
+                        if let Some(article) = &uploaded {
+                            events.emit(name, &key, uploaded_event(&meta.title, article, took))?;
+                        }
+                    }
+                    if let Some(article) = uploaded {
+                        if !article.url.is_empty() {
+                            row.url = Some(article.url.clone());
+                        }
+                        history::record(&root.dir, &key, &content, history_depth)?;
+                        let published = meta.publish_status() == PublishStatus::Published;
+                        let events = audit::Action::taken(created, was_published, published)
+                            .into_iter()
+                            .map(|action| {
+                                Event::new(
+                                    action,
+                                    name,
+                                    &key,
+                                    &article.id,
+                                    profile,
+                                    &content,
+                                    backend.markdown(&post),
+                                )
+                            })
+                            .collect::<Vec<_>>();
+                        audit::record(&root.dir, &events)?;
+                        state.record(
+                            key.clone(),
+                            Entry::new(&article.id, &content, backend.markdown(&post)),
+                        );
+                        state.save()?;
+                    }
+                }
+                report.rows.push(events.row(row)?);
+                progress.done.insert(key);
+                match trip {
+                    Trip::Closed => (),
+                    Trip::Pause(pause) => {
+                        output.say(format!(
+                            "{} {} api requests in a row failed, so it looks to be down. Pausing {}s before carrying on",
+                            "warning:".yellow(),
+                            breaker.threshold(),
+                            pause.as_secs()
+                        ));
+                        tokio::time::sleep(pause).await;
+                    }
+                    Trip::Open => {
+                        output.say_all(listing.flush());
+                        queue(&mut retries, &report.rows[first_row..]);
+                        retries.save()?;
+                        progress.save()?;
+                        bail!(
+                            "api requests kept failing, so the api looks to be down.\n  ▶ Re-run with --resume later to continue where this push left off"
+                        );
+                    }
+                }
+            }
+            output.say_all(listing.flush());
+            if unmodified_files > 0 {
+                output.say(format!(
+                    "Skipped {} file(s) unmodified since they were last pushed",
+                    unmodified_files
+                ));
+            }
+            if !dryrun {
+                progress.clear()?;
+                queue(&mut retries, &report.rows[first_row..]);
+                retries.save()?;
             }
         }
-        let series = string("series");
-        let canonical_url = string("canonical_url");
-        let cover_image = string("cover_image");
-
-        Ok(Frontmatter {
-            title,
-            published,
-            tags,
-            date,
-            series,
-            canonical_url,
-            cover_image,
-        })
+        metrics.retries += accounts.retries();
+        launches.push((name, accounts, pending));
+    }
+    if atomic && !dryrun {
+        launch(launches, report, history_depth, output, &listing).await?;
+    }
+    if secret_files > 0 {
+        bail!(
+            "{} file(s) look like they contain secrets and were not pushed.\n  ▶ Remove them, or re-run with --allow-secrets if they're safe to publish",
+            secret_files
+        );
     }
+    Ok(())
 }
 
-async fn post(
-    client: Client,
-    api_key: String,
-    content: String,
+/// publish the articles an --atomic push held back, unless any article failed to upload. Should
+/// publishing one fail, none stay published
+async fn launch(
+    mut launches: Vec<(&String, Accounts<'_>, Vec<Pending>)>,
+    report: &mut Report,
+    history_depth: usize,
+    output: Output,
+    listing: &Listing,
 ) -> anyhow::Result<()> {
-    again::retry(move || {
-        let client = client.clone();
-        let api_key = api_key.clone();
-        let content = content.clone();
-        async move {
-            let resp = client
-                .post("https://dev.to/api/articles")
-                .header("api-key", api_key.as_str())
-                .json(&CreateArticleInput {
-                    body_markdown: content,
-                })
-                .send()
-                .await?;
-
-            if !resp.status().is_success() {
-                println!("Dev.to error: {:#?} {}", resp.status(), resp.text().await?);
-            } else {
-                println!("Post was successful");
+    if report.rows.iter().any(|row| row.action == Action::Failed) {
+        return rollback(
+            launches,
+            &[],
+            output,
+            listing,
+            "Articles failed to upload, so none were published",
+        )
+        .await;
+    }
+    // articles published so far, by launch and position within it, with the markdown each stores
+    let mut published = Vec::new();
+    for (at, (name, accounts, pending)) in launches.iter_mut().enumerate() {
+        for (position, pending) in pending.iter().enumerate() {
+            let backend = &accounts.get(pending.profile.as_deref()).await?.backend;
+            let post = Post {
+                meta: &pending.meta,
+                content: &pending.content,
+                body: &pending.body,
+            };
+            let row = &mut report.rows[pending.row];
+            let fields = Fields {
+                target: name,
+                file: &pending.key,
+                title: &pending.meta.title,
+                url: row.url.as_deref(),
+                date: pending.meta.date.as_deref(),
+                published: None,
+            };
+            let article = match backend.update(&pending.id, &post).await {
+                Ok(article) => article,
+                Err(err) => {
+                    output.say(listing.line(&fields, "FAILED".red()));
+                    output.say(format!("  ▶ {}", err));
+                    row.action = Action::Failed;
+                    row.error = Some(err.to_string());
+                    let published = published
+                        .iter()
+                        .map(|(at, position, _, _)| (*at, *position))
+                        .collect::<Vec<_>>();
+                    return rollback(
+                        launches,
+                        &published,
+                        output,
+                        listing,
+                        "An article failed to publish, so none were left published",
+                    )
+                    .await;
+                }
+            };
+            let fields = Fields {
+                url: Some(article.url.as_str()).filter(|url| !url.is_empty()),
+                published: article.published_at.as_deref(),
+                ..fields
+            };
+            output.say(listing.line(&fields, "PUBLISHED".green()));
+            if !article.url.is_empty() {
+                row.url = Some(article.url.clone());
             }
-            Ok(())
+            let markdown = backend.markdown(&post).to_string();
+            published.push((at, position, article, markdown));
         }
-    })
-    .await
+    }
+    // only recorded once every article is published, as they'd otherwise be put back
+    for (at, position, article, markdown) in published {
+        let (name, _, pending) = &launches[at];
+        let pending = &pending[position];
+        history::record(&pending.dir, &pending.key, &pending.content, history_depth)?;
+        let events = audit::Action::taken(pending.created, pending.was_published, true)
+            .into_iter()
+            .map(|action| {
+                Event::new(
+                    action,
+                    name,
+                    &pending.key,
+                    &article.id,
+                    pending.profile.as_deref(),
+                    &pending.content,
+                    &markdown,
+                )
+            })
+            .collect::<Vec<_>>();
+        audit::record(&pending.dir, &events)?;
+        let mut state = State::load(&pending.dir, name)?;
+        state.record(
+            pending.key.clone(),
+            Entry::new(&article.id, &pending.content, &markdown),
+        );
+        state.save()?;
+    }
+    Ok(())
 }
 
-async fn put(
-    id: u32,
-    client: Client,
-    api_key: String,
-    content: String,
+/// undo an --atomic push which failed: articles it published, by launch and position within it,
+/// are put back as they were and the drafts it created are removed where the service can
+/// remove them
+async fn rollback(
+    launches: Vec<(&String, Accounts<'_>, Vec<Pending>)>,
+    published: &[(usize, usize)],
+    output: Output,
+    listing: &Listing,
+    failure: &str,
 ) -> anyhow::Result<()> {
-    again::retry(move || {
-        let client = client.clone();
-        let api_key = api_key.clone();
-        let content = content.clone();
-        async move {
-            let resp = client
-                .put(format!("https://dev.to/api/articles/{}", id).as_str())
-                .header("api-key", api_key.as_str())
-                .json(&CreateArticleInput {
-                    body_markdown: content,
+    let mut drafts = Vec::new();
+    let mut live = Vec::new();
+    for (at, (name, mut accounts, pending)) in launches.into_iter().enumerate() {
+        for (position, pending) in pending.iter().enumerate() {
+            let launched = published.contains(&(at, position));
+            if !pending.created && !launched {
+                continue;
+            }
+            let backend = &accounts.get(pending.profile.as_deref()).await?.backend;
+            let fields = Fields {
+                target: name,
+                file: &pending.key,
+                title: &pending.meta.title,
+                url: None,
+                date: pending.meta.date.as_deref(),
+                published: None,
+            };
+            let removed = if pending.created {
+                backend.remove(&pending.id).await.unwrap_or_else(|err| {
+                    output.say(format!("  ▶ {}", err));
+                    false
                 })
-                .send()
-                .await?;
-
-            if !resp.status().is_success() {
-                println!("Dev.to error {:#?} {}", resp.status(), resp.text().await?);
             } else {
-                println!("Update was successful");
+                false
+            };
+            if removed {
+                output.say(listing.line(&fields, "REMOVED".yellow()));
+                continue;
+            }
+            if launched {
+                if let Err(err) = revert(backend.as_ref(), pending).await {
+                    output.say(listing.line(&fields, "STILL PUBLISHED".red()));
+                    output.say(format!("  ▶ {}", err));
+                    live.push(pending.key.clone());
+                    continue;
+                }
+                output.say(listing.line(&fields, "REVERTED".yellow()));
+            }
+            if pending.created {
+                drafts.push(pending.key.clone());
             }
-            Ok(())
         }
-    })
-    .await
+    }
+    let mut message = failure.to_string();
+    if !drafts.is_empty() {
+        message.push_str(&format!(
+            ".\n  ▶ New articles were left as unpublished drafts, which the api can't delete: {}",
+            drafts.join(", ")
+        ));
+    }
+    if !live.is_empty() {
+        message.push_str(&format!(
+            ".\n  ▶ These articles could not be put back as they were and are still published: {}",
+            live.join(", ")
+        ));
+    }
+    bail!(message)
 }
 
-async fn fetch(
-    client: &Client,
-    api_key: &str,
-) -> anyhow::Result<Vec<Article>> {
-    let resp = client
-        .get("https://dev.to/api/articles/me/all?per_page=1000")
-        .header("api-key", api_key)
-        .send()
-        .await?;
-
-    if !resp.status().is_success() {
-        bail!("Dev.to error {:#?} - bad or invalid API Key", resp.status(),);
-    } else {
-        Ok(resp.json().await?)
+/// put an article an --atomic push published back as it was: its earlier content if it was
+/// already live, or else a draft
+async fn revert(
+    backend: &dyn Backend,
+    pending: &Pending,
+) -> anyhow::Result<()> {
+    match &pending.previous {
+        Some(previous) => {
+            let (meta, body) = article::extract(&pending.key, previous)?;
+            let post = Post {
+                meta: &meta,
+                content: previous,
+                body: &body,
+            };
+            backend.update(&pending.id, &post).await?;
+        }
+        None => {
+            let draft = article::set(&pending.content, "published", "false")?;
+            let post = Post {
+                meta: &pending.meta,
+                content: &draft,
+                body: &pending.body,
+            };
+            backend.update(&pending.id, &post).await?;
+        }
     }
+    Ok(())
 }
 
-fn valid_path(path: &PathBuf) -> bool {
-    !path.is_dir()
-        && path
-            .extension()
-            .into_iter()
-            .any(|e| e == "md" || e == "markdown")
+/// markdown read from stdin with a title, setting one when given
+fn titled(
+    input: &str,
+    title: Option<&str>,
+) -> anyhow::Result<String> {
+    let has_frontmatter = article::frontmatter_end(input).is_some();
+    match title {
+        Some(title) if has_frontmatter => {
+            article::set(input, "title", &serde_json::to_string(title)?)
+        }
+        Some(title) => Ok(format!(
+            "---\ntitle: {}\n---\n{}",
+            serde_json::to_string(title)?,
+            input
+        )),
+        None if has_frontmatter => Ok(input.into()),
+        None => {
+            bail!("markdown read from stdin has no frontmatter\n  ▶ Name its title with --title")
+        }
+    }
 }
 
-pub async fn run(
-    api_key: String,
+/// create an article from markdown read from stdin on each target, printing only their urls
+/// to stdout so scripts can capture them
+async fn push_stdin(
+    config: &Config,
     args: Push,
+    input: &str,
 ) -> anyhow::Result<()> {
-    let Push { source, dryrun } = args;
-    let client = Client::new();
-    let articles = fetch(&client, &api_key).await?;
-    let mut hasher = Sha256::new();
-    for path in WalkDir::new(source.unwrap_or_else(|| ".".into()))
-        .into_iter()
-        .filter_map(|e| e.ok().map(|e| e.path().to_path_buf()))
-        .filter(valid_path)
-    {
-        let client = client.clone();
-        let api_key = api_key.clone();
-        let content = fs::read_to_string(&path)?;
-        let name = path.file_name().unwrap_or_default().to_string_lossy();
-        let (meta, _) = extract(name.as_ref(), &content)?;
-        let status = match articles.iter().find(|a| a.title == meta.title) {
-            None => UploadStatus::Posting,
-            Some(remote) => {
-                let differ = {
-                    hasher.update(content.as_bytes());
-                    let local = hasher.finalize_reset();
-                    hasher.update(remote.body_markdown.as_bytes());
-                    let remote = hasher.finalize_reset();
-                    local != remote
-                };
-                if differ {
-                    UploadStatus::Syncing(remote)
-                } else {
-                    UploadStatus::Uploaded
-                }
-            }
+    let input = titled(input, args.title.as_deref())?;
+    let strictness = Strictness::from_flags(args.strict, args.lenient);
+    let source = args.source(config);
+    // placed at the root of the source directory, for relative links and its tags
+    let path = source.join(STDIN);
+    let gates = Gates::new(config, args.allow_secrets, args.override_checklist)?;
+    // markdown without a file has no path for owners to claim
+    let owners = Owners::default();
+    // every target's rendering is checked before any is created
+    let mut rendered = Vec::new();
+    for (name, target) in config.targets(&args.target)? {
+        let content = article::render_content(config, &source, &path, target.kind, &input).await?;
+        let (meta, body, warnings) =
+            article::parse("stdin", &content, strictness, config.timezone)?;
+        for warning in &warnings {
+            eprintln!("{} {}", "warning:".yellow(), warning);
+        }
+        let upload = Upload {
+            key: STDIN,
+            meta: &meta,
+            content: &content,
+            body: &body,
         };
-        println!(
-            "{}{}{}",
-            meta.title.chars().take(50).collect::<String>().bold(),
-            String::from(".")
-                .repeat(50_usize.checked_sub(meta.title.len()).unwrap_or_default())
-                .dimmed(),
-            format!("[{} {}]", status, meta.publish_status()).bold(),
-        );
-        if !dryrun {
-            match status {
-                UploadStatus::Syncing(remote) => {
-                    put(remote.id, client.clone(), api_key.clone(), content.clone()).await?
-                }
-                UploadStatus::Posting => {
-                    post(client.clone(), api_key.clone(), content.clone()).await?
-                }
-                _ => (),
+        let published = meta.publish_status() == PublishStatus::Published;
+        let profile = meta.api_key_ref.as_deref().or(args.profile.as_deref());
+        let limited = target.kind == Kind::Forem;
+        if let Err(refusal) = gates.check(&owners, profile, &upload, published, limited) {
+            let hint = match refusal.gate {
+                Gate::Secrets => "\n  ▶ Re-run with --allow-secrets if it's safe to publish",
+                Gate::Checklist => "\n  ▶ Publish it anyway with --override-checklist",
+                _ => "",
+            };
+            bail!(
+                "markdown read from stdin was not pushed: {}{}",
+                refusal.reason,
+                hint
+            );
+        }
+        rendered.push((name, target, meta, body, content));
+    }
+    for (name, target, meta, body, content) in rendered {
+        let post = Post {
+            meta: &meta,
+            content: &content,
+            body: &body,
+        };
+        if args.dryrun {
+            eprintln!(
+                "{}",
+                line(
+                    &meta.title,
+                    format!("{} {}", UploadStatus::Posting, meta.publish_status())
+                )
+            );
+            continue;
+        }
+        let profile = meta.api_key_ref.as_deref().or(args.profile.as_deref());
+        let target = match profile {
+            Some(profile) => target.with_profile(config.profile(profile)?),
+            None => target,
+        };
+        let backend = backend::new(&name, &target).await?;
+        let article = backend.create(&post).await?;
+        let published = meta.publish_status() == PublishStatus::Published;
+        let events = audit::Action::taken(true, false, published)
+            .into_iter()
+            .map(|action| {
+                Event::new(
+                    action,
+                    &name,
+                    STDIN,
+                    &article.id,
+                    profile,
+                    &content,
+                    backend.markdown(&post),
+                )
+            })
+            .collect::<Vec<_>>();
+        audit::record(&source, &events)?;
+        println!("{}", article.url);
+    }
+    Ok(())
+}
+
+/// true if a file wasn't modified since it was last in sync
+fn unmodified(
+    path: &Path,
+    entry: Option<&Entry>,
+) -> bool {
+    match (entry, fs::metadata(path).and_then(|m| m.modified())) {
+        (Some(entry), Ok(modified)) => DateTime::<Utc>::from(modified) <= entry.synced_at,
+        _ => false,
+    }
+}
+
+/// queue files which failed to push for retrying, and drop those which pushed from the queue
+fn queue(
+    retries: &mut Retries,
+    rows: &[Row],
+) {
+    for row in rows {
+        match row.action {
+            Action::Failed => {
+                retries
+                    .failed
+                    .insert(row.file.clone(), row.error.clone().unwrap_or_default());
+            }
+            _ => {
+                retries.failed.remove(&row.file);
             }
         }
     }
+}
+
+/// the event of starting to upload an article
+fn uploading<'a>(
+    title: &'a str,
+    status: &UploadStatus<'_>,
+) -> Progressed<'a> {
+    let action = match status {
+        UploadStatus::Syncing(_) => "update",
+        _ => "create",
+    };
+    Progressed::Uploading { title, action }
+}
+
+/// the event of having uploaded an article
+fn uploaded_event<'a>(
+    title: &'a str,
+    article: &'a Article,
+    took: std::time::Duration,
+) -> Progressed<'a> {
+    Progressed::Uploaded {
+        title,
+        url: Some(article.url.as_str()).filter(|url| !url.is_empty()),
+        seconds: took.as_secs_f64(),
+    }
+}
+
+/// how long a request took, warning when slower than `slow` and otherwise only with -v
+fn timing(
+    what: &str,
+    took: std::time::Duration,
+    slow: Duration,
+    verbose: u8,
+) -> Option<String> {
+    if took > slow.0.to_std().unwrap_or_default() {
+        Some(format!(
+            "{} {} took {:.1}s, longer than --slow-request {}",
+            "warning:".yellow(),
+            what,
+            took.as_secs_f64(),
+            slow
+        ))
+    } else if verbose > 0 {
+        Some(format!("  ▶ {} took {:.1}s", what, took.as_secs_f64()))
+    } else {
+        None
+    }
+}
+
+/// when a file was last modified, to sort files without a date by
+fn modified(path: &Path) -> Option<String> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(DateTime::<Utc>::from(modified).to_rfc3339())
+}
+
+/// when an article last in sync as recorded by `entry` may next be updated, if not yet
+fn cooling_down(
+    entry: Option<&Entry>,
+    cooldown: Duration,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    entry
+        .map(|entry| entry.synced_at + cooldown.0)
+        .filter(|next| *next > now)
+}
+
+/// true if neither side changed since the last push, judged by the local
+/// hash and remote timestamps alone rather than comparing content
+fn untouched(
+    content: &str,
+    entry: Option<&Entry>,
+    remote: &Article,
+) -> bool {
+    match (entry, remote.changed_at()) {
+        (Some(entry), Some(changed)) => {
+            entry.id == remote.id
+                && changed <= entry.synced_at
+                && state::hash(content) == entry.local
+        }
+        _ => false,
+    }
+}
+
+/// a test case per article, failing when it could not be published
+fn cases(report: &Report) -> Vec<Case> {
+    report
+        .rows
+        .iter()
+        .map(|row| Case {
+            classname: format!("devtogo.push.{}", row.target),
+            name: row.file.clone(),
+            failures: row.error.iter().cloned().collect(),
+            ..Case::default()
+        })
+        .collect()
+}
+
+/// record progress when a push runs out of requests
+fn stop(
+    progress: &Progress,
+    used: usize,
+    dryrun: bool,
+    output: Output,
+) -> anyhow::Result<()> {
+    if !dryrun {
+        progress.save()?;
+    }
+    output.say(format!(
+        "Stopped after {} request(s).\n  ▶ Re-run with --resume to continue where this push left off",
+        used
+    ));
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::recording::tests::{exchange, listing, replayed};
+    use serde_json::json;
 
-    #[test]
-    fn frontmatter_is_published_when_expected() {
-        assert_eq!(
-            Frontmatter::default().publish_status(),
-            PublishStatus::Draft
-        );
+    #[tokio::test]
+    async fn unreviewed_articles_are_streamed_as_errors() -> anyhow::Result<()> {
+        let source = std::env::temp_dir().join("devtogo-push-review-test");
+        let _ = fs::remove_dir_all(&source);
+        fs::create_dir_all(&source)?;
+        fs::write(
+            source.join("foo.md"),
+            "---\ntitle: foo\npublished: true\n---\nhi",
+        )?;
+        let path = std::env::temp_dir().join("devtogo-push-review.ndjson");
+        let config = Config {
+            require_review: true,
+            ..Config::default()
+        };
+        let args = Push::from_iter_safe([
+            "push".as_ref(),
+            "--dryrun".as_ref(),
+            "--skip-preflight".as_ref(),
+            "--source".as_ref(),
+            source.as_os_str(),
+            "--events-ndjson".as_ref(),
+            path.as_os_str(),
+        ])?;
+        run(&config, args).await?;
+        let lines = fs::read_to_string(&path)?
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<serde_json::Value>, _>>()?;
+        let error = lines
+            .iter()
+            .find(|line| line["event"] == "error")
+            .ok_or_else(|| anyhow!("no error was streamed"))?;
+        assert_eq!(error["file"], "foo.md");
         assert_eq!(
-            Frontmatter {
-                published: Some(false),
-                ..Frontmatter::default()
-            }
-            .publish_status(),
-            PublishStatus::Draft
+            error["message"],
+            "published articles must be reviewed, naming a reviewed_by"
         );
+        fs::remove_dir_all(&source)?;
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn atomic_pushes_are_put_back_when_publishing_fails() -> anyhow::Result<()> {
+        let source = std::env::temp_dir().join("devtogo-push-atomic-test");
+        let _ = fs::remove_dir_all(&source);
+        fs::create_dir_all(&source)?;
+        for name in ["a", "b"] {
+            fs::write(
+                source.join(format!("{}.md", name)),
+                format!("---\ntitle: {}\npublished: true\n---\nhi", name),
+            )?;
+        }
+        let draft = |name: &str| format!("---\ntitle: {}\npublished: false\n---\nhi", name);
+        let live = |name: &str| format!("---\ntitle: {}\npublished: true\n---\nhi", name);
+        let article = |id: u32, name: &str| json!({ "id": id, "title": name });
+        let mut failing = exchange("PUT", "articles/2", Some(&live("b")), json!({}));
+        failing["status"] = json!(422);
+        let args = Push::from_iter_safe([
+            "push".as_ref(),
+            "--atomic".as_ref(),
+            "--skip-preflight".as_ref(),
+            "--source".as_ref(),
+            source.as_os_str(),
+        ])?;
+        let config = Config::default();
+        let err = replayed(
+            &source.with_extension("recording"),
+            &[
+                // once while checking articles and again once pushing them
+                listing(json!([])),
+                listing(json!([])),
+                exchange("POST", "articles", Some(&draft("a")), article(1, "a")),
+                exchange("POST", "articles", Some(&draft("b")), article(2, "b")),
+                exchange("PUT", "articles/1", Some(&live("a")), article(1, "a")),
+                failing,
+                // a is only put back when it's updated as a draft again
+                exchange("PUT", "articles/1", Some(&draft("a")), article(1, "a")),
+            ],
+            run(&config, args),
+        )
+        .unwrap_err();
         assert_eq!(
-            Frontmatter {
-                published: Some(true),
-                ..Frontmatter::default()
-            }
-            .publish_status(),
-            PublishStatus::Published
+            err.to_string(),
+            "An article failed to publish, so none were left published.\n  ▶ New articles were left as unpublished drafts, which the api can't delete: a.md, b.md"
         );
+        assert!(State::load(&source, "devto")?.get("a.md").is_none());
+        fs::remove_dir_all(&source)?;
+        Ok(())
     }
+
     #[test]
-    fn valid_path_isnt_dirs() {
-        assert!(!valid_path(&PathBuf::from("/")))
+    fn upload_status_impl_display() {
+        fn test(_: impl fmt::Display) {}
+        test(UploadStatus::Posting)
     }
 
     #[test]
-    fn valid_path_contains_md_ext() {
-        assert!(valid_path(&PathBuf::from("/foo.md")));
+    fn budget_limits_requests() {
+        let mut budget = Budget {
+            max: Some(2),
+            used: 0,
+        };
+        assert!(budget.spend());
+        assert!(budget.spend());
+        assert!(!budget.spend());
+        assert_eq!(budget.used, 2);
     }
 
     #[test]
-    fn valid_path_contains_markdown_ext() {
-        assert!(valid_path(&PathBuf::from("/foo.markdown")));
+    fn budget_is_unlimited_by_default() {
+        let mut budget = Budget { max: None, used: 0 };
+        assert!((0..1000).all(|_| budget.spend()));
     }
 
     #[test]
-    fn valid_path_doesnt_contains_other_ext() {
-        assert!(!valid_path(&PathBuf::from("/foo.txt")));
+    fn cases_fail_articles_which_errored() {
+        let row = |file: &str, error: Option<&str>| Row {
+            target: "devto".into(),
+            file: file.into(),
+            title: file.into(),
+            action: Action::Create,
+            changes: None,
+            url: None,
+            error: error.map(String::from),
+        };
+        let cases = cases(&Report {
+            dryrun: false,
+            rows: vec![row("a.md", None), row("b.md", Some("nope"))],
+        });
+        assert_eq!(cases[0].classname, "devtogo.push.devto");
+        assert!(cases[0].failures.is_empty());
+        assert_eq!(cases[1].failures, vec!["nope"]);
     }
 
     #[test]
-    fn upload_status_impl_display() {
-        fn test(_: impl fmt::Display) {}
-        test(UploadStatus::Posting)
+    fn cooling_down_until_cooldown_passes() -> anyhow::Result<()> {
+        let entry = Entry::new("1", "hi", "hi");
+        let hour = "1h".parse()?;
+        assert_eq!(
+            cooling_down(Some(&entry), hour, entry.synced_at),
+            Some(entry.synced_at + chrono::Duration::hours(1))
+        );
+        assert_eq!(
+            cooling_down(
+                Some(&entry),
+                hour,
+                entry.synced_at + chrono::Duration::hours(2)
+            ),
+            None
+        );
+        assert_eq!(cooling_down(None, hour, entry.synced_at), None);
+        Ok(())
     }
 
     #[test]
-    fn publish_status_impl_display() {
-        fn test(_: impl fmt::Display) {}
-        test(PublishStatus::Draft)
+    fn queue_keeps_files_until_they_push() {
+        let mut retries = Retries::default();
+        retries.failed.insert("posts/a.md".into(), "offline".into());
+        queue(
+            &mut retries,
+            &[
+                Row {
+                    action: Action::Update,
+                    ..Row::failed("devto", "posts/a.md", "A", "")
+                },
+                Row::failed("devto", "posts/b.md", "B", "timed out"),
+            ],
+        );
+        assert_eq!(
+            retries.failed.into_iter().collect::<Vec<_>>(),
+            vec![("posts/b.md".to_string(), "timed out".to_string())]
+        );
     }
 
     #[test]
-    fn test_extract_fails_with_missing_frontmatter() {
-        let result = extract(
-            "foo.md",
-            r#"
-        "#,
+    fn timing_warns_about_slow_requests() -> anyhow::Result<()> {
+        colored::control::set_override(false);
+        let slow: Duration = "10s".parse()?;
+        let took = std::time::Duration::from_millis(1250);
+        assert_eq!(timing("Upload", took, slow, 0), None);
+        assert_eq!(
+            timing("Upload", took, slow, 1).as_deref(),
+            Some("  ▶ Upload took 1.2s")
+        );
+        assert_eq!(
+            timing("Upload", std::time::Duration::from_secs(12), slow, 0).as_deref(),
+            Some("warning: Upload took 12.0s, longer than --slow-request 10s")
         );
-        assert!(result.is_err())
+        Ok(())
     }
 
     #[test]
-    fn test_extract_fails_with_missing_title() {
-        let result = extract(
-            "foo.md",
-            r#"
-        --
-        --
-        "#,
-        );
-        assert!(result.is_err())
+    fn unmodified_compares_mtime_with_last_sync() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join("devtogo-unmodified.md");
+        fs::write(&path, "hi")?;
+        let mut entry = Entry::new("1", "hi", "hi");
+        entry.synced_at = Utc::now() + chrono::Duration::hours(1);
+        assert!(unmodified(&path, Some(&entry)));
+        entry.synced_at = Utc::now() - chrono::Duration::hours(1);
+        assert!(!unmodified(&path, Some(&entry)));
+        assert!(!unmodified(&path, None));
+        fs::remove_file(&path)?;
+        Ok(())
     }
 
     #[test]
-    fn test_extract_passes_with_missing_frontmatter() -> anyhow::Result<()> {
-        let (front, _) = extract(
-            "foo.md",
-            r#"---
-            title: foo
-            ---
-            "#,
-        )?;
+    fn untouched_requires_both_sides_unchanged() {
+        let entry = Entry::new("1", "hi", "hi");
+        let remote = |at: chrono::DateTime<Utc>| Article {
+            id: "1".into(),
+            edited_at: Some(at.to_rfc3339()),
+            ..Article::default()
+        };
+        let before = remote(entry.synced_at - chrono::Duration::minutes(1));
+        let after = remote(entry.synced_at + chrono::Duration::minutes(1));
+        assert!(untouched("hi", Some(&entry), &before));
+        assert!(!untouched("changed", Some(&entry), &before));
+        assert!(!untouched("hi", Some(&entry), &after));
+        assert!(!untouched("hi", None, &before));
+        assert!(!untouched("hi", Some(&entry), &Article::default()));
+    }
+
+    #[tokio::test]
+    async fn stdin_is_rendered_and_checked() -> anyhow::Result<()> {
+        let source = std::env::temp_dir().join("devtogo-push-stdin-test");
+        let _ = fs::remove_dir_all(&source);
+        fs::create_dir_all(source.join("snippets"))?;
+        fs::write(source.join("snippets/bio.md"), "our codename ships soon\n")?;
+        let config = Config {
+            blocklist: vec!["codename".into()],
+            ..Config::default()
+        };
+        let args = Push::from_iter_safe([
+            "push".as_ref(),
+            "--stdin".as_ref(),
+            "--dryrun".as_ref(),
+            "--source".as_ref(),
+            source.as_os_str(),
+        ])?;
+        let input = "---\ntitle: foo\n---\n{{< include \"snippets/bio.md\" >}}\n";
+        let err = push_stdin(&config, args.clone(), input).await.unwrap_err();
         assert_eq!(
-            front,
-            Frontmatter {
-                title: "foo".into(),
-                ..Frontmatter::default()
-            }
+            err.to_string(),
+            "markdown read from stdin was not pushed: contains blocked terms codename"
         );
+        push_stdin(&config, args, "---\ntitle: foo\n---\nhi\n").await?;
+        fs::remove_dir_all(&source)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stdin_requires_review() -> anyhow::Result<()> {
+        let config = Config {
+            require_review: true,
+            ..Config::default()
+        };
+        let args = Push::from_iter_safe(["push", "--stdin", "--dryrun"])?;
+        let err = push_stdin(
+            &config,
+            args.clone(),
+            "---\ntitle: foo\npublished: true\n---\nhi",
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "markdown read from stdin was not pushed: published articles must be reviewed, naming a reviewed_by"
+        );
+        push_stdin(&config, args, "---\ntitle: foo\n---\nhi").await?;
         Ok(())
     }
 
     #[test]
-    fn test_extract_validates_date() {
-        let result = extract(
-            "foo.md",
-            r#"---
-            title: foo
-            date: ...
-            ---
-            "#,
+    fn titled_sets_titles() -> anyhow::Result<()> {
+        assert_eq!(
+            titled("hello\n", Some("Quick note"))?,
+            "---\ntitle: \"Quick note\"\n---\nhello\n"
+        );
+        assert_eq!(
+            titled("---\ntitle: foo\n---\nhello\n", Some("Quick note"))?,
+            "---\ntitle: Quick note\n---\nhello\n"
+        );
+        assert_eq!(
+            titled("---\ntitle: foo\n---\nhello\n", None)?,
+            "---\ntitle: foo\n---\nhello\n"
         );
-        assert!(result.is_err());
+        assert!(titled("hello\n", None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn line_pads_short_titles() {
+        colored::control::set_override(false);
+        assert_eq!(line("foo", "OK"), format!("foo{}[OK]", ".".repeat(47)));
     }
 }
@@ -0,0 +1,250 @@
+use crate::{
+    api::{self, Article},
+    article,
+    config::{Config, Kind},
+    console,
+    state::DIR,
+    vault,
+};
+use anyhow::bail;
+use colored::Colorize;
+use regex::{Captures, Regex};
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+use structopt::StructOpt;
+
+/// Name of the directory, within a source directory's .devtogo directory, articles read are
+/// cached in
+const CACHE: &str = "reading";
+
+/// Renders a published article in the terminal, paging it when it's long
+///
+/// Articles are cached once read, so they can be read again offline
+#[derive(StructOpt, Debug)]
+pub struct Read {
+    /// Url of the article, its username/slug or its id
+    article: String,
+    /// Directory whose .devtogo directory articles are cached in. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Configured target to read from. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+    /// Print the article rather than paging it
+    #[structopt(long)]
+    no_pager: bool,
+}
+
+/// the api path of an article, from its url, username/slug or id
+pub fn path(article: &str) -> anyhow::Result<String> {
+    if !article.is_empty() && article.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(format!("articles/{}", article));
+    }
+    let path = match reqwest::Url::parse(article) {
+        Ok(url) => url.path().to_string(),
+        Err(_) => article.to_string(),
+    };
+    match path.trim_matches('/').split('/').collect::<Vec<_>>()[..] {
+        [username, slug] if !username.is_empty() && !slug.is_empty() => {
+            Ok(format!("articles/{}/{}", username, slug))
+        }
+        _ => bail!(
+            "{} is not an article.\n  ▶ Pass its url, username/slug or id",
+            article
+        ),
+    }
+}
+
+/// markdown for reading an article, headed by its title and author
+fn document(article: &Article) -> String {
+    format!(
+        "# {}\n\nby {} {}\n\n{}",
+        article.title,
+        article.user.name,
+        article.url,
+        article::body(&article.body_markdown).trim()
+    )
+}
+
+/// text with inline markdown styled
+fn inline(text: &str) -> String {
+    let images = Regex::new(r"!\[([^\]]*)\]\([^)]*\)").expect("valid regex");
+    let links = Regex::new(r"\[([^\]]+)\]\(([^)\s]+)[^)]*\)").expect("valid regex");
+    let code = Regex::new(r"`([^`]+)`").expect("valid regex");
+    let strong = Regex::new(r"\*\*([^*]+)\*\*|__([^_]+)__").expect("valid regex");
+    let text = images.replace_all(text, |c: &Captures| {
+        format!("[image: {}]", &c[1]).dimmed().to_string()
+    });
+    let text = links.replace_all(&text, |c: &Captures| {
+        format!("{} {}", c[1].underline(), format!("({})", &c[2]).dimmed())
+    });
+    let text = code.replace_all(&text, |c: &Captures| c[1].cyan().to_string());
+    let text = strong.replace_all(&text, |c: &Captures| {
+        c.get(1)
+            .or_else(|| c.get(2))
+            .map(|m| m.as_str())
+            .unwrap_or_default()
+            .bold()
+            .to_string()
+    });
+    text.into_owned()
+}
+
+/// markdown styled for a terminal
+pub fn render(markdown: &str) -> String {
+    let mut out = Vec::new();
+    let mut fence: Option<&str> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            } else {
+                out.push(format!("    {}", line.cyan()));
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            continue;
+        }
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let rendered = if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            let heading = inline(trimmed[level..].trim());
+            if level == 1 {
+                heading.bold().underline().to_string()
+            } else {
+                heading.bold().to_string()
+            }
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            format!("{} {}", "│".dimmed(), inline(quote.trim_start()).italic())
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .or_else(|| trimmed.strip_prefix("+ "))
+        {
+            let indent = &line[..line.len() - trimmed.len()];
+            format!("{}• {}", indent, inline(item))
+        } else if trimmed.starts_with("{%") {
+            trimmed.dimmed().to_string()
+        } else if trimmed.chars().all(|c| c == '-' || c == '*') && trimmed.len() >= 3 {
+            "─".repeat(40).dimmed().to_string()
+        } else {
+            inline(line)
+        };
+        out.push(rendered);
+    }
+    out.join("\n")
+}
+
+/// print text through $PAGER, or less, when printing to a terminal
+fn page(text: &str) -> anyhow::Result<()> {
+    if !io::stdout().is_terminal() {
+        println!("{}", text);
+        return Ok(());
+    }
+    let pager = env::var("PAGER").unwrap_or_else(|_| "less -R".into());
+    let mut words = pager.split_whitespace();
+    let child = match words.next() {
+        Some(program) => Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .spawn(),
+        None => Err(io::ErrorKind::NotFound.into()),
+    };
+    let mut child = match child {
+        Ok(child) => child,
+        Err(_) => {
+            println!("{}", text);
+            return Ok(());
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // quitting the pager early closes its input
+        match writeln!(stdin, "{}", text) {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(err.into()),
+            _ => (),
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+pub async fn run(
+    config: &Config,
+    args: Read,
+) -> anyhow::Result<()> {
+    let Read {
+        article,
+        source,
+        target,
+        no_pager,
+    } = args;
+    let (_, target) = config.target(target.as_deref())?;
+    if target.kind != Kind::Forem {
+        bail!("reading articles is only supported with Forem targets");
+    }
+    let path = path(&article)?;
+    let cached = config.source(source).join(DIR).join(CACHE).join(format!(
+        "{}.md",
+        path.trim_start_matches("articles/").replace('/', "-")
+    ));
+    let markdown = match api::public::<Article>(&target, &path).await {
+        Ok(article) => {
+            let markdown = document(&article);
+            if let Some(dir) = cached.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            // encrypted along with the rest of state, when it is
+            vault::write(&cached, &markdown)?;
+            markdown
+        }
+        Err(err) => match vault::read(&cached) {
+            Ok(markdown) => {
+                eprintln!(
+                    "{}",
+                    format!("Showing a cached copy, as fetching failed: {}", err).dimmed()
+                );
+                markdown
+            }
+            Err(_) => return Err(err),
+        },
+    };
+    let rendered = console::text(&render(&markdown)).into_owned();
+    if no_pager {
+        println!("{}", rendered);
+        Ok(())
+    } else {
+        page(&rendered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_accepts_urls_slugs_and_ids() -> anyhow::Result<()> {
+        assert_eq!(path("1234")?, "articles/1234");
+        assert_eq!(
+            path("https://dev.to/ada/hello-4k2j")?,
+            "articles/ada/hello-4k2j"
+        );
+        assert_eq!(path("ada/hello-4k2j/")?, "articles/ada/hello-4k2j");
+        assert!(path("https://dev.to/ada").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn render_styles_markdown() {
+        colored::control::set_override(false);
+        assert_eq!(
+            render("# Hello\n\n- a [link](https://a.b) and `code`\n```rust\nfn main() {}\n```\n> quoted"),
+            "Hello\n\n• a link (https://a.b) and code\n    fn main() {}\n│ quoted"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use crate::backend::REDACTED;
+use anyhow::{anyhow, bail};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::{RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Endings of names of fields, headers and parameters whose values are left out of recordings
+const SECRETS: &[&str] = &["token", "secret", "password", "key", "code", "cookie"];
+
+/// A request and the response it got, as recorded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Exchange {
+    method: String,
+    url: String,
+    /// request body, with secrets redacted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request: Option<String>,
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    body: String,
+    /// true if body is base64 encoded, as it wasn't text
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    base64: bool,
+}
+
+/// Http exchanges being recorded to, or replayed from, a directory
+#[derive(Debug)]
+enum Session {
+    Record { dir: PathBuf, count: usize },
+    Replay { exchanges: Vec<(Exchange, bool)> },
+}
+
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+/// record every http exchange to a directory, or replay those recorded in one in place of
+/// sending requests
+pub fn init(
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let session = match (record, replay) {
+        (Some(dir), _) => {
+            fs::create_dir_all(&dir)?;
+            Some(Session::Record { dir, count: 0 })
+        }
+        (_, Some(dir)) => Some(Session::Replay {
+            exchanges: load(&dir)?.into_iter().map(|e| (e, false)).collect(),
+        }),
+        _ => None,
+    };
+    *SESSION.lock().unwrap_or_else(|err| err.into_inner()) = session;
+    Ok(())
+}
+
+/// true if responses are replayed, so no credentials are needed
+pub fn replaying() -> bool {
+    matches!(
+        *SESSION.lock().unwrap_or_else(|err| err.into_inner()),
+        Some(Session::Replay { .. })
+    )
+}
+
+/// exchanges recorded in a directory, in the order they were made
+fn load(dir: &Path) -> anyhow::Result<Vec<Exchange>> {
+    let mut paths = fs::read_dir(dir)
+        .map_err(|err| anyhow!("unable to read recording {}: {}", dir.display(), err))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().into_iter().any(|ext| ext == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            serde_json::from_str(&fs::read_to_string(path)?)
+                .map_err(|err| anyhow!("{} is not a recorded exchange: {}", path.display(), err))
+        })
+        .collect()
+}
+
+fn secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRETS.iter().any(|secret| name.ends_with(secret))
+}
+
+/// json with the values of secret fields redacted
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields.iter_mut() {
+                if secret(name) && !value.is_null() {
+                    *value = REDACTED.into();
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(redact_json),
+        _ => (),
+    }
+}
+
+/// form encoded parameters with the values of secret ones redacted
+fn redact_form(form: &str) -> String {
+    form.split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if secret(name) => format!("{}={}", name, REDACTED),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// a body with secrets redacted, whether it's json or form encoded
+fn redact(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact_json(&mut value);
+            value.to_string()
+        }
+        Err(_) if body.contains('=') && !body.contains(char::is_whitespace) => redact_form(body),
+        Err(_) => body.into(),
+    }
+}
+
+/// a url with the values of secret query parameters redacted
+fn redact_url(url: &reqwest::Url) -> String {
+    match url.query() {
+        Some(query) => {
+            let mut url = url.clone();
+            url.set_query(Some(&redact_form(query)));
+            url.to_string()
+        }
+        None => url.to_string(),
+    }
+}
+
+/// a response rebuilt from a recorded exchange
+fn response(exchange: &Exchange) -> anyhow::Result<Response> {
+    let body = if exchange.base64 {
+        STANDARD.decode(&exchange.body)?
+    } else {
+        exchange.body.clone().into_bytes()
+    };
+    let mut builder = http::Response::builder().status(exchange.status);
+    for (name, value) in &exchange.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    Ok(Response::from(builder.body(body)?))
+}
+
+/// send a request, recording the exchange or replaying a recorded one when asked to
+pub async fn send(request: RequestBuilder) -> anyhow::Result<Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let method = request.method().to_string();
+    let url = redact_url(request.url());
+    let sent = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(|body| redact(&String::from_utf8_lossy(body)));
+    let recording = {
+        let mut session = SESSION.lock().unwrap_or_else(|err| err.into_inner());
+        match &mut *session {
+            None => None,
+            Some(Session::Replay { exchanges }) => {
+                let mut recorded = exchanges
+                    .iter_mut()
+                    .filter(|(exchange, used)| {
+                        !used && exchange.method == method && exchange.url == url
+                    })
+                    .peekable();
+                if recorded.peek().is_none() {
+                    bail!("no recorded response to {} {}", method, url);
+                }
+                // requests recorded with a body are only answered when they send it again, so
+                // replays notice content sent differently
+                let found = recorded
+                    .find(|(exchange, _)| exchange.request.is_none() || exchange.request == sent)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "{} {} sent a body other than the recorded one: {}",
+                            method,
+                            url,
+                            sent.as_deref().unwrap_or_default()
+                        )
+                    })?;
+                found.1 = true;
+                return response(&found.0);
+            }
+            Some(Session::Record { dir, count }) => {
+                *count += 1;
+                Some(dir.join(format!("{:04}.json", count)))
+            }
+        }
+    };
+    let path = match recording {
+        Some(path) => path,
+        None => return Ok(client.execute(request).await?),
+    };
+    let resp = client.execute(request).await?;
+    let status = resp.status().as_u16();
+    let headers = resp
+        .headers()
+        .iter()
+        .filter(|(name, _)| !secret(name.as_str()))
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                value.to_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect();
+    let bytes = resp.bytes().await?;
+    let (body, base64) = match std::str::from_utf8(&bytes) {
+        Ok(text) => (redact(text), false),
+        Err(_) => (STANDARD.encode(&bytes), true),
+    };
+    let exchange = Exchange {
+        method,
+        url,
+        request: sent,
+        status,
+        headers,
+        body,
+        base64,
+    };
+    fs::write(&path, serde_json::to_string_pretty(&exchange)?)?;
+    response(&exchange)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::future::Future;
+
+    /// run a command answering requests only with `exchanges`, written to `dir` while it runs,
+    /// failing unless it made every request. Replaying is global, so one command runs at a time
+    pub(crate) fn replayed<T>(
+        dir: &Path,
+        exchanges: &[Value],
+        command: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        static REPLAYING: Mutex<()> = Mutex::new(());
+        let _replaying = REPLAYING.lock().unwrap_or_else(|err| err.into_inner());
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(dir)?;
+        for (n, exchange) in exchanges.iter().enumerate() {
+            fs::write(dir.join(format!("{:04}.json", n + 1)), exchange.to_string())?;
+        }
+        init(None, Some(dir.into()))?;
+        let result = tokio::runtime::Runtime::new()?.block_on(command);
+        let unused = match &*SESSION.lock().unwrap_or_else(|err| err.into_inner()) {
+            Some(Session::Replay { exchanges }) => exchanges
+                .iter()
+                .filter(|(_, used)| !used)
+                .map(|(exchange, _)| format!("{} {}", exchange.method, exchange.url))
+                .collect(),
+            _ => Vec::new(),
+        };
+        init(None, None)?;
+        fs::remove_dir_all(dir)?;
+        if !unused.is_empty() {
+            bail!("never requested {}", unused.join(", "));
+        }
+        result
+    }
+
+    /// an exchange with the api, answered only when a request sends `body_markdown`, if given
+    pub(crate) fn exchange(
+        method: &str,
+        path: &str,
+        body_markdown: Option<&str>,
+        response: Value,
+    ) -> Value {
+        json!({
+            "method": method,
+            "url": format!("https://dev.to/api/{}", path),
+            "request": body_markdown.map(|body| json!({ "body_markdown": body }).to_string()),
+            "status": 200,
+            "body": response.to_string(),
+        })
+    }
+
+    /// the account's articles, as listed
+    pub(crate) fn listing(articles: Value) -> Value {
+        exchange("GET", "articles/me/all?per_page=1000", None, articles)
+    }
+
+    #[test]
+    fn redact_hides_secrets() {
+        assert_eq!(
+            redact(r#"{"access_token": "abc", "article": {"title": "hi", "api_key": "xyz"}}"#),
+            r#"{"access_token":"<redacted>","article":{"api_key":"<redacted>","title":"hi"}}"#
+        );
+        assert_eq!(
+            redact("grant_type=refresh_token&refresh_token=abc&client_id=devtogo"),
+            "grant_type=refresh_token&refresh_token=<redacted>&client_id=devtogo"
+        );
+        assert_eq!(redact("# Hello, world"), "# Hello, world");
+    }
+
+    #[test]
+    fn response_is_rebuilt_from_exchanges() -> anyhow::Result<()> {
+        let exchange = Exchange {
+            method: "GET".into(),
+            url: "https://dev.to/api/users/me".into(),
+            request: None,
+            status: 429,
+            headers: vec![("retry-after".into(), "30".into())],
+            body: "{}".into(),
+            base64: false,
+        };
+        let resp = response(&exchange)?;
+        assert_eq!(resp.status().as_u16(), 429);
+        assert_eq!(resp.headers()["retry-after"], "30");
+        Ok(())
+    }
+}
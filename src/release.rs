@@ -0,0 +1,172 @@
+use crate::{
+    article,
+    config::Config,
+    duration::Duration,
+    push::{self, Push},
+    state::{self, Release as Scheduled, Schedule},
+};
+use chrono::{DateTime, Local, Utc};
+use colored::Colorize;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// Publishes drafts one at a time, spaced out so followers aren't flooded with new articles
+///
+/// Drafts named are scheduled one --spread apart, in order, the first right away. Each run
+/// publishes the next draft which is due and pushes, so run it regularly, i.e. from a
+/// scheduled CI job
+#[derive(StructOpt, Debug)]
+pub struct Release {
+    /// Drafts to schedule. Without any, only what's due is published
+    files: Vec<PathBuf>,
+    /// Time between publishing drafts, i.e. 12h or 1d
+    #[structopt(long, default_value = "1d")]
+    spread: Duration,
+    #[structopt(flatten)]
+    args: Push,
+}
+
+/// schedule files after those already scheduled, one `spread` apart
+fn plan(
+    releases: &mut Vec<Scheduled>,
+    files: Vec<String>,
+    spread: Duration,
+    now: DateTime<Utc>,
+) {
+    let mut next = releases
+        .last()
+        .map(|release| release.due + spread.0)
+        .filter(|due| *due > now)
+        .unwrap_or(now);
+    for file in files {
+        if releases.iter().any(|release| release.file == file) {
+            continue;
+        }
+        releases.push(Scheduled { file, due: next });
+        next += spread.0;
+    }
+}
+
+/// the first release if it's due, pushing back the rest if they'd follow too closely, i.e.
+/// when releases weren't run for a while
+fn next_due(
+    releases: &mut Vec<Scheduled>,
+    spread: Duration,
+    now: DateTime<Utc>,
+) -> Option<Scheduled> {
+    if releases.first()?.due > now {
+        return None;
+    }
+    let due = releases.remove(0);
+    let mut next = now + spread.0;
+    for release in releases.iter_mut() {
+        if release.due < next {
+            release.due = next;
+        }
+        next = release.due + spread.0;
+    }
+    Some(due)
+}
+
+pub async fn run(
+    config: &Config,
+    args: Release,
+) -> anyhow::Result<()> {
+    let Release {
+        files,
+        spread,
+        args,
+    } = args;
+    let source = args.source(config);
+    let dryrun = args.dryrun();
+    let mut schedule = Schedule::load(&source)?;
+    let now = Utc::now();
+    let mut drafts = Vec::new();
+    for path in files {
+        let content = fs::read_to_string(&path)?;
+        let (meta, _) = article::extract(&path.to_string_lossy(), &content)?;
+        let key = state::key(&source, &path);
+        if meta.published.unwrap_or_default() {
+            println!("{}", push::line(&key, "ALREADY PUBLISHED".dimmed()));
+            continue;
+        }
+        drafts.push(key);
+    }
+    plan(&mut schedule.releases, drafts, spread, now);
+    let due = next_due(&mut schedule.releases, spread, now);
+    if let Some(release) = &due {
+        let path = source.join(&release.file);
+        if !dryrun {
+            let content = fs::read_to_string(&path)?;
+            fs::write(&path, article::set(&content, "published", "true")?)?;
+        }
+        println!("{}", push::line(&release.file, "RELEASED".green()));
+    }
+    for release in &schedule.releases {
+        println!(
+            "{}",
+            push::line(
+                &release.file,
+                format!(
+                    "SCHEDULED {}",
+                    release.due.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                )
+                .yellow()
+            )
+        );
+    }
+    if !dryrun {
+        schedule.save()?;
+    }
+    if due.is_some() {
+        push::run(config, args).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_spaces_drafts_after_those_scheduled() -> anyhow::Result<()> {
+        let now = Utc::now();
+        let day: Duration = "1d".parse()?;
+        let mut releases = Vec::new();
+        plan(&mut releases, vec!["a.md".into(), "b.md".into()], day, now);
+        plan(&mut releases, vec!["b.md".into(), "c.md".into()], day, now);
+        assert_eq!(
+            releases
+                .iter()
+                .map(|release| (release.file.as_str(), release.due))
+                .collect::<Vec<_>>(),
+            vec![
+                ("a.md", now),
+                ("b.md", now + day.0),
+                ("c.md", now + day.0 + day.0)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn next_due_releases_one_at_a_time() -> anyhow::Result<()> {
+        let now = Utc::now();
+        let day: Duration = "1d".parse()?;
+        let mut releases = Vec::new();
+        plan(
+            &mut releases,
+            vec!["a.md".into(), "b.md".into(), "c.md".into()],
+            day,
+            now - day.0 - day.0,
+        );
+        assert_eq!(
+            next_due(&mut releases, day, now).map(|r| r.file),
+            Some("a.md".into())
+        );
+        assert_eq!(releases[0].due, now + day.0);
+        assert_eq!(releases[1].due, now + day.0 + day.0);
+        assert_eq!(next_due(&mut releases, day, now), None);
+        Ok(())
+    }
+}
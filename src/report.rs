@@ -0,0 +1,209 @@
+use similar::{ChangeTag, TextDiff};
+
+/// What a push did, or would do, with a single article
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Unchanged,
+    Create,
+    Update,
+    /// an update postponed by `update_cooldown`
+    Deferred,
+    Failed,
+}
+
+impl Action {
+    fn as_str(self) -> &'static str {
+        match self {
+            Action::Unchanged => "unchanged",
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Deferred => "deferred",
+            Action::Failed => "failed",
+        }
+    }
+}
+
+/// A line in a report for a single article
+#[derive(Debug)]
+pub struct Row {
+    pub target: String,
+    /// file path relative to the source directory
+    pub file: String,
+    pub title: String,
+    pub action: Action,
+    /// lines added and removed
+    pub changes: Option<(usize, usize)>,
+    pub url: Option<String>,
+    /// why publishing failed
+    pub error: Option<String>,
+}
+
+impl Row {
+    /// a row for an article which could not be published
+    pub fn failed(
+        target: &str,
+        file: &str,
+        title: &str,
+        error: impl Into<String>,
+    ) -> Row {
+        Row {
+            target: target.into(),
+            file: file.into(),
+            title: title.into(),
+            action: Action::Failed,
+            changes: None,
+            url: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// A shareable markdown summary of a push, free of credentials and local paths
+#[derive(Debug, Default)]
+pub struct Report {
+    pub dryrun: bool,
+    pub rows: Vec<Row>,
+}
+
+/// lines added to and removed from `old` by `new`
+pub fn changes(
+    old: &str,
+    new: &str,
+) -> (usize, usize) {
+    TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .fold((0, 0), |(added, removed), change| match change.tag() {
+            ChangeTag::Insert => (added + 1, removed),
+            ChangeTag::Delete => (added, removed + 1),
+            ChangeTag::Equal => (added, removed),
+        })
+}
+
+/// escape text for a markdown table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+impl Report {
+    /// counts of articles by action, i.e. "2 created, 5 unchanged"
+    pub fn summary(&self) -> String {
+        let counts = [
+            (Action::Create, "created"),
+            (Action::Update, "updated"),
+            (Action::Unchanged, "unchanged"),
+            (Action::Deferred, "deferred"),
+            (Action::Failed, "failed"),
+        ]
+        .iter()
+        .filter_map(|(action, done)| {
+            match self.rows.iter().filter(|row| row.action == *action).count() {
+                0 => None,
+                count => Some(format!("{} {}", count, done)),
+            }
+        })
+        .collect::<Vec<_>>();
+        if counts.is_empty() {
+            return "No articles to publish".into();
+        }
+        counts.join(", ")
+    }
+
+    /// articles which failed to publish and why, i.e. "posts/a.md on devto: offline"
+    pub fn failures(&self) -> Vec<String> {
+        self.rows
+            .iter()
+            .filter(|row| row.action == Action::Failed)
+            .map(|row| {
+                format!(
+                    "{} on {}: {}",
+                    row.file,
+                    row.target,
+                    row.error.as_deref().unwrap_or_default()
+                )
+            })
+            .collect()
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "## devtogo push{}\n\n",
+            if self.dryrun { " (dry run)" } else { "" }
+        );
+        if self.rows.is_empty() {
+            out.push_str("No articles to publish\n");
+            return out;
+        }
+        out.push_str("| Target | File | Title | Action | Changes | Url |\n");
+        out.push_str("| --- | --- | --- | --- | --- | --- |\n");
+        for row in &self.rows {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                cell(&row.target),
+                cell(&row.file),
+                cell(&row.title),
+                row.action.as_str(),
+                row.changes
+                    .map(|(added, removed)| format!("+{} -{}", added, removed))
+                    .unwrap_or_default(),
+                row.url.as_deref().unwrap_or_default()
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_counts_lines() {
+        assert_eq!(changes("a\nb\n", "a\nc\nd\n"), (2, 1));
+        assert_eq!(changes("a\n", "a\n"), (0, 0));
+    }
+
+    #[test]
+    fn render_tabulates_rows() {
+        let report = Report {
+            dryrun: true,
+            rows: vec![Row {
+                target: "devto".into(),
+                file: "posts/a.md".into(),
+                title: "a | b".into(),
+                action: Action::Update,
+                changes: Some((2, 1)),
+                url: Some("https://dev.to/a".into()),
+                error: None,
+            }],
+        };
+        assert_eq!(
+            report.render(),
+            "## devtogo push (dry run)\n\n| Target | File | Title | Action | Changes | Url |\n| --- | --- | --- | --- | --- | --- |\n| devto | posts/a.md | a \\| b | update | +2 -1 | https://dev.to/a |\n"
+        );
+    }
+
+    #[test]
+    fn render_without_rows() {
+        assert!(Report::default()
+            .render()
+            .ends_with("No articles to publish\n"));
+        assert_eq!(Report::default().summary(), "No articles to publish");
+    }
+
+    #[test]
+    fn summary_counts_actions() {
+        let report = Report {
+            dryrun: false,
+            rows: ["a", "b", "c"]
+                .iter()
+                .map(|file| Row::failed("devto", file, file, "offline"))
+                .chain(std::iter::once(Row {
+                    action: Action::Create,
+                    ..Row::failed("devto", "d", "d", "")
+                }))
+                .collect(),
+        };
+        assert_eq!(report.summary(), "1 created, 3 failed");
+        assert_eq!(report.failures()[0], "a on devto: offline");
+    }
+}
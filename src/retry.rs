@@ -0,0 +1,20 @@
+use crate::{
+    config::Config,
+    push::{self, Push},
+};
+use structopt::StructOpt;
+
+/// Pushes again only the files which failed to push before, i.e. after a flaky network
+/// interrupted a large push, taking the same options as push
+#[derive(StructOpt, Debug)]
+pub struct Retry {
+    #[structopt(flatten)]
+    push: Push,
+}
+
+pub async fn run(
+    config: &Config,
+    args: Retry,
+) -> anyhow::Result<()> {
+    push::run(config, args.push.retrying()).await
+}
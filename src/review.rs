@@ -0,0 +1,58 @@
+use crate::article::{Frontmatter, PublishStatus};
+use std::process::Command;
+
+/// name of whoever is pushing, from git's user.name, taken as the author of articles which
+/// don't name one
+pub fn git_user() -> Option<String> {
+    let output = Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(name).filter(|name| !name.is_empty())
+}
+
+/// why an article may not be published without review, if it may not. Articles kept as drafts
+/// need no review
+pub fn unreviewed(
+    meta: &Frontmatter,
+    pusher: Option<&str>,
+) -> Option<String> {
+    if meta.publish_status() != PublishStatus::Published {
+        return None;
+    }
+    let reviewer = match meta.reviewed_by.as_deref().map(str::trim) {
+        Some(reviewer) if !reviewer.is_empty() => reviewer,
+        _ => return Some("published articles must be reviewed, naming a reviewed_by".into()),
+    };
+    match meta.author.as_deref().or(pusher).map(str::trim) {
+        Some(author) if author.eq_ignore_ascii_case(reviewer) => Some(format!(
+            "reviewed_by names the author, {}. Someone else must review it",
+            author
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreviewed_requires_another_reviewer() {
+        let meta = |author: Option<&str>, reviewed_by: Option<&str>| Frontmatter {
+            published: Some(true),
+            author: author.map(String::from),
+            reviewed_by: reviewed_by.map(String::from),
+            ..Frontmatter::default()
+        };
+        assert!(unreviewed(&meta(None, None), None).is_some());
+        assert!(unreviewed(&meta(None, Some(" ")), None).is_some());
+        assert!(unreviewed(&meta(Some("ada"), Some("Ada")), None).is_some());
+        assert!(unreviewed(&meta(None, Some("ada")), Some("ada")).is_some());
+        assert!(unreviewed(&meta(Some("ada"), Some("grace")), Some("grace")).is_none());
+        assert!(unreviewed(&meta(None, Some("grace")), Some("ada")).is_none());
+        assert!(unreviewed(&Frontmatter::default(), None).is_none());
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::anyhow;
+use std::{fs, path::Path, process::Command};
+
+/// A fenced code block of a language checked by `lint --check-code`
+#[derive(Debug, PartialEq)]
+pub struct Sample {
+    /// 1-based line of the opening fence
+    pub line: usize,
+    pub language: String,
+    pub code: String,
+}
+
+/// file extension samples of a language are written with, so checks recognize them
+fn extension(language: &str) -> &str {
+    match language {
+        "rust" => "rs",
+        "python" => "py",
+        "javascript" => "js",
+        "typescript" => "ts",
+        "ruby" => "rb",
+        "bash" | "shell" => "sh",
+        other => other,
+    }
+}
+
+/// fenced code blocks in any of `languages`. Blocks marked like ```rust,ignore are skipped, as
+/// in rustdoc
+pub fn extract(
+    content: &str,
+    languages: &[String],
+) -> Vec<Sample> {
+    let mut samples = Vec::new();
+    let mut open: Option<(&str, usize, Option<String>)> = None;
+    let mut code = Vec::new();
+    for (n, line) in content.split('\n').enumerate() {
+        let trimmed = line.trim();
+        match &open {
+            None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                let mut info = trimmed[3..]
+                    .trim_start_matches(['`', '~'])
+                    .split([',', ' '])
+                    .filter(|part| !part.is_empty());
+                let language = info.next().unwrap_or_default();
+                let checked = languages.iter().any(|l| l == language)
+                    && !info.any(|attribute| attribute == "ignore");
+                open = Some((&trimmed[..3], n, checked.then(|| language.to_string())));
+                code.clear();
+            }
+            Some((marker, start, language)) if trimmed.starts_with(marker) => {
+                if let Some(language) = language {
+                    samples.push(Sample {
+                        line: start + 1,
+                        language: language.clone(),
+                        code: code.join("\n"),
+                    });
+                }
+                open = None;
+            }
+            Some(_) => code.push(line),
+            None => (),
+        }
+    }
+    samples
+}
+
+impl Sample {
+    /// run a check command, with {file} replaced by the path of a file holding the sample, in a
+    /// scratch directory. Returns what the command reported when the check fails
+    pub fn check(
+        &self,
+        command: &str,
+        dir: &Path,
+    ) -> anyhow::Result<Option<String>> {
+        fs::create_dir_all(dir)?;
+        let file = dir.join(format!("sample.{}", extension(&self.language)));
+        fs::write(&file, &self.code)?;
+        let command = command.replace("{file}", &file.to_string_lossy());
+        let output = Command::new("sh")
+            .args(["-c", &command])
+            .current_dir(dir)
+            .output()
+            .map_err(|err| anyhow!("failed to run {}: {}", command, err))?;
+        if output.status.success() {
+            return Ok(None);
+        }
+        let reported = [&output.stderr, &output.stdout]
+            .iter()
+            .map(|out| String::from_utf8_lossy(out).trim().to_string())
+            .find(|out| !out.is_empty())
+            .unwrap_or_else(|| format!("{} failed with {}", command, output.status));
+        Ok(Some(reported))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_finds_selected_languages() {
+        let content = "```rust\nfn main() {}\n```\n\n```rust,ignore\nnope\n```\n~~~bash\necho hi\n~~~\n```python\nprint()\n```";
+        assert_eq!(
+            extract(content, &["rust".into(), "bash".into()]),
+            vec![
+                Sample {
+                    line: 1,
+                    language: "rust".into(),
+                    code: "fn main() {}".into()
+                },
+                Sample {
+                    line: 8,
+                    language: "bash".into(),
+                    code: "echo hi".into()
+                }
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn check_reports_failures() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join("devtogo-samples-test");
+        let sample = Sample {
+            line: 1,
+            language: "bash".into(),
+            code: "echo hi".into(),
+        };
+        assert_eq!(sample.check("sh -n {file}", &dir)?, None);
+        assert_eq!(
+            sample.check("grep -q bye {file} || echo missing bye >&2; exit 1", &dir)?,
+            Some("missing bye".into())
+        );
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
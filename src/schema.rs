@@ -0,0 +1,152 @@
+use anyhow::anyhow;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+use yaml_rust::Yaml;
+
+/// A project's own rules for frontmatter, i.e. an editorial team's publishing policies
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Schema {
+    /// Fields every article must set
+    pub required: Vec<String>,
+    /// Fields articles may set. Others are refused. Defaults to any
+    pub allowed: Vec<String>,
+    /// Regular expressions values must match, by field, i.e. canonical_url =
+    /// "^https://myblog.com/". Each item of a list must match
+    pub patterns: BTreeMap<String, String>,
+}
+
+/// Schema rules ready to check articles with
+#[derive(Debug)]
+pub struct Rules<'a> {
+    schema: &'a Schema,
+    patterns: Vec<(&'a str, Regex)>,
+}
+
+/// A frontmatter field breaking a rule
+#[derive(Debug, PartialEq)]
+pub struct Violation {
+    pub field: String,
+    message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// a scalar yaml value as text
+fn text(value: &Yaml) -> Option<String> {
+    match value {
+        Yaml::String(value) | Yaml::Real(value) => Some(value.clone()),
+        Yaml::Integer(value) => Some(value.to_string()),
+        Yaml::Boolean(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+impl<'a> Rules<'a> {
+    pub fn new(schema: &'a Schema) -> anyhow::Result<Rules<'a>> {
+        let patterns = schema
+            .patterns
+            .iter()
+            .map(|(field, pattern)| {
+                Regex::new(pattern)
+                    .map(|regex| (field.as_str(), regex))
+                    .map_err(|err| anyhow!("pattern for {} is invalid: {}", field, err))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Rules { schema, patterns })
+    }
+
+    /// fields of an article's frontmatter breaking rules
+    pub fn check(
+        &self,
+        content: &str,
+    ) -> Vec<Violation> {
+        let hash = match frontmatter::parse_and_find_content(content) {
+            Ok((Some(Yaml::Hash(hash)), _)) => hash,
+            _ => return Vec::new(),
+        };
+        let value = |field: &str| {
+            hash.get(&Yaml::String(field.into()))
+                .filter(|value| !value.is_null())
+        };
+        let mut violations = Vec::new();
+        for field in &self.schema.required {
+            if value(field).is_none() {
+                violations.push(Violation {
+                    field: field.clone(),
+                    message: format!("{} is required", field),
+                });
+            }
+        }
+        if !self.schema.allowed.is_empty() {
+            for field in hash.keys().filter_map(Yaml::as_str) {
+                if field != "title" && !self.schema.allowed.iter().any(|allowed| allowed == field) {
+                    violations.push(Violation {
+                        field: field.into(),
+                        message: format!("{} is not an allowed field", field),
+                    });
+                }
+            }
+        }
+        for (field, regex) in &self.patterns {
+            let values = match value(field) {
+                Some(Yaml::Array(values)) => values.iter().filter_map(text).collect(),
+                Some(value) => text(value).into_iter().collect(),
+                None => Vec::new(),
+            };
+            for value in values {
+                if !regex.is_match(&value) {
+                    violations.push(Violation {
+                        field: field.to_string(),
+                        message: format!("{} {} doesn't match {}", field, value, regex),
+                    });
+                }
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_check_required_allowed_and_patterns() -> anyhow::Result<()> {
+        let schema: Schema = toml::from_str(
+            r#"
+            required = ["description"]
+            allowed = ["description", "tags", "canonical_url"]
+            patterns = { canonical_url = "^https://myblog.com/", tags = "^[a-z]+$" }
+            "#,
+        )?;
+        let rules = Rules::new(&schema)?;
+        let violations = rules.check(
+            "---\ntitle: foo\ncanonical_url: http://elsewhere.com/foo\ntags: [rust, CLI]\nseries: bar\n---\n",
+        );
+        assert_eq!(
+            violations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "description is required",
+                "series is not an allowed field",
+                "canonical_url http://elsewhere.com/foo doesn't match ^https://myblog.com/",
+                "tags CLI doesn't match ^[a-z]+$",
+            ]
+        );
+        assert!(rules
+            .check("---\ntitle: foo\ndescription: hi\ncanonical_url: https://myblog.com/foo\n---\n")
+            .is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{
+    api::{Article, Client},
+    article::{self, PublishStatus},
+    config::{Config, Kind, Target},
+    oauth,
+    state::{self, State, DIR},
+    vault,
+};
+use anyhow::bail;
+use colored::Colorize;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+/// Name of the directory, within a source directory's .devtogo directory, remote articles are
+/// cached in, a file per target
+const CACHE: &str = "remote";
+
+/// Finds where you wrote about something, across local files and your articles on configured
+/// targets
+///
+/// Remote articles are searched as cached by the last sync, or by --refresh
+#[derive(StructOpt, Debug)]
+pub struct Search {
+    /// Text to find, ignoring case
+    query: String,
+    /// Directory to search markdown files in. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Lines to print before and after each matching line
+    #[structopt(short = "C", long, default_value = "1")]
+    context: usize,
+    /// Fetch articles from Forem targets before searching, rather than searching those cached
+    #[structopt(long)]
+    refresh: bool,
+}
+
+/// A remote article as cached for searching
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Cached {
+    pub id: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub url: String,
+    pub published: bool,
+    pub body: String,
+}
+
+fn path(
+    source: &Path,
+    target: &str,
+) -> PathBuf {
+    source
+        .join(DIR)
+        .join(CACHE)
+        .join(format!("{}.json", target))
+}
+
+/// keep a target's articles for searching
+pub fn cache(
+    source: &Path,
+    target: &str,
+    articles: &[Article],
+) -> anyhow::Result<()> {
+    let cached = articles
+        .iter()
+        .map(|article| Cached {
+            id: article.id.clone(),
+            title: article.title.clone(),
+            tags: article.tag_list.clone(),
+            url: article.url.clone(),
+            published: article.published,
+            body: article.body_markdown.clone(),
+        })
+        .collect::<Vec<_>>();
+    let path = path(source, target);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // drafts are cached too, so they're encrypted along with the rest of state
+    vault::write(&path, &serde_json::to_string(&cached)?)
+}
+
+/// a target's articles as last cached
+pub fn cached(
+    source: &Path,
+    target: &str,
+) -> anyhow::Result<Vec<Cached>> {
+    let path = path(source, target);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_str(&vault::read(&path)?)?)
+}
+
+/// configured Forem targets, whose articles are cached
+pub fn targets(config: &Config) -> anyhow::Result<Vec<(String, Target)>> {
+    Ok(config
+        .targets(&[])?
+        .into_iter()
+        .filter(|(_, target)| target.kind == Kind::Forem)
+        .collect())
+}
+
+/// cache the articles of Forem targets afresh
+pub async fn refresh(
+    source: &Path,
+    targets: &[(String, Target)],
+) -> anyhow::Result<()> {
+    for (name, target) in targets {
+        let client = Client::new(target, oauth::auth(name, target).await?)?;
+        cache(source, name, &client.articles().await?)?;
+    }
+    Ok(())
+}
+
+/// lines of text to print for matches of a query, with their line numbers and whether they
+/// match, None separating lines which aren't adjacent
+fn excerpt<'a>(
+    text: &'a str,
+    query: &Regex,
+    context: usize,
+) -> Vec<Option<(usize, &'a str, bool)>> {
+    let lines = text.lines().collect::<Vec<_>>();
+    let mut shown = vec![false; lines.len()];
+    for (n, line) in lines.iter().enumerate() {
+        if query.is_match(line) {
+            let end = (n + context + 1).min(lines.len());
+            shown[n.saturating_sub(context)..end]
+                .iter_mut()
+                .for_each(|shown| *shown = true);
+        }
+    }
+    let mut excerpt = Vec::new();
+    let mut last = None;
+    for (n, line) in lines.iter().enumerate().filter(|(n, _)| shown[*n]) {
+        if last.is_some_and(|last| last + 1 != n) {
+            excerpt.push(None);
+        }
+        excerpt.push(Some((n + 1, *line, query.is_match(line))));
+        last = Some(n);
+    }
+    excerpt
+}
+
+/// print an article matching a query, if it does
+fn print(
+    heading: &str,
+    status: &str,
+    location: &str,
+    text: &str,
+    fields: &[&str],
+    query: &Regex,
+    context: usize,
+) -> bool {
+    let excerpt = excerpt(text, query, context);
+    if excerpt.is_empty() && !fields.iter().any(|field| query.is_match(field)) {
+        return false;
+    }
+    let highlight = |line: &str| {
+        query
+            .replace_all(line, |c: &regex::Captures| c[0].yellow().bold().to_string())
+            .into_owned()
+    };
+    println!(
+        "{} {} {}",
+        highlight(heading).bold(),
+        format!("[{}]", status).dimmed(),
+        location.dimmed()
+    );
+    for line in excerpt {
+        match line {
+            Some((n, line, true)) => println!("{:>5}: {}", n.to_string().green(), highlight(line)),
+            Some((n, line, false)) => println!("{:>5}- {}", n.to_string().dimmed(), line),
+            None => println!("{}", "   --".dimmed()),
+        }
+    }
+    println!();
+    true
+}
+
+pub async fn run(
+    config: &Config,
+    args: Search,
+) -> anyhow::Result<()> {
+    let Search {
+        query,
+        source,
+        context,
+        refresh,
+    } = args;
+    let source = config.source(source);
+    let pattern = RegexBuilder::new(&regex::escape(query.trim()))
+        .case_insensitive(true)
+        .build()?;
+    let targets = targets(config)?;
+    if refresh {
+        self::refresh(&source, &targets).await?;
+    }
+    let mut remote = Vec::new();
+    for (name, _) in &targets {
+        let state = State::load(&source, name)?;
+        remote.push((name.clone(), state, cached(&source, name)?));
+    }
+    let mut found = 0;
+    for path in article::paths(&source, config) {
+        let content = article::read(config, &source, &path)?;
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let meta = match article::extract(file_name.as_ref(), &content) {
+            Ok((meta, _)) => meta,
+            Err(_) => continue,
+        };
+        let key = state::key(&source, &path);
+        // local files link to the articles they were pushed as
+        let url = remote
+            .iter()
+            .filter_map(|(_, state, cached)| {
+                let id = &state.get(&key)?.id;
+                cached.iter().find(|article| &article.id == id)
+            })
+            .map(|article| article.url.as_str())
+            .find(|url| !url.is_empty());
+        let location = match url {
+            Some(url) => format!("{} {}", key, url),
+            None => key.clone(),
+        };
+        let tags = meta.tags.as_deref().unwrap_or_default();
+        if print(
+            &meta.title,
+            match meta.publish_status() {
+                PublishStatus::Published => "published",
+                PublishStatus::Draft => "draft",
+            },
+            &location,
+            article::body(&content),
+            &[tags],
+            &pattern,
+            context,
+        ) {
+            found += 1;
+        }
+    }
+    for (name, state, cached) in &remote {
+        for article in cached {
+            // articles with local files were searched as those
+            if state.key_for(&article.id).is_some() {
+                continue;
+            }
+            let status = if article.published {
+                "published"
+            } else {
+                "draft"
+            };
+            if print(
+                &article.title,
+                status,
+                &format!("{} {}", name, article.url),
+                article::body(&article.body),
+                &[&article.tags.join(", ")],
+                &pattern,
+                context,
+            ) {
+                found += 1;
+            }
+        }
+    }
+    if found == 0 {
+        bail!("nothing mentions {}", query);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excerpt_includes_context() -> anyhow::Result<()> {
+        let query = RegexBuilder::new("tokio").case_insensitive(true).build()?;
+        let text = "a\nTokio channels\nb\nc\nd\ne\nmore tokio";
+        assert_eq!(
+            excerpt(text, &query, 1),
+            vec![
+                Some((1, "a", false)),
+                Some((2, "Tokio channels", true)),
+                Some((3, "b", false)),
+                None,
+                Some((6, "e", false)),
+                Some((7, "more tokio", true)),
+            ]
+        );
+        assert!(excerpt("nothing here", &query, 1).is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+use crate::{config::Config, duration::Duration};
+use anyhow::{anyhow, bail};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use structopt::StructOpt;
+
+/// Marks service definitions written by devtogo so they may be safely replaced
+const MARKER: &str = "installed by devtogo";
+
+/// Manages a background service running devtogo daemon
+#[derive(StructOpt, Debug)]
+pub enum Service {
+    /// Installs and starts a systemd user unit, or a launchd agent on macOS, running devtogo daemon
+    Install(Install),
+}
+
+#[derive(StructOpt, Debug)]
+pub struct Install {
+    /// Name of the service, to tell apart services syncing different directories
+    #[structopt(long, default_value = "devtogo")]
+    name: String,
+    /// Time between syncs, i.e. 15m or 1h
+    #[structopt(long, default_value = "15m")]
+    interval: Duration,
+    /// Directory to sync. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Configured target to sync with. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+    /// Print the service definition rather than installing it
+    #[structopt(long)]
+    print: bool,
+    /// Replace an existing service not installed by devtogo
+    #[structopt(short, long)]
+    force: bool,
+}
+
+/// A service manager devtogo can install services with
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Manager {
+    Systemd,
+    Launchd,
+}
+
+/// Everything a service needs to run devtogo daemon
+#[derive(Debug)]
+struct Unit {
+    name: String,
+    /// devtogo executable and its arguments
+    command: Vec<String>,
+    dir: PathBuf,
+    /// environment variables the daemon needs, i.e. api keys
+    env: Vec<(String, String)>,
+}
+
+/// quote a systemd command line argument or environment assignment
+fn systemd_quote(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('%', "%%")
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Manager {
+    /// the service manager of the current platform
+    fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Manager::Launchd
+        } else {
+            Manager::Systemd
+        }
+    }
+
+    /// where a service definition is installed, within a home directory
+    fn path(
+        self,
+        home: &Path,
+        name: &str,
+    ) -> PathBuf {
+        match self {
+            Manager::Systemd => home
+                .join(".config/systemd/user")
+                .join(format!("{}.service", name)),
+            Manager::Launchd => home
+                .join("Library/LaunchAgents")
+                .join(format!("dev.devtogo.{}.plist", name)),
+        }
+    }
+
+    /// a service definition running a unit
+    fn render(
+        self,
+        unit: &Unit,
+    ) -> String {
+        match self {
+            Manager::Systemd => {
+                let mut out = format!(
+                    "# {}\n[Unit]\nDescription=devtogo daemon ({})\nAfter=network-online.target\n\n[Service]\nWorkingDirectory={}\nExecStart={}\n",
+                    MARKER,
+                    unit.name,
+                    unit.dir.to_string_lossy().replace('%', "%%"),
+                    unit.command
+                        .iter()
+                        .map(|arg| systemd_quote(arg))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                );
+                for (key, value) in &unit.env {
+                    out.push_str(&format!(
+                        "Environment={}\n",
+                        systemd_quote(&format!("{}={}", key, value))
+                    ));
+                }
+                out.push_str(
+                    "Restart=on-failure\nRestartSec=30\n\n[Install]\nWantedBy=default.target\n",
+                );
+                out
+            }
+            Manager::Launchd => {
+                let mut out = format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!-- {} -->\n<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n<plist version=\"1.0\">\n<dict>\n  <key>Label</key>\n  <string>dev.devtogo.{}</string>\n  <key>ProgramArguments</key>\n  <array>\n",
+                    MARKER,
+                    xml_escape(&unit.name)
+                );
+                for arg in &unit.command {
+                    out.push_str(&format!("    <string>{}</string>\n", xml_escape(arg)));
+                }
+                out.push_str(&format!(
+                    "  </array>\n  <key>WorkingDirectory</key>\n  <string>{}</string>\n  <key>EnvironmentVariables</key>\n  <dict>\n",
+                    xml_escape(&unit.dir.to_string_lossy())
+                ));
+                for (key, value) in &unit.env {
+                    out.push_str(&format!(
+                        "    <key>{}</key>\n    <string>{}</string>\n",
+                        xml_escape(key),
+                        xml_escape(value)
+                    ));
+                }
+                out.push_str(&format!(
+                    "  </dict>\n  <key>RunAtLoad</key>\n  <true/>\n  <key>KeepAlive</key>\n  <true/>\n  <key>StandardOutPath</key>\n  <string>{0}/{1}.log</string>\n  <key>StandardErrorPath</key>\n  <string>{0}/{1}.log</string>\n</dict>\n</plist>\n",
+                    xml_escape(&unit.dir.join(crate::state::DIR).to_string_lossy()),
+                    xml_escape(&unit.name)
+                ));
+                out
+            }
+        }
+    }
+
+    /// commands which load and start an installed service
+    fn start(
+        self,
+        path: &Path,
+        name: &str,
+    ) -> Vec<Vec<String>> {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect();
+        match self {
+            Manager::Systemd => vec![
+                args(&["systemctl", "--user", "daemon-reload"]),
+                args(&[
+                    "systemctl",
+                    "--user",
+                    "enable",
+                    "--now",
+                    &format!("{}.service", name),
+                ]),
+            ],
+            Manager::Launchd => vec![
+                args(&["launchctl", "unload", &path.to_string_lossy()]),
+                args(&["launchctl", "load", "-w", &path.to_string_lossy()]),
+            ],
+        }
+    }
+}
+
+/// names of environment variables holding api keys for configured targets and profiles
+fn key_envs(config: &Config) -> anyhow::Result<Vec<String>> {
+    let mut names = config
+        .targets(&[])?
+        .iter()
+        .map(|(_, target)| target.api_key_env().to_string())
+        .chain(
+            config
+                .profiles
+                .values()
+                .map(|profile| profile.api_key_env.clone()),
+        )
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// an absolute path, resolving relative ones against the current directory
+fn absolute(path: &Path) -> anyhow::Result<PathBuf> {
+    Ok(if path.is_absolute() {
+        path.into()
+    } else {
+        env::current_dir()?.join(path)
+    })
+}
+
+pub fn run(
+    config: &Config,
+    config_path: &Path,
+    args: Service,
+) -> anyhow::Result<()> {
+    match args {
+        Service::Install(Install {
+            name,
+            interval,
+            source,
+            target,
+            print,
+            force,
+        }) => {
+            let dir = match source {
+                Some(source) => absolute(&source)?,
+                None => env::current_dir()?,
+            };
+            let mut command = vec![
+                env::current_exe()?.to_string_lossy().into_owned(),
+                "--config".into(),
+                absolute(config_path)?.to_string_lossy().into_owned(),
+                "daemon".into(),
+                "--interval".into(),
+                interval.to_string(),
+            ];
+            if let Some(target) = target {
+                command.extend(["--target".into(), target]);
+            }
+            let mut env = Vec::new();
+            for key in std::iter::once("PATH".to_string()).chain(key_envs(config)?) {
+                if let Ok(value) = env::var(&key) {
+                    env.push((key, value));
+                }
+            }
+            let unit = Unit {
+                name,
+                command,
+                dir,
+                env,
+            };
+            let manager = Manager::current();
+            let definition = manager.render(&unit);
+            if print {
+                print!("{}", definition);
+                return Ok(());
+            }
+            let home = env::var("HOME").map_err(|_| anyhow!("HOME is not set"))?;
+            let path = manager.path(Path::new(&home), &unit.name);
+            if path.exists() && !force && !fs::read_to_string(&path)?.contains(MARKER) {
+                bail!(
+                    "{} already exists.\n  ▶ Re-run with --force to replace it",
+                    path.display()
+                );
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, definition)?;
+            // definitions carry api keys
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+            }
+            println!("Installed {} service at {}", unit.name, path.display());
+            for command in manager.start(&path, &unit.name) {
+                let status = Command::new(&command[0])
+                    .args(&command[1..])
+                    .status()
+                    .map_err(|err| anyhow!("failed to run {}: {}", command[0], err))?;
+                // unloading a launchd agent which wasn't loaded yet fails harmlessly
+                if !status.success() && command.get(1).map(String::as_str) != Some("unload") {
+                    bail!("{} failed with {}", command.join(" "), status);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit() -> Unit {
+        Unit {
+            name: "blog".into(),
+            command: vec![
+                "/usr/bin/devtogo".into(),
+                "daemon".into(),
+                "--interval".into(),
+                "15m".into(),
+            ],
+            dir: "/home/me/my blog".into(),
+            env: vec![("DEVTO_API_KEY".into(), "100%".into())],
+        }
+    }
+
+    #[test]
+    fn render_systemd_unit() {
+        let unit = Manager::Systemd.render(&unit());
+        assert!(unit.starts_with("# installed by devtogo\n[Unit]\n"));
+        assert!(unit.contains("WorkingDirectory=/home/me/my blog\nExecStart=\"/usr/bin/devtogo\" \"daemon\" \"--interval\" \"15m\"\nEnvironment=\"DEVTO_API_KEY=100%%\"\n"));
+        assert_eq!(
+            Manager::Systemd.path(Path::new("/home/me"), "blog"),
+            PathBuf::from("/home/me/.config/systemd/user/blog.service")
+        );
+    }
+
+    #[test]
+    fn render_launchd_plist() {
+        let plist = Manager::Launchd.render(&unit());
+        assert!(plist.contains("<string>dev.devtogo.blog</string>"));
+        assert!(
+            plist.contains("    <string>/usr/bin/devtogo</string>\n    <string>daemon</string>\n")
+        );
+        assert!(plist.contains("    <key>DEVTO_API_KEY</key>\n    <string>100%</string>\n"));
+    }
+}
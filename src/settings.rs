@@ -0,0 +1,304 @@
+use crate::{config::Config, console, oauth};
+use anyhow::{anyhow, bail};
+use colored::Colorize;
+use std::{env, fs, path::Path};
+use structopt::StructOpt;
+use toml_edit::{DocumentMut, Item, Table, TableLike};
+
+/// Shows, checks and changes settings in devtogo.toml
+#[derive(StructOpt, Debug)]
+pub enum Settings {
+    /// Prints every setting in effect and where its value comes from: the config file, a flag,
+    /// the environment, the keychain or a default
+    Show,
+    /// Checks the config file for invalid values and unknown settings, i.e. typos
+    Validate,
+    /// Changes a setting in the config file, i.e. `set targets.devto.api_version 1`, keeping its
+    /// comments and formatting
+    Set {
+        /// Setting to change, with nested settings separated by dots
+        key: String,
+        /// Toml value, i.e. true, 3 or ["rust"]. Values which don't parse are taken as strings
+        value: String,
+    },
+}
+
+/// the value at a dotted key of a toml table, if any
+fn lookup<'a>(
+    table: &'a toml::Value,
+    key: &str,
+) -> Option<&'a toml::Value> {
+    key.split('.')
+        .try_fold(table, |value, part| value.get(part))
+}
+
+/// every leaf setting in a table, by dotted key
+fn entries(
+    prefix: &str,
+    value: &toml::Value,
+    out: &mut Vec<(String, toml::Value)>,
+) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                entries(&key, value, out);
+            }
+        }
+        value => out.push((prefix.into(), value.clone())),
+    }
+}
+
+/// settings in effect, each with a description of where its value comes from
+fn show(
+    config: &Config,
+    file: &toml::Value,
+    file_name: &str,
+) -> anyhow::Result<Vec<(String, String, String)>> {
+    let mut settings = toml::Value::try_from(config)?;
+    // targets are shown as resolved, with defaults for their kind filled in
+    let mut targets = toml::value::Table::new();
+    for (name, target) in config.targets(&[])? {
+        let mut table = match config.targets.get(&name) {
+            Some(configured) => toml::Value::try_from(configured)?,
+            None => toml::Value::Table(toml::value::Table::new()),
+        };
+        if let Some(table) = table.as_table_mut() {
+            table.insert("kind".into(), toml::Value::try_from(target.kind)?);
+            table.insert("base_url".into(), target.base_url().into());
+            table.insert("api_key_env".into(), target.api_key_env().into());
+        }
+        targets.insert(name, table);
+    }
+    if let Some(settings) = settings.as_table_mut() {
+        settings.insert("targets".into(), toml::Value::Table(targets));
+    }
+    let mut out = Vec::new();
+    entries("", &settings, &mut out);
+    let mut shown = out
+        .into_iter()
+        .map(|(key, value)| {
+            let source = match config.overrides.get(&key) {
+                Some(flag) => flag.clone(),
+                None if lookup(file, &key).is_some() => file_name.into(),
+                None => "default".into(),
+            };
+            (key, value.to_string(), source)
+        })
+        .collect::<Vec<_>>();
+    for (name, target) in config.targets(&[])? {
+        let key = format!("targets.{}.api_key", name);
+        let (value, source) = match env::var(target.api_key_env()) {
+            Ok(api_key) => (mask(&api_key), format!("env {}", target.api_key_env())),
+            Err(_) => match oauth::stored_api_key(&target) {
+                Some(api_key) => (mask(&api_key), "keychain".into()),
+                None => (
+                    "none".into(),
+                    format!("env {} is not set", target.api_key_env()),
+                ),
+            },
+        };
+        shown.push((key, value, source));
+    }
+    shown.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(shown)
+}
+
+/// a secret with all but its last few characters hidden
+fn mask(secret: &str) -> String {
+    let len = secret.chars().count();
+    let hidden = len - (len / 4).min(4);
+    format!("\"…{}\"", secret.chars().skip(hidden).collect::<String>())
+}
+
+/// a toml document with a dotted key set to a value, creating tables along the way
+fn set(
+    doc: &mut DocumentMut,
+    key: &str,
+    value: toml_edit::Value,
+) -> anyhow::Result<()> {
+    let mut parts = key.split('.').collect::<Vec<_>>();
+    let last = parts.pop().filter(|last| !last.is_empty());
+    let last = last.ok_or_else(|| anyhow!("{} is not a setting", key))?;
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for part in parts {
+        table = table
+            .entry(part)
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow!("{} is not a table of settings", part))?;
+    }
+    match table.get_mut(last) {
+        Some(Item::Value(existing)) => {
+            let decor = existing.decor().clone();
+            *existing = value;
+            *existing.decor_mut() = decor;
+        }
+        _ => {
+            table.insert(last, Item::Value(value));
+        }
+    }
+    Ok(())
+}
+
+/// a config file with a setting changed, trying the value as toml then as a string, and
+/// refusing values which make the config invalid
+pub fn change(
+    text: &str,
+    key: &str,
+    value: &str,
+) -> anyhow::Result<String> {
+    let mut candidates = Vec::new();
+    if let Ok(value) = value.parse::<toml_edit::Value>() {
+        candidates.push(value);
+    }
+    candidates.push(value.into());
+    let mut error = None;
+    for candidate in candidates {
+        let mut doc = text.parse::<DocumentMut>()?;
+        set(&mut doc, key, candidate)?;
+        let changed = doc.to_string();
+        match Config::parse(&changed) {
+            Ok((_, unknown))
+                if unknown
+                    .iter()
+                    .any(|unknown| key.starts_with(unknown.as_str())) =>
+            {
+                bail!("{} is not a setting", key)
+            }
+            Ok(_) => return Ok(changed),
+            Err(err) => error = error.or(Some(err)),
+        }
+    }
+    Err(error
+        .map(|err| anyhow!("{} can't be set to {}: {}", key, value, err))
+        .unwrap_or_else(|| anyhow!("{} can't be set to {}", key, value)))
+}
+
+pub fn run(
+    config: &Config,
+    config_path: &Path,
+    args: Settings,
+) -> anyhow::Result<()> {
+    let text = if config_path.exists() {
+        fs::read_to_string(config_path)?
+    } else {
+        String::new()
+    };
+    match args {
+        Settings::Show => {
+            let file = toml::from_str(&text)?;
+            let settings = show(config, &file, &config_path.display().to_string())?;
+            let width = settings
+                .iter()
+                .map(|(key, value, _)| key.len() + value.chars().count())
+                .max()
+                .unwrap_or_default();
+            for (key, value, source) in settings {
+                println!(
+                    "{} = {}{}  {}",
+                    key,
+                    console::text(&value),
+                    " ".repeat(width - key.len() - value.chars().count()),
+                    format!("# {}", source).dimmed()
+                );
+            }
+        }
+        Settings::Validate => {
+            if !config_path.exists() {
+                println!("{} doesn't exist, so defaults apply", config_path.display());
+                return Ok(());
+            }
+            let (config, unknown) = Config::parse(&text)
+                .map_err(|err| anyhow!("{} is not valid: {}", config_path.display(), err))?;
+            // unknown settings were already listed when config was loaded
+            if !unknown.is_empty() {
+                bail!(
+                    "{} has {} unknown setting(s)",
+                    config_path.display(),
+                    unknown.len()
+                );
+            }
+            config.targets(&[])?;
+            for root in &config.sources {
+                if !root.dir.is_dir() {
+                    bail!("source directory {} doesn't exist", root.dir.display());
+                }
+                if let Some(profile) = &root.profile {
+                    config.profile(profile)?;
+                }
+            }
+            if let Some(source) = &config.source {
+                if !source.is_dir() {
+                    bail!("source directory {} doesn't exist", source.display());
+                }
+            }
+            println!("{} is valid", config_path.display());
+        }
+        Settings::Set { key, value } => {
+            fs::write(config_path, change(&text, &key, &value)?)?;
+            println!("Set {} in {}", key, config_path.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn show_describes_sources() -> anyhow::Result<()> {
+        let text = "smart_typography = true\n[targets.devto]\napi_version = \"0\"\n";
+        let (mut config, _) = Config::parse(text)?;
+        config.set_api_version("1".into());
+        let settings = show(&config, &toml::from_str(text)?, "devtogo.toml")?;
+        let setting = |key: &str| {
+            settings
+                .iter()
+                .find(|(k, _, _)| k == key)
+                .map(|(_, value, source)| (value.as_str(), source.as_str()))
+        };
+        assert_eq!(setting("smart_typography"), Some(("true", "devtogo.toml")));
+        assert_eq!(setting("convert_footnotes"), Some(("false", "default")));
+        assert_eq!(
+            setting("targets.devto.api_version"),
+            Some(("\"1\"", "--api-version"))
+        );
+        assert_eq!(
+            setting("targets.devto.base_url"),
+            Some(("\"https://dev.to\"", "default"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn mask_hides_secrets() {
+        assert_eq!(mask("som3R@ndOmAp1K3y"), "\"…1K3y\"");
+        assert_eq!(mask("abc"), "\"…\"");
+    }
+
+    #[test]
+    fn change_keeps_comments() -> anyhow::Result<()> {
+        let text = "# my blog\nsmart_typography = false\n";
+        assert_eq!(
+            change(text, "smart_typography", "true")?,
+            "# my blog\nsmart_typography = true\n"
+        );
+        assert_eq!(
+            change(text, "targets.devto.api_version", "1")?,
+            "# my blog\nsmart_typography = false\n\n[targets.devto]\napi_version = \"1\"\n"
+        );
+        assert!(change(text, "smart_typograhpy", "true").is_err());
+        assert!(change(text, "smart_typography", "sure").is_err());
+        Ok(())
+    }
+}
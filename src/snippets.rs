@@ -0,0 +1,334 @@
+use crate::{api, recording};
+use anyhow::{anyhow, bail};
+use regex::{Captures, Regex};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Name of the directory, relative to a source directory, shared snippets are kept in.
+/// Files here are never published on their own
+pub const DIR: &str = "snippets";
+
+/// replace `{{< include "snippets/bio.md" >}}` directives with the content of the file
+/// they name, relative to the source directory. Directives in fenced code are left as is
+pub fn expand(
+    source: &Path,
+    content: &str,
+) -> anyhow::Result<String> {
+    let directive = Regex::new(r#"\{\{<\s*include\s+"([^"]+)"\s*>\}\}"#)?;
+    replace(content, &directive, |captures| {
+        let name = &captures[1];
+        let path = Path::new(name);
+        if !path.starts_with(DIR) || path.components().any(|c| c == Component::ParentDir) {
+            bail!(
+                "can not include {}.\n  ▶ Included files must be within the {} directory",
+                name,
+                DIR
+            );
+        }
+        fs::read_to_string(source.join(path))
+            .map_err(|err| anyhow!("can not include {}: {}", name, err))
+    })
+}
+
+/// the captures of directives outside of fenced code
+fn directives<'a>(
+    content: &'a str,
+    directive: &Regex,
+) -> Vec<Captures<'a>> {
+    let mut found = Vec::new();
+    let mut fenced = false;
+    for line in content.split('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        }
+        if !fenced {
+            found.extend(directive.captures_iter(line));
+        }
+    }
+    found
+}
+
+/// replace directives outside of fenced code with what `with` makes of them
+fn replace(
+    content: &str,
+    directive: &Regex,
+    mut with: impl FnMut(&Captures) -> anyhow::Result<String>,
+) -> anyhow::Result<String> {
+    if !directive.is_match(content) {
+        return Ok(content.to_string());
+    }
+    let mut expanded = Vec::new();
+    let mut fenced = false;
+    for line in content.split('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+        }
+        if fenced {
+            expanded.push(line.to_string());
+            continue;
+        }
+        let mut out = String::new();
+        let mut last = 0;
+        for captures in directive.captures_iter(line) {
+            if let Some(whole) = captures.get(0) {
+                out.push_str(&line[last..whole.start()]);
+                out.push_str(with(&captures)?.trim_end_matches('\n'));
+                last = whole.end();
+            }
+        }
+        out.push_str(&line[last..]);
+        expanded.push(out);
+    }
+    Ok(expanded.join("\n"))
+}
+
+/// the raw file a GitHub permalink, i.e. https://github.com/o/r/blob/<sha>/src/main.rs#L10-L30,
+/// points to, and the lines it highlights
+fn permalink(url: &str) -> Option<(String, Option<(usize, usize)>)> {
+    let (url, anchor) = url.split_once('#').unwrap_or((url, ""));
+    let path = url.strip_prefix("https://github.com/")?;
+    let (repo, file) = path.split_once("/blob/")?;
+    let lines = anchor.strip_prefix('L').and_then(|anchor| {
+        let (start, end) = anchor.split_once("-L").unwrap_or((anchor, anchor));
+        Some((start.parse().ok()?, end.parse().ok()?))
+    });
+    Some((
+        format!("https://raw.githubusercontent.com/{}/{}", repo, file),
+        lines,
+    ))
+}
+
+/// code fence language for a file, by its extension
+fn language(name: &str) -> &str {
+    let name = name.split(['?', '#']).next().unwrap_or_default();
+    let extension = match name.rsplit_once('.') {
+        Some((_, extension)) if !extension.contains('/') => extension,
+        _ => return "",
+    };
+    match extension {
+        "rs" => "rust",
+        "py" => "python",
+        "js" | "mjs" => "javascript",
+        "ts" => "typescript",
+        "rb" => "ruby",
+        "sh" => "bash",
+        "yml" => "yaml",
+        "md" => "markdown",
+        other => other,
+    }
+}
+
+/// 1-based, inclusive `lines` of a file, without the indentation they share, in a code fence
+fn fence(
+    name: &str,
+    text: &str,
+    lines: Option<(usize, usize)>,
+) -> anyhow::Result<String> {
+    let all = text.lines().collect::<Vec<_>>();
+    let selected = match lines {
+        Some((start, end)) if start == 0 || start > end || end > all.len() => bail!(
+            "can not include lines {}..{} of {}, which has {} lines",
+            start,
+            end,
+            name,
+            all.len()
+        ),
+        Some((start, end)) => &all[start - 1..end],
+        None => &all[..],
+    };
+    let indent = selected
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    let code = selected
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+    // longer than any run of backticks in the code, so fences within it, i.e. in a readme,
+    // don't close it
+    let longest = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest.max(2) + 1);
+    Ok(format!("{}{}\n{}\n{}", fence, language(name), code, fence))
+}
+
+/// where a snippet named relative to the article at `path` is, as long as that's within the
+/// source directory. Anything else, like ~/.aws/credentials, could end up published
+fn confined(
+    source: &Path,
+    path: &Path,
+    name: &str,
+) -> anyhow::Result<PathBuf> {
+    let outside = || {
+        anyhow!(
+            "can not include snippet {}.\n  ▶ Snippets must be within the source directory",
+            name
+        )
+    };
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let within = dir.strip_prefix(source).map_err(|_| outside())?;
+    let mut parts = Vec::new();
+    for component in within.components().chain(Path::new(name).components()) {
+        match component {
+            Component::Normal(part) => parts.push(part),
+            Component::CurDir => (),
+            Component::ParentDir if parts.pop().is_some() => (),
+            _ => return Err(outside()),
+        }
+    }
+    Ok(parts
+        .into_iter()
+        .fold(source.to_path_buf(), |path, part| path.join(part)))
+}
+
+/// replace `{{ snippet "examples/demo.rs" lines=10..30 }}` directives with a code fence of the
+/// file they name, relative to the article at `path` and within `source`, or a GitHub
+/// permalink. Lines are 1-based and inclusive, defaulting to those a permalink highlights or
+/// else the whole file
+pub async fn code(
+    source: &Path,
+    path: &Path,
+    content: &str,
+) -> anyhow::Result<String> {
+    let directive = Regex::new(r#"\{\{\s*snippet\s+"([^"]+)"(?:\s+lines=(\d+)\.\.(\d+))?\s*\}\}"#)?;
+    let mut fetched = HashMap::new();
+    for captures in directives(content, &directive) {
+        let name = &captures[1];
+        if (name.starts_with("https://") || name.starts_with("http://"))
+            && !fetched.contains_key(name)
+        {
+            let raw = permalink(name).map(|(raw, _)| raw);
+            let text = recording::send(api::http()?.get(raw.as_deref().unwrap_or(name)))
+                .await?
+                .error_for_status()
+                .map_err(|err| anyhow!("can not include snippet {}: {}", name, err))?
+                .text()
+                .await?;
+            fetched.insert(name.to_string(), text);
+        }
+    }
+    replace(content, &directive, |captures| {
+        let name = &captures[1];
+        let lines = match (captures.get(2), captures.get(3)) {
+            (Some(start), Some(end)) => Some((start.as_str().parse()?, end.as_str().parse()?)),
+            _ => permalink(name).and_then(|(_, lines)| lines),
+        };
+        let text = match fetched.get(name) {
+            Some(text) => text.clone(),
+            None => fs::read_to_string(confined(source, path, name)?)
+                .map_err(|err| anyhow!("can not include snippet {}: {}", name, err))?,
+        };
+        fence(name, &text, lines)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_expands_directives() -> anyhow::Result<()> {
+        let directive = Regex::new(r#"\{\{<\s*include\s+"([^"]+)"\s*>\}\}"#)?;
+        let content = "# hi\n\n{{< include \"snippets/bio.md\" >}}\n\n```\n{{< include \"snippets/bio.md\" >}}\n```\n";
+        assert_eq!(
+            replace(content, &directive, |captures| Ok(format!(
+                "written by {}\n",
+                &captures[1]
+            )))?,
+            "# hi\n\nwritten by snippets/bio.md\n\n```\n{{< include \"snippets/bio.md\" >}}\n```\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn expand_stays_within_snippets() {
+        let source = Path::new(".");
+        assert!(expand(source, "{{< include \"../secrets.md\" >}}").is_err());
+        assert!(expand(source, "{{< include \"snippets/../../secrets.md\" >}}").is_err());
+        assert!(expand(source, "{{< include \"snippets/missing.md\" >}}").is_err());
+    }
+
+    #[test]
+    fn permalink_points_to_raw_files() {
+        assert_eq!(
+            permalink("https://github.com/o/r/blob/abc123/src/main.rs#L10-L30"),
+            Some((
+                "https://raw.githubusercontent.com/o/r/abc123/src/main.rs".into(),
+                Some((10, 30))
+            ))
+        );
+        assert_eq!(
+            permalink("https://github.com/o/r/blob/main/a.py#L4"),
+            Some((
+                "https://raw.githubusercontent.com/o/r/main/a.py".into(),
+                Some((4, 4))
+            ))
+        );
+        assert_eq!(permalink("https://example.com/a.rs"), None);
+    }
+
+    #[tokio::test]
+    async fn code_fences_lines_of_files() -> anyhow::Result<()> {
+        let content = "{{ snippet \"foo.md\" lines=2..3 }}\n```\n{{ snippet \"missing.rs\" }}\n```";
+        assert_eq!(
+            code(Path::new("tests"), Path::new("tests/data/post.md"), content).await?,
+            format!(
+                "```markdown\n{}\n```\n```\n{{{{ snippet \"missing.rs\" }}}}\n```",
+                fs::read_to_string("tests/data/foo.md")?
+                    .lines()
+                    .skip(1)
+                    .take(2)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        );
+        assert!(code(
+            Path::new("tests"),
+            Path::new("tests/data/post.md"),
+            "{{ snippet \"foo.md\" lines=2..99 }}"
+        )
+        .await
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn snippets_stay_within_the_source() -> anyhow::Result<()> {
+        let (source, path) = (Path::new("site"), Path::new("site/posts/post.md"));
+        assert_eq!(
+            confined(source, path, "../examples/demo.rs")?,
+            Path::new("site/examples/demo.rs")
+        );
+        assert_eq!(
+            confined(source, path, "./demo.rs")?,
+            Path::new("site/posts/demo.rs")
+        );
+        assert!(confined(source, path, "../../.aws/credentials").is_err());
+        assert!(confined(source, path, "/etc/passwd").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn fence_outlasts_fences_in_code() -> anyhow::Result<()> {
+        assert_eq!(
+            fence("a.rs", "let a = 1;", None)?,
+            "```rust\nlet a = 1;\n```"
+        );
+        assert_eq!(
+            fence("README.md", "# a\n```sh\nls\n```", None)?,
+            "````markdown\n# a\n```sh\nls\n```\n````"
+        );
+        Ok(())
+    }
+}
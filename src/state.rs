@@ -0,0 +1,310 @@
+use crate::vault;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Name of the directory, relative to a source directory, devtogo keeps its bookkeeping in
+pub const DIR: &str = ".devtogo";
+
+/// What devtogo knew about each local file the last time it was in sync with dev.to
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    articles: BTreeMap<String, Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// remote article id
+    #[serde(deserialize_with = "crate::api::id")]
+    pub id: String,
+    /// hash of the local file content when last in sync
+    pub local: String,
+    /// hash of the remote article body when last in sync
+    pub remote: String,
+    pub synced_at: DateTime<Utc>,
+}
+
+impl Entry {
+    pub fn new(
+        id: &str,
+        local: &str,
+        remote: &str,
+    ) -> Self {
+        Entry {
+            id: id.into(),
+            local: hash(local),
+            remote: hash(remote),
+            synced_at: Utc::now(),
+        }
+    }
+}
+
+impl State {
+    /// load state for a source directory and target, starting fresh if none was recorded yet
+    pub fn load(
+        source: &Path,
+        target: &str,
+    ) -> anyhow::Result<State> {
+        let path = source
+            .join(DIR)
+            .join("state")
+            .join(format!("{}.json", target));
+        let mut state: State = if path.exists() {
+            serde_json::from_str(&vault::read(&path)?)?
+        } else {
+            State::default()
+        };
+        state.path = path;
+        Ok(state)
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        vault::write(&self.path, &serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn get(
+        &self,
+        key: &str,
+    ) -> Option<&Entry> {
+        self.articles.get(key)
+    }
+
+    /// the local file key last synced with a given remote article id
+    pub fn key_for(
+        &self,
+        id: &str,
+    ) -> Option<&str> {
+        self.articles
+            .iter()
+            .find(|(_, entry)| entry.id == id)
+            .map(|(key, _)| key.as_str())
+    }
+
+    pub fn record(
+        &mut self,
+        key: String,
+        entry: Entry,
+    ) {
+        self.articles.insert(key, entry);
+    }
+}
+
+/// Files a push stopped short of finishing had already processed, so a resumed push can skip them
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Progress {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    pub done: BTreeSet<String>,
+}
+
+impl Progress {
+    /// load progress for a source directory and target, starting fresh if none was recorded
+    pub fn load(
+        source: &Path,
+        target: &str,
+    ) -> anyhow::Result<Progress> {
+        let path = source
+            .join(DIR)
+            .join("progress")
+            .join(format!("{}.json", target));
+        let mut progress: Progress = if path.exists() {
+            serde_json::from_str(&vault::read(&path)?)?
+        } else {
+            Progress::default()
+        };
+        progress.path = path;
+        Ok(progress)
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        vault::write(&self.path, &serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// forget progress once a push finishes
+    pub fn clear(&self) -> anyhow::Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Files which failed to push to a target and why, so `retry` can push just those again
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Retries {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    pub failed: BTreeMap<String, String>,
+}
+
+impl Retries {
+    /// load the files queued for retrying for a source directory and target, if any
+    pub fn load(
+        source: &Path,
+        target: &str,
+    ) -> anyhow::Result<Retries> {
+        let path = source
+            .join(DIR)
+            .join("retry")
+            .join(format!("{}.json", target));
+        let mut retries: Retries = if path.exists() {
+            serde_json::from_str(&vault::read(&path)?)?
+        } else {
+            Retries::default()
+        };
+        retries.path = path;
+        Ok(retries)
+    }
+
+    /// save the queue, removing it once no files are left to retry
+    pub fn save(&self) -> anyhow::Result<()> {
+        if self.failed.is_empty() {
+            if self.path.exists() {
+                fs::remove_file(&self.path)?;
+            }
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        vault::write(&self.path, &serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Drafts `release` publishes one at a time, in order of when they are due
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Schedule {
+    #[serde(skip)]
+    path: PathBuf,
+    #[serde(default)]
+    pub releases: Vec<Release>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Release {
+    /// file key within the source directory
+    pub file: String,
+    pub due: DateTime<Utc>,
+}
+
+impl Schedule {
+    /// load the schedule for a source directory, starting fresh if none was recorded
+    pub fn load(source: &Path) -> anyhow::Result<Schedule> {
+        let path = source.join(DIR).join("schedule.json");
+        let mut schedule: Schedule = if path.exists() {
+            serde_json::from_str(&vault::read(&path)?)?
+        } else {
+            Schedule::default()
+        };
+        schedule.path = path;
+        Ok(schedule)
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        vault::write(&self.path, &serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// a file's path relative to its source directory, separated by / on every platform. Roots,
+/// drive letters and UNC prefixes of paths outside the source directory are left out
+pub fn key(
+    source: &Path,
+    path: &Path,
+) -> String {
+    path.strip_prefix(source)
+        .unwrap_or(path)
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            Component::ParentDir => Some("..".into()),
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub fn hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_is_relative_to_source() {
+        assert_eq!(
+            key(Path::new("."), Path::new("./posts/foo.md")),
+            "posts/foo.md"
+        );
+    }
+
+    #[test]
+    fn key_leaves_out_roots() {
+        assert_eq!(
+            key(Path::new("posts"), Path::new("/tmp/foo.md")),
+            "tmp/foo.md"
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn key_handles_windows_paths() {
+        assert_eq!(
+            key(Path::new(r"C:\blog"), Path::new(r"C:\blog\posts\foo.md")),
+            "posts/foo.md"
+        );
+        assert_eq!(
+            key(Path::new(r"C:\blog"), Path::new(r"D:\drafts\foo.md")),
+            "drafts/foo.md"
+        );
+        assert_eq!(
+            key(
+                Path::new(r"\\server\share\blog"),
+                Path::new(r"\\server\share\blog\foo.md")
+            ),
+            "foo.md"
+        );
+        assert_eq!(
+            key(Path::new("."), Path::new(r"\\?\C:\blog\foo.md")),
+            "blog/foo.md"
+        );
+    }
+
+    #[test]
+    fn hash_is_stable() {
+        assert_eq!(hash("foo"), hash("foo"));
+        assert_ne!(hash("foo"), hash("bar"));
+    }
+
+    #[test]
+    fn key_for_finds_recorded_ids() {
+        let mut state = State::default();
+        state.record("foo.md".into(), Entry::new("1", "a", "a"));
+        assert_eq!(state.key_for("1"), Some("foo.md"));
+        assert_eq!(state.key_for("2"), None);
+    }
+}
@@ -0,0 +1,327 @@
+use crate::{
+    api::{Article, Client},
+    config::{Config, Kind},
+    console, oauth, state,
+};
+use anyhow::bail;
+use chrono::{DateTime, Local, Utc};
+use colored::Colorize;
+use rusqlite::{params, Connection};
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// Name of the database, within a source directory's .devtogo directory, snapshots are kept in
+pub const DATABASE: &str = "stats.sqlite";
+
+/// Bars sparklines are drawn with, lowest first
+const BARS: &[char] = &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Shows views, reactions and comments for your published articles
+///
+/// Pass --record to keep a snapshot of them, i.e. from a scheduled CI job, and --history to
+/// chart how an article grew across recorded snapshots
+#[derive(StructOpt, Debug)]
+pub struct Stats {
+    /// Directory whose .devtogo directory snapshots are kept in. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Configured target to show. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+    /// Record a snapshot of each article's metrics
+    #[structopt(long)]
+    record: bool,
+    /// Chart recorded metrics of the article with this slug rather than fetching current ones
+    #[structopt(long, value_name = "slug")]
+    history: Option<String>,
+}
+
+/// An article's metrics at a point in time
+#[derive(Debug, PartialEq)]
+pub struct Snapshot {
+    pub recorded_at: DateTime<Utc>,
+    pub title: String,
+    pub views: i64,
+    pub reactions: i64,
+    pub comments: i64,
+}
+
+pub fn open(path: &std::path::Path) -> anyhow::Result<Connection> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let db = Connection::open(path)?;
+    migrate(&db)?;
+    Ok(db)
+}
+
+fn migrate(db: &Connection) -> anyhow::Result<()> {
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS snapshots (
+            recorded_at TEXT NOT NULL,
+            target TEXT NOT NULL,
+            article_id TEXT NOT NULL,
+            slug TEXT NOT NULL,
+            title TEXT NOT NULL,
+            views INTEGER NOT NULL,
+            reactions INTEGER NOT NULL,
+            comments INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS snapshots_slug ON snapshots (slug, recorded_at);",
+    )?;
+    Ok(())
+}
+
+fn record(
+    db: &mut Connection,
+    target: &str,
+    articles: &[Article],
+    at: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let tx = db.transaction()?;
+    for article in articles {
+        tx.execute(
+            "INSERT INTO snapshots (recorded_at, target, article_id, slug, title, views, reactions, comments)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                at.to_rfc3339(),
+                target,
+                article.id,
+                article.slug,
+                article.title,
+                article.page_views_count,
+                article.public_reactions_count,
+                article.comments_count
+            ],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// snapshots from rows of recorded_at, title, views, reactions and comments
+fn snapshots(
+    statement: &mut rusqlite::Statement<'_>,
+    params: impl rusqlite::Params,
+) -> anyhow::Result<Vec<Snapshot>> {
+    let snapshots = statement
+        .query_map(params, |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })?
+        .map(|row| {
+            let (recorded_at, title, views, reactions, comments) = row?;
+            Ok(Snapshot {
+                recorded_at: DateTime::parse_from_rfc3339(&recorded_at)?.with_timezone(&Utc),
+                title,
+                views,
+                reactions,
+                comments,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(snapshots)
+}
+
+/// recorded snapshots of an article, oldest first
+fn history(
+    db: &Connection,
+    target: &str,
+    slug: &str,
+) -> anyhow::Result<Vec<Snapshot>> {
+    let mut statement = db.prepare(
+        "SELECT recorded_at, title, views, reactions, comments FROM snapshots
+         WHERE target = ?1 AND slug = ?2 ORDER BY recorded_at",
+    )?;
+    snapshots(&mut statement, params![target, slug])
+}
+
+/// the snapshot of an article to compare its metrics since a point in time with: the last
+/// recorded by then, or else the first recorded after
+pub fn baseline(
+    db: &Connection,
+    target: &str,
+    article_id: &str,
+    since: DateTime<Utc>,
+) -> anyhow::Result<Option<Snapshot>> {
+    let mut statement = db.prepare(
+        "SELECT recorded_at, title, views, reactions, comments FROM snapshots
+         WHERE target = ?1 AND article_id = ?2
+         ORDER BY recorded_at <= ?3 DESC,
+             CASE WHEN recorded_at <= ?3 THEN recorded_at END DESC,
+             recorded_at
+         LIMIT 1",
+    )?;
+    Ok(snapshots(
+        &mut statement,
+        params![target, article_id, since.to_rfc3339()],
+    )?
+    .into_iter()
+    .next())
+}
+
+/// values drawn as bars scaled between the lowest and highest of them
+fn sparkline(values: &[i64]) -> String {
+    let min = values.iter().copied().min().unwrap_or_default();
+    let max = values.iter().copied().max().unwrap_or_default();
+    values
+        .iter()
+        .map(|value| {
+            let level = if max == min {
+                0
+            } else {
+                ((value - min) * (BARS.len() as i64 - 1) / (max - min)) as usize
+            };
+            BARS[level]
+        })
+        .collect()
+}
+
+fn chart(
+    slug: &str,
+    snapshots: &[Snapshot],
+) {
+    let (first, last) = match (snapshots.first(), snapshots.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return,
+    };
+    println!(
+        "{} {}",
+        last.title.bold(),
+        format!(
+            "({} snapshots since {})",
+            snapshots.len(),
+            first.recorded_at.with_timezone(&Local).format("%Y-%m-%d")
+        )
+        .dimmed()
+    );
+    let values = |metric: fn(&Snapshot) -> i64| snapshots.iter().map(metric).collect::<Vec<_>>();
+    for (name, values) in [
+        ("views", values(|s| s.views)),
+        ("reactions", values(|s| s.reactions)),
+        ("comments", values(|s| s.comments)),
+    ] {
+        let latest = values.last().copied().unwrap_or_default();
+        println!(
+            "{:<10} {} {} {}",
+            name,
+            console::text(&sparkline(&values)),
+            latest,
+            format!("(+{})", latest - values[0]).green()
+        );
+    }
+    if snapshots.len() < 2 {
+        println!(
+            "{}",
+            format!("Record more snapshots of {} to see it grow", slug).dimmed()
+        );
+    }
+}
+
+pub async fn run(
+    config: &Config,
+    args: Stats,
+) -> anyhow::Result<()> {
+    let Stats {
+        source,
+        target,
+        record: recording,
+        history: slug,
+    } = args;
+    let (name, target) = config.target(target.as_deref())?;
+    let path = config.source(source).join(state::DIR).join(DATABASE);
+    if let Some(slug) = slug {
+        let snapshots = history(&open(&path)?, &name, &slug)?;
+        if snapshots.is_empty() {
+            bail!(
+                "no snapshots of {} were recorded.\n  ▶ Record them with stats --record",
+                slug
+            );
+        }
+        chart(&slug, &snapshots);
+        return Ok(());
+    }
+    if target.kind != Kind::Forem {
+        bail!("stats are only supported with Forem targets");
+    }
+    let client = Client::new(&target, oauth::auth(&name, &target).await?)?;
+    let mut articles = client
+        .articles()
+        .await?
+        .into_iter()
+        .filter(|article| article.published)
+        .collect::<Vec<_>>();
+    articles.sort_by_key(|article| -article.page_views_count);
+    println!(
+        "{:>8} {:>10} {:>9}  {}",
+        "views".bold(),
+        "reactions".bold(),
+        "comments".bold(),
+        "article".bold()
+    );
+    for article in &articles {
+        println!(
+            "{:>8} {:>10} {:>9}  {} {}",
+            article.page_views_count,
+            article.public_reactions_count,
+            article.comments_count,
+            article.title,
+            article.slug.dimmed()
+        );
+    }
+    if recording {
+        record(&mut open(&path)?, &name, &articles, Utc::now())?;
+        println!("Recorded a snapshot of {} article(s)", articles.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparkline_scales_values() {
+        assert_eq!(sparkline(&[0, 7, 14]), "▁▄█");
+        assert_eq!(sparkline(&[3, 3]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn history_returns_recorded_snapshots() -> anyhow::Result<()> {
+        let mut db = Connection::open_in_memory()?;
+        migrate(&db)?;
+        let article = |views| Article {
+            id: "1".into(),
+            slug: "foo-1".into(),
+            title: "foo".into(),
+            page_views_count: views,
+            ..Article::default()
+        };
+        let start = Utc::now();
+        record(&mut db, "devto", &[article(10)], start)?;
+        record(
+            &mut db,
+            "devto",
+            &[article(25)],
+            start + chrono::Duration::days(1),
+        )?;
+        record(&mut db, "other", &[article(99)], start)?;
+        let snapshots = history(&db, "devto", "foo-1")?;
+        assert_eq!(
+            snapshots.iter().map(|s| s.views).collect::<Vec<_>>(),
+            vec![10, 25]
+        );
+        assert!(history(&db, "devto", "bar")?.is_empty());
+        let baseline = |days| baseline(&db, "devto", "1", start + chrono::Duration::days(days));
+        assert_eq!(baseline(7)?.map(|s| s.views), Some(25));
+        assert_eq!(baseline(0)?.map(|s| s.views), Some(10));
+        assert_eq!(baseline(-1)?.map(|s| s.views), Some(10));
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    api::{self, Article, Client},
+    comments,
+    config::{Config, Kind},
+    oauth,
+};
+use anyhow::bail;
+use chrono::Local;
+use serde_json::Value;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// Followers listed by name in a takeout's index, most recent first
+const RECENT_FOLLOWERS: usize = 10;
+
+/// Backs up your data: your profile, articles, comments you made on them, reading list and
+/// followers, as JSON and markdown in a dated directory
+#[derive(StructOpt, Debug)]
+pub struct Takeout {
+    /// Directory to create the dated directory in
+    #[structopt(short, long, default_value = "takeout")]
+    out: PathBuf,
+    /// Configured target to back up. Required when more than one is configured
+    #[structopt(short, long)]
+    target: Option<String>,
+}
+
+/// a string field of a json object, empty when missing
+fn field<'a>(
+    value: &'a Value,
+    name: &str,
+) -> &'a str {
+    value[name].as_str().unwrap_or_default()
+}
+
+/// comments and their replies by a user, each with the url of the article it's on
+fn mine(
+    comments: Vec<Value>,
+    username: &str,
+    article_url: &str,
+) -> Vec<Value> {
+    let mut found = Vec::new();
+    let mut pending = comments;
+    while let Some(mut comment) = pending.pop() {
+        if let Some(children) = comment.get_mut("children").map(Value::take) {
+            pending.extend(serde_json::from_value::<Vec<Value>>(children).unwrap_or_default());
+        }
+        if comment["user"]["username"].as_str() == Some(username) {
+            comment["article_url"] = Value::from(article_url);
+            found.push(comment);
+        }
+    }
+    found.sort_by(|a, b| field(a, "created_at").cmp(field(b, "created_at")));
+    found
+}
+
+/// the index of a takeout, summarizing what it holds
+fn index(
+    profile: &Value,
+    articles: &[Article],
+    comments: &[Value],
+    reading_list: &[Value],
+    followers: &[Value],
+) -> String {
+    let mut out = vec![
+        format!(
+            "# {} ({})\n",
+            field(profile, "name"),
+            field(profile, "username")
+        ),
+        format!(
+            "Taken out {}: {} article(s), {} comment(s), {} reading list item(s) and {} follower(s).",
+            Local::now().format("%Y-%m-%d"),
+            articles.len(),
+            comments.len(),
+            reading_list.len(),
+            followers.len()
+        ),
+        "\n## Articles\n".into(),
+    ];
+    for article in articles {
+        out.push(format!(
+            "- [{}](articles/{}.md){}",
+            article.title,
+            article.file_stem(),
+            if article.published { "" } else { " (draft)" }
+        ));
+    }
+    out.push("\n## Reading list\n".into());
+    for item in reading_list {
+        let article = &item["article"];
+        out.push(format!(
+            "- [{}]({})",
+            field(article, "title"),
+            field(article, "url")
+        ));
+    }
+    out.push("\n## Followers\n".into());
+    let mut recent = followers.iter().collect::<Vec<_>>();
+    recent.sort_by(|a, b| field(b, "created_at").cmp(field(a, "created_at")));
+    for follower in recent.iter().take(RECENT_FOLLOWERS) {
+        out.push(format!(
+            "- {} ({})",
+            field(follower, "name"),
+            field(follower, "username")
+        ));
+    }
+    if followers.len() > RECENT_FOLLOWERS {
+        out.push(format!("- and {} more", followers.len() - RECENT_FOLLOWERS));
+    }
+    out.join("\n") + "\n"
+}
+
+/// comments as markdown, under the articles they're on
+fn comments_markdown(comments: &[Value]) -> String {
+    let mut out = vec!["# Comments\n".to_string()];
+    for comment in comments {
+        out.push(format!(
+            "## {} on {}\n\n{}\n",
+            field(comment, "created_at"),
+            field(comment, "article_url"),
+            comments::text(field(comment, "body_html"))
+        ));
+    }
+    out.join("\n")
+}
+
+fn write_json(
+    path: PathBuf,
+    value: &impl serde::Serialize,
+) -> anyhow::Result<()> {
+    fs::write(path, serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+pub async fn run(
+    config: &Config,
+    args: Takeout,
+) -> anyhow::Result<()> {
+    let Takeout { out, target } = args;
+    let (name, target) = config.target(target.as_deref())?;
+    if target.kind != Kind::Forem {
+        bail!("takeout is only supported with Forem targets");
+    }
+    let client = Client::new(&target, oauth::auth(&name, &target).await?)?;
+    let dir = out.join(Local::now().format("%Y-%m-%d").to_string());
+    fs::create_dir_all(dir.join("articles"))?;
+
+    let profile: Value = client.get("users/me").await?;
+    let username = field(&profile, "username").to_string();
+    println!("Taking out {}'s data to {}", username, dir.display());
+
+    let raw: Vec<Value> = client.all("articles/me/all").await?;
+    let articles = raw
+        .iter()
+        .map(|article| serde_json::from_value::<Article>(article.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+    for article in &articles {
+        fs::write(
+            dir.join("articles")
+                .join(format!("{}.md", article.file_stem())),
+            &article.body_markdown,
+        )?;
+    }
+
+    let mut comments = Vec::new();
+    for article in articles
+        .iter()
+        .filter(|a| a.published && a.comments_count > 0)
+    {
+        let thread: Vec<Value> =
+            api::public(&target, &format!("comments?a_id={}", article.id)).await?;
+        comments.extend(mine(thread, &username, &article.url));
+    }
+    let reading_list: Vec<Value> = client.all("readinglist").await?;
+    let followers: Vec<Value> = client.all("followers/users").await?;
+
+    write_json(dir.join("profile.json"), &profile)?;
+    write_json(dir.join("articles.json"), &raw)?;
+    write_json(dir.join("comments.json"), &comments)?;
+    write_json(dir.join("reading-list.json"), &reading_list)?;
+    write_json(dir.join("followers.json"), &followers)?;
+    fs::write(dir.join("comments.md"), comments_markdown(&comments))?;
+    fs::write(
+        dir.join("index.md"),
+        index(&profile, &articles, &comments, &reading_list, &followers),
+    )?;
+    println!(
+        "Took out {} article(s), {} comment(s), {} reading list item(s) and {} follower(s)",
+        articles.len(),
+        comments.len(),
+        reading_list.len(),
+        followers.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn mine_finds_replies_by_the_user() {
+        let comments = vec![json!({
+            "created_at": "2024-01-01T00:00:00Z",
+            "user": { "username": "ada" },
+            "children": [
+                {
+                    "created_at": "2024-01-03T00:00:00Z",
+                    "user": { "username": "me" },
+                    "children": []
+                },
+                {
+                    "created_at": "2024-01-02T00:00:00Z",
+                    "user": { "username": "me" },
+                    "children": []
+                }
+            ]
+        })];
+        let found = mine(comments, "me", "https://dev.to/me/post");
+        assert_eq!(
+            found
+                .iter()
+                .map(|c| (field(c, "created_at"), field(c, "article_url")))
+                .collect::<Vec<_>>(),
+            vec![
+                ("2024-01-02T00:00:00Z", "https://dev.to/me/post"),
+                ("2024-01-03T00:00:00Z", "https://dev.to/me/post")
+            ]
+        );
+    }
+
+    #[test]
+    fn index_lists_recent_followers() {
+        let followers = (0..12)
+            .map(|n| {
+                json!({
+                    "name": format!("Follower {}", n),
+                    "username": format!("f{}", n),
+                    "created_at": format!("2024-01-{:02}T00:00:00Z", n + 1)
+                })
+            })
+            .collect::<Vec<_>>();
+        let index = index(
+            &json!({ "name": "Me", "username": "me" }),
+            &[],
+            &[],
+            &[],
+            &followers,
+        );
+        assert!(index.starts_with("# Me (me)\n"));
+        assert!(index.contains("## Followers\n\n- Follower 11 (f11)\n"));
+        assert!(index.ends_with("- Follower 2 (f2)\n- and 2 more\n"));
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{dates, push};
+use anyhow::{anyhow, bail};
+use regex::Regex;
+use std::{fmt, str::FromStr};
+
+/// A field of a file's status line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column {
+    Status,
+    Title,
+    File,
+    Target,
+    Url,
+    /// when the file's article was published, in this machine's timezone
+    Published,
+}
+
+/// Names of columns, as written in templates and --columns
+pub const COLUMNS: &[&str] = &["status", "title", "file", "target", "url", "published"];
+
+impl FromStr for Column {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "status" => Ok(Column::Status),
+            "title" => Ok(Column::Title),
+            "file" => Ok(Column::File),
+            "target" => Ok(Column::Target),
+            "url" => Ok(Column::Url),
+            "published" => Ok(Column::Published),
+            other => Err(anyhow!(
+                "unknown column {}. Expected one of {}",
+                other,
+                COLUMNS.join(", ")
+            )),
+        }
+    }
+}
+
+impl Column {
+    const ALL: [Column; 6] = [
+        Column::Status,
+        Column::Title,
+        Column::File,
+        Column::Target,
+        Column::Url,
+        Column::Published,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Column::Status => "status",
+            Column::Title => "title",
+            Column::File => "file",
+            Column::Target => "target",
+            Column::Url => "url",
+            Column::Published => "published",
+        }
+    }
+
+    /// characters a column is padded or cut to in a table, the rest of the line when none
+    fn width(self) -> Option<usize> {
+        match self {
+            Column::Status => Some(24),
+            Column::Title => Some(50),
+            Column::File => Some(40),
+            Column::Target => Some(12),
+            Column::Url => None,
+            Column::Published => Some(16),
+        }
+    }
+}
+
+/// How a push prints each file's status, besides the default dotted line
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineFormat {
+    /// text with placeholders like {title} replaced
+    Template(String),
+    /// aligned columns under a header
+    Table,
+}
+
+impl FromStr for LineFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "table" {
+            return Ok(LineFormat::Table);
+        }
+        let placeholder = Regex::new(r"\{([^}]*)\}").expect("valid regex");
+        for name in placeholder.captures_iter(s) {
+            if let Err(err) = name[1].parse::<Column>() {
+                bail!("invalid --format {}: {}", s, err);
+            }
+        }
+        // shells pass \t in quotes as is
+        Ok(LineFormat::Template(
+            s.replace("\\t", "\t").replace("\\n", "\n"),
+        ))
+    }
+}
+
+/// The fields of a file's status line
+pub struct Fields<'a> {
+    pub target: &'a str,
+    pub file: &'a str,
+    pub title: &'a str,
+    pub url: Option<&'a str>,
+    /// when the file was written, to sort by
+    pub date: Option<&'a str>,
+    /// when the file's article was published, as the api reports it
+    pub published: Option<&'a str>,
+}
+
+/// Renders files' status lines in the format chosen for a push
+pub struct Lines {
+    format: Option<LineFormat>,
+    columns: Vec<Column>,
+}
+
+/// text without terminal color codes
+pub fn plain(text: &str) -> String {
+    let codes = Regex::new("\x1b\\[[0-9;]*m").expect("valid regex");
+    codes.replace_all(text, "").into_owned()
+}
+
+/// length of text as displayed, leaving out terminal color codes
+fn visible_len(text: &str) -> usize {
+    plain(text).chars().count()
+}
+
+impl Lines {
+    pub fn new(
+        format: Option<LineFormat>,
+        columns: Vec<Column>,
+    ) -> Self {
+        Lines { format, columns }
+    }
+
+    /// the header of a table, if printing one
+    pub fn header(&self) -> Option<String> {
+        match self.format {
+            Some(LineFormat::Table) => Some(
+                self.row(
+                    self.columns
+                        .iter()
+                        .map(|column| column.name().to_uppercase())
+                        .collect(),
+                ),
+            ),
+            _ => None,
+        }
+    }
+
+    /// cells padded to their columns' widths
+    fn row(
+        &self,
+        cells: Vec<String>,
+    ) -> String {
+        let last = cells.len().saturating_sub(1);
+        cells
+            .into_iter()
+            .zip(&self.columns)
+            .enumerate()
+            .map(|(n, (cell, column))| match column.width() {
+                Some(width) if n < last => {
+                    let cell = if visible_len(&cell) > width {
+                        cell.chars().take(width - 1).collect::<String>() + "…"
+                    } else {
+                        cell
+                    };
+                    let padding = width.saturating_sub(visible_len(&cell));
+                    format!("{}{} ", cell, " ".repeat(padding))
+                }
+                _ => cell,
+            })
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    /// a file's status line
+    pub fn line(
+        &self,
+        fields: &Fields<'_>,
+        status: impl fmt::Display,
+    ) -> String {
+        let status = status.to_string();
+        let value = |column: Column| match column {
+            Column::Status => status.clone(),
+            Column::Title => fields.title.to_string(),
+            Column::File => fields.file.to_string(),
+            Column::Target => fields.target.to_string(),
+            Column::Url => fields.url.unwrap_or_default().to_string(),
+            Column::Published => fields.published.map(dates::local).unwrap_or_default(),
+        };
+        match &self.format {
+            None => push::line(fields.title, status),
+            Some(LineFormat::Table) => self.row(self.columns.iter().map(|c| value(*c)).collect()),
+            Some(LineFormat::Template(template)) => {
+                Column::ALL.iter().fold(template.clone(), |line, column| {
+                    line.replace(&format!("{{{}}}", column.name()), &value(*column))
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELDS: Fields<'static> = Fields {
+        target: "devto",
+        file: "posts/hello.md",
+        title: "Hello, world",
+        url: Some("https://dev.to/me/hello"),
+        date: None,
+        published: None,
+    };
+
+    #[test]
+    fn templates_replace_placeholders() -> anyhow::Result<()> {
+        let lines = Lines::new(Some(r"{status}\t{title}\t{url}".parse()?), Vec::new());
+        assert_eq!(
+            lines.line(&FIELDS, "SYNCING published"),
+            "SYNCING published\tHello, world\thttps://dev.to/me/hello"
+        );
+        assert!("{status} {views}".parse::<LineFormat>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn tables_align_columns() -> anyhow::Result<()> {
+        let lines = Lines::new(
+            Some("table".parse()?),
+            vec![Column::Target, Column::Status, Column::File],
+        );
+        assert_eq!(
+            lines.header(),
+            Some(format!(
+                "TARGET{}STATUS{}FILE",
+                " ".repeat(7),
+                " ".repeat(19)
+            ))
+        );
+        assert_eq!(
+            lines.line(&FIELDS, "UPLOADED"),
+            format!(
+                "devto{}UPLOADED{}posts/hello.md",
+                " ".repeat(8),
+                " ".repeat(17)
+            )
+        );
+        Ok(())
+    }
+}
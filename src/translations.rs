@@ -0,0 +1,127 @@
+use crate::{article, config::Config, push};
+use anyhow::bail;
+use colored::Colorize;
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use structopt::StructOpt;
+
+/// Reports translations of articles which are missing or older than their primary language version
+///
+/// Translations are named after the primary language file with a language suffix,
+/// i.e. my-post.es.md translates my-post.md
+#[derive(StructOpt, Debug)]
+pub struct Translations {
+    /// Directory to source markdown files from. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Status {
+    Current,
+    Stale,
+    Missing,
+}
+
+impl fmt::Display for Status {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let s = format!(
+            "{}",
+            match self {
+                Status::Current => "CURRENT".green(),
+                Status::Stale => "STALE".yellow(),
+                Status::Missing => "MISSING".red(),
+            }
+        );
+        f.write_str(&s)
+    }
+}
+
+/// a translation is stale when its primary language version changed after it
+fn status(
+    primary: SystemTime,
+    translation: Option<SystemTime>,
+) -> Status {
+    match translation {
+        None => Status::Missing,
+        Some(modified) if modified < primary => Status::Stale,
+        Some(_) => Status::Current,
+    }
+}
+
+fn modified(path: &Path) -> anyhow::Result<SystemTime> {
+    Ok(fs::metadata(path)?.modified()?)
+}
+
+pub fn run(
+    config: &Config,
+    args: Translations,
+) -> anyhow::Result<()> {
+    let source = config.source(args.source);
+    let (primary, languages) = match config.languages.split_first() {
+        Some(languages) => languages,
+        None => bail!(
+            "no languages are configured.\n  ▶ List them, primary language first, in devtogo.toml, i.e. languages = [\"en\", \"es\"]"
+        ),
+    };
+    // translations of each primary language file, by language
+    let mut articles: BTreeMap<PathBuf, BTreeMap<String, PathBuf>> = BTreeMap::new();
+    for path in article::paths(&source, config) {
+        match article::language(&path, &config.languages) {
+            Some((language, base)) if &language != primary => {
+                articles.entry(base).or_default().insert(language, path);
+            }
+            Some((_, base)) => {
+                articles.entry(base).or_default();
+            }
+            None => {
+                articles.entry(path).or_default();
+            }
+        }
+    }
+    for (base, translations) in articles {
+        if !base.exists() {
+            continue;
+        }
+        let content = article::read(config, &source, &base)?;
+        let file_name = base.file_name().unwrap_or_default().to_string_lossy();
+        let (meta, _) = article::extract(file_name.as_ref(), &content)?;
+        let primary = modified(&base)?;
+        let statuses = languages
+            .iter()
+            .map(|language| {
+                let translation = match translations.get(language) {
+                    Some(path) => Some(modified(path)?),
+                    None => None,
+                };
+                Ok(format!("{} {}", language, status(primary, translation)))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        println!("{}", push::line(&meta.title, statuses.join(" ")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn status_compares_modification_times() {
+        let now = SystemTime::now();
+        assert_eq!(status(now, None), Status::Missing);
+        assert_eq!(
+            status(now, Some(now - Duration::from_secs(60))),
+            Status::Stale
+        );
+        assert_eq!(status(now, Some(now)), Status::Current);
+    }
+}
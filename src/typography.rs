@@ -0,0 +1,124 @@
+/// the end of a span starting at the beginning of `text` which must be left as is: inline
+/// code, html tags, liquid tags and link destinations
+pub fn protected(text: &str) -> Option<usize> {
+    let close = |open: &str, close: &str| {
+        text.strip_prefix(open)?
+            .find(close)
+            .map(|end| open.len() + end + close.len())
+    };
+    if text.starts_with('`') {
+        let ticks = text.chars().take_while(|c| *c == '`').count();
+        return close(&text[..ticks], &text[..ticks]).or(Some(text.len()));
+    }
+    if text.starts_with('<') && text[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/')
+    {
+        return close("<", ">");
+    }
+    close("{%", "%}")
+        .or_else(|| close("{{", "}}"))
+        .or_else(|| close("](", ")"))
+}
+
+/// true if a line only separates, i.e. a thematic break or table delimiter row
+fn separator(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty()
+        && trimmed
+            .chars()
+            .all(|c| matches!(c, '-' | '|' | ':' | ' ' | '*' | '_'))
+}
+
+fn opening(previous: Option<char>) -> bool {
+    match previous {
+        None => true,
+        Some(c) => c.is_whitespace() || matches!(c, '(' | '[' | '{' | '—' | '–' | '“' | '‘'),
+    }
+}
+
+fn line(text: &str) -> String {
+    if separator(text) {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut previous = None;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some(end) = protected(rest) {
+            out.push_str(&rest[..end]);
+            previous = rest[..end].chars().last();
+            rest = &rest[end..];
+            continue;
+        }
+        let (replacement, len) = if rest.starts_with("...") {
+            ('…', 3)
+        } else if rest.starts_with("---") {
+            ('—', 3)
+        } else if rest.starts_with("--") {
+            ('–', 2)
+        } else if c == '"' {
+            (if opening(previous) { '“' } else { '”' }, 1)
+        } else if c == '\'' {
+            match previous {
+                Some(p) if p.is_alphanumeric() => ('’', 1),
+                p if opening(p) => ('‘', 1),
+                _ => ('’', 1),
+            }
+        } else {
+            (c, c.len_utf8())
+        };
+        out.push(replacement);
+        previous = Some(replacement);
+        rest = &rest[len..];
+    }
+    out
+}
+
+/// apply smart quotes, dashes and ellipses to prose. Frontmatter, code, html and liquid tags
+/// and link destinations are left as is
+pub fn smarten(content: &str) -> String {
+    let mut out = Vec::new();
+    let mut fenced = false;
+    let mut lines = content.split('\n');
+    if content.starts_with("---") {
+        for line in &mut lines {
+            out.push(line.to_string());
+            if out.len() > 1 && line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+    for text in lines {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fenced = !fenced;
+            out.push(text.to_string());
+        } else if fenced {
+            out.push(text.to_string());
+        } else {
+            out.push(line(text));
+        }
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smarten_polishes_prose() {
+        assert_eq!(
+            smarten("---\ntitle: \"it's\"\n---\n\"Don't\" -- she said... 'ok'---fine\n"),
+            "---\ntitle: \"it's\"\n---\n“Don’t” – she said… ‘ok’—fine\n"
+        );
+    }
+
+    #[test]
+    fn smarten_leaves_code_and_markup_alone() {
+        let content = "Run `echo \"hi\"` <a href=\"x\">it's</a> [link](https://x.dev/a--b) {% embed \"x\" %}\n\n---\n| a | b |\n|---|---|\n```\nlet s = \"...\";\n```";
+        assert_eq!(
+            smarten(content),
+            "Run `echo \"hi\"` <a href=\"x\">it’s</a> [link](https://x.dev/a--b) {% embed \"x\" %}\n\n---\n| a | b |\n|---|---|\n```\nlet s = \"...\";\n```"
+        );
+    }
+}
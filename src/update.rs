@@ -0,0 +1,245 @@
+use crate::api;
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Duration, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{env, fs, path::Path, process::Command};
+use structopt::StructOpt;
+
+/// Where releases are published
+const RELEASES_URL: &str = "https://api.github.com/repos/softprops/devtogo/releases/latest";
+
+/// Name of the file, in the temp directory, the last check for a new version is kept in
+const LAST_CHECK: &str = "devtogo-update-check.json";
+
+/// Replaces this devtogo with the latest release, once its checksum is verified
+#[derive(StructOpt, Debug)]
+pub struct SelfUpdate {
+    /// Only report whether a newer version is available
+    #[structopt(long)]
+    check: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// The outcome of the last check for a new version, so notices check at most once a day
+#[derive(Debug, Serialize, Deserialize)]
+struct LastCheck {
+    checked_at: DateTime<Utc>,
+    latest: String,
+}
+
+/// numeric parts of a version like v1.2.3, ignoring pre-release and build suffixes
+fn parts(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or_default())
+        .collect()
+}
+
+/// true if `latest` is a later version than `current`
+fn newer(
+    latest: &str,
+    current: &str,
+) -> bool {
+    parts(latest) > parts(current)
+}
+
+/// name of the release archive built for this platform
+fn asset_name() -> anyhow::Result<String> {
+    let (os, extension) = match env::consts::OS {
+        "linux" => ("Linux", "tar.gz"),
+        "macos" => ("Darwin", "tar.gz"),
+        "windows" => ("Windows", "zip"),
+        other => bail!("no releases are built for {}", other),
+    };
+    Ok(format!(
+        "devtogo-{}-{}.{}",
+        os,
+        env::consts::ARCH,
+        extension
+    ))
+}
+
+async fn latest(http: &reqwest::Client) -> anyhow::Result<Release> {
+    let resp = http.get(RELEASES_URL).send().await?;
+    if !resp.status().is_success() {
+        bail!("unable to fetch the latest release: {}", resp.status());
+    }
+    Ok(resp.json().await?)
+}
+
+async fn download(
+    http: &reqwest::Client,
+    url: &str,
+) -> anyhow::Result<Vec<u8>> {
+    let resp = http.get(url).send().await?;
+    if !resp.status().is_success() {
+        bail!("unable to download {}: {}", url, resp.status());
+    }
+    Ok(resp.bytes().await?.to_vec())
+}
+
+/// move a new executable into place of the running one. Windows won't remove a running
+/// executable, so it's moved aside first
+fn replace(
+    current: &Path,
+    binary: &Path,
+) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(binary, fs::Permissions::from_mode(0o755))?;
+    }
+    let old = current.with_extension("old");
+    let _ = fs::remove_file(&old);
+    fs::rename(current, &old)?;
+    if let Err(err) = fs::rename(binary, current).or_else(|_| fs::copy(binary, current).map(|_| ()))
+    {
+        fs::rename(&old, current)?;
+        return Err(err.into());
+    }
+    let _ = fs::remove_file(&old);
+    Ok(())
+}
+
+pub async fn run(args: SelfUpdate) -> anyhow::Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let http = api::http()?;
+    let release = latest(&http).await?;
+    if !newer(&release.tag_name, current) {
+        println!("devtogo {} is the latest version", current);
+        return Ok(());
+    }
+    if args.check {
+        println!(
+            "devtogo {} is available, you have {}",
+            release.tag_name, current
+        );
+        return Ok(());
+    }
+    let name = asset_name()?;
+    let asset = |name: &str| {
+        release
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .ok_or_else(|| anyhow!("release {} has no {}", release.tag_name, name))
+    };
+    let archive = download(&http, &asset(&name)?.browser_download_url).await?;
+    let checksum = download(
+        &http,
+        &asset(&format!("{}.sha256", name))?.browser_download_url,
+    )
+    .await?;
+    let expected = String::from_utf8_lossy(&checksum)
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if format!("{:x}", Sha256::digest(&archive)) != expected {
+        bail!(
+            "the checksum of {} doesn't match the one published with it, so it was not installed",
+            name
+        );
+    }
+    let dir = env::temp_dir().join(format!("devtogo-{}", release.tag_name));
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(&name);
+    fs::write(&path, &archive)?;
+    // tar ships with Linux, macOS and Windows 10 onwards, and reads zip archives on Windows
+    let status = Command::new("tar")
+        .arg("-xf")
+        .arg(&path)
+        .current_dir(&dir)
+        .status()
+        .map_err(|err| anyhow!("failed to run tar: {}", err))?;
+    if !status.success() {
+        bail!("unable to extract {}", name);
+    }
+    let binary = dir.join(format!("devtogo{}", env::consts::EXE_SUFFIX));
+    replace(&env::current_exe()?, &binary)?;
+    let _ = fs::remove_dir_all(&dir);
+    println!("Updated devtogo {} to {}", current, release.tag_name);
+    Ok(())
+}
+
+/// print a notice when a newer version was released, checking at most once a day. Failures
+/// are ignored so checks never get in the way
+pub async fn notify() {
+    let path = env::temp_dir().join(LAST_CHECK);
+    let last = fs::read_to_string(&path)
+        .ok()
+        .and_then(|last| serde_json::from_str::<LastCheck>(&last).ok())
+        .filter(|last| last.checked_at + Duration::days(1) > Utc::now());
+    let latest = match last {
+        Some(last) => last.latest,
+        None => {
+            let http = match api::http() {
+                Ok(http) => http,
+                Err(_) => return,
+            };
+            let check = tokio::time::timeout(std::time::Duration::from_secs(2), latest(&http));
+            let release = match check.await {
+                Ok(Ok(release)) => release,
+                _ => return,
+            };
+            let last = LastCheck {
+                checked_at: Utc::now(),
+                latest: release.tag_name,
+            };
+            if let Ok(json) = serde_json::to_string(&last) {
+                let _ = fs::write(&path, json);
+            }
+            last.latest
+        }
+    };
+    if newer(&latest, env!("CARGO_PKG_VERSION")) {
+        eprintln!(
+            "{}",
+            format!(
+                "devtogo {} is available. Update with devtogo self-update",
+                latest
+            )
+            .dimmed()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_compares_versions() {
+        assert!(newer("v0.2.0", "0.1.0"));
+        assert!(newer("0.10.0", "0.9.1"));
+        assert!(newer("v1.0.0", "0.9.0-alpha"));
+        assert!(!newer("v0.1.0", "0.1.0"));
+        assert!(!newer("v0.1.0-rc.1", "0.1.0"));
+    }
+
+    #[test]
+    fn asset_name_matches_release_builds() -> anyhow::Result<()> {
+        if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+            assert_eq!(asset_name()?, "devtogo-Linux-x86_64.tar.gz");
+        }
+        Ok(())
+    }
+}
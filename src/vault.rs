@@ -0,0 +1,172 @@
+use anyhow::{anyhow, bail};
+use ring::{
+    aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use std::{
+    convert::TryInto,
+    env, fs,
+    num::NonZeroU32,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+/// Environment variable holding the passphrase state is encrypted with
+pub const PASSPHRASE_ENV: &str = "DEVTOGO_PASSPHRASE";
+
+/// Start of every encrypted file, so plain files written before encryption was turned on can
+/// still be read
+const MAGIC: &[u8] = b"devtogo-encrypted-v1\n";
+
+/// Rounds of PBKDF2 keys are derived from passphrases with
+const ROUNDS: u32 = 100_000;
+
+const SALT_LEN: usize = 16;
+
+static ENCRYPT: AtomicBool = AtomicBool::new(false);
+
+/// A passphrase, a salt and the key they derive
+type Derived = (String, [u8; SALT_LEN], [u8; 32]);
+
+/// Keys derived so far, since deriving them is slow on purpose. Files written by one run share a
+/// salt
+static KEYS: Mutex<Vec<Derived>> = Mutex::new(Vec::new());
+
+/// encrypt state written from now on, when asked to
+pub fn init(encrypt: bool) {
+    ENCRYPT.store(encrypt, Ordering::Relaxed);
+}
+
+fn passphrase() -> anyhow::Result<String> {
+    match env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => Ok(passphrase),
+        _ => bail!(
+            "state is encrypted, so a passphrase is required\n  ▶ Export it as {}",
+            PASSPHRASE_ENV
+        ),
+    }
+}
+
+/// the key a passphrase derives with a salt
+fn key(
+    passphrase: &str,
+    salt: &[u8; SALT_LEN],
+) -> LessSafeKey {
+    let mut keys = KEYS.lock().unwrap_or_else(|err| err.into_inner());
+    let bytes = match keys.iter().find(|(p, s, _)| p == passphrase && s == salt) {
+        Some((_, _, bytes)) => *bytes,
+        None => {
+            let mut bytes = [0; 32];
+            let rounds = NonZeroU32::new(ROUNDS).expect("rounds are not zero");
+            pbkdf2::derive(
+                pbkdf2::PBKDF2_HMAC_SHA256,
+                rounds,
+                salt,
+                passphrase.as_bytes(),
+                &mut bytes,
+            );
+            keys.push((passphrase.into(), *salt, bytes));
+            bytes
+        }
+    };
+    LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, &bytes).expect("keys are 32 bytes"))
+}
+
+/// text encrypted with a passphrase
+fn seal(
+    passphrase: &str,
+    text: &str,
+) -> anyhow::Result<Vec<u8>> {
+    let random = SystemRandom::new();
+    let salt = {
+        let keys = KEYS.lock().unwrap_or_else(|err| err.into_inner());
+        keys.iter()
+            .find(|(p, _, _)| p == passphrase)
+            .map(|(_, salt, _)| *salt)
+    };
+    let salt = match salt {
+        Some(salt) => salt,
+        None => {
+            let mut salt = [0; SALT_LEN];
+            random
+                .fill(&mut salt)
+                .map_err(|_| anyhow!("unable to generate a salt"))?;
+            salt
+        }
+    };
+    let mut nonce = [0; NONCE_LEN];
+    random
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("unable to generate a nonce"))?;
+    let mut sealed = text.as_bytes().to_vec();
+    key(passphrase, &salt)
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::empty(),
+            &mut sealed,
+        )
+        .map_err(|_| anyhow!("unable to encrypt"))?;
+    Ok([MAGIC, &salt, &nonce, &sealed].concat())
+}
+
+/// text of data, decrypting it with a passphrase if it's encrypted
+fn open(
+    passphrase: impl FnOnce() -> anyhow::Result<String>,
+    data: Vec<u8>,
+) -> anyhow::Result<String> {
+    let sealed = match data.strip_prefix(MAGIC) {
+        Some(sealed) if sealed.len() >= SALT_LEN + NONCE_LEN + aead::MAX_TAG_LEN => sealed,
+        Some(_) => bail!("encrypted data is truncated"),
+        None => return Ok(String::from_utf8(data)?),
+    };
+    let (salt, rest) = sealed.split_at(SALT_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let salt = salt.try_into().expect("salts are SALT_LEN bytes");
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("invalid nonce"))?;
+    let mut sealed = sealed.to_vec();
+    let text = key(&passphrase()?, &salt)
+        .open_in_place(nonce, Aad::empty(), &mut sealed)
+        .map_err(|_| anyhow!("unable to decrypt. Is {} right?", PASSPHRASE_ENV))?;
+    Ok(String::from_utf8(text.to_vec())?)
+}
+
+/// read a state file, decrypting it if it's encrypted
+pub fn read(path: &Path) -> anyhow::Result<String> {
+    open(passphrase, fs::read(path)?).map_err(|err| anyhow!("{}: {}", path.display(), err))
+}
+
+/// write a state file, encrypting it when state is encrypted
+pub fn write(
+    path: &Path,
+    text: &str,
+) -> anyhow::Result<()> {
+    if ENCRYPT.load(Ordering::Relaxed) {
+        fs::write(path, seal(&passphrase()?, text)?)?;
+    } else {
+        fs::write(path, text)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_text_opens_with_its_passphrase() -> anyhow::Result<()> {
+        let sealed = seal("correct horse", "embargoed draft")?;
+        assert!(sealed.starts_with(MAGIC));
+        assert!(!String::from_utf8_lossy(&sealed).contains("embargoed"));
+        assert_eq!(
+            open(|| Ok("correct horse".into()), sealed.clone())?,
+            "embargoed draft"
+        );
+        assert!(open(|| Ok("battery staple".into()), sealed).is_err());
+        assert_eq!(open(|| bail!("no passphrase"), b"plain".to_vec())?, "plain");
+        Ok(())
+    }
+}
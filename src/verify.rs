@@ -0,0 +1,159 @@
+use crate::{
+    article,
+    backend::{Account, Accounts},
+    config::Config,
+    console, push,
+    state::{self, Entry, State},
+};
+use anyhow::bail;
+use colored::Colorize;
+use std::{fmt, path::PathBuf};
+use structopt::StructOpt;
+
+/// Checks local files and remote articles against what was recorded when they were last in sync
+///
+/// Reports files edited locally, articles edited remotely and articles
+/// which no longer exist, without changing anything
+#[derive(StructOpt, Debug)]
+pub struct Verify {
+    /// Directory to source markdown files from. Defaults to current working directory
+    #[structopt(short, long)]
+    source: Option<PathBuf>,
+    /// Configured target to verify. May be repeated. Defaults to all targets
+    #[structopt(short, long)]
+    target: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Status {
+    Verified,
+    Untracked,
+    LocalDrift,
+    RemoteDrift,
+    Drift,
+    Missing,
+}
+
+impl fmt::Display for Status {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let s = format!(
+            "{}",
+            match self {
+                Status::Verified => "VERIFIED".green(),
+                Status::Untracked => "UNTRACKED".dimmed(),
+                Status::LocalDrift => "CHANGED LOCALLY".yellow(),
+                Status::RemoteDrift => "CHANGED REMOTELY".yellow(),
+                Status::Drift => "CHANGED BOTH".red(),
+                Status::Missing => "MISSING REMOTELY".red(),
+            }
+        );
+        f.write_str(&s)
+    }
+}
+
+/// compare a file and its remote article with their recorded hashes. Remote
+/// articles are only looked for on backends which can list them
+fn status(
+    content: &str,
+    remote: Option<&str>,
+    entry: Option<&Entry>,
+    lists: bool,
+) -> Status {
+    let entry = match entry {
+        Some(entry) => entry,
+        None => return Status::Untracked,
+    };
+    let local = state::hash(content) != entry.local;
+    let remote = match remote {
+        Some(body) => state::hash(body) != entry.remote,
+        None if lists => return Status::Missing,
+        None => false,
+    };
+    match (local, remote) {
+        (false, false) => Status::Verified,
+        (true, false) => Status::LocalDrift,
+        (false, true) => Status::RemoteDrift,
+        (true, true) => Status::Drift,
+    }
+}
+
+pub async fn run(
+    config: &Config,
+    args: Verify,
+) -> anyhow::Result<()> {
+    let Verify { source, target } = args;
+    let source = config.source(source);
+    let targets = config.targets(&target)?;
+    let announce = targets.len() > 1;
+    let mut drifted = 0;
+    for (name, target) in targets {
+        if announce {
+            println!("{}", console::text(&format!("▶ {}", name)).bold());
+        }
+        let mut accounts = Accounts::new(config, &name, &target);
+        let state = State::load(&source, &name)?;
+        for path in article::paths(&source, config) {
+            let content = article::render(config, &source, &path, target.kind).await?;
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let (meta, _) = article::extract(file_name.as_ref(), &content)?;
+            let language = article::language(&path, &config.languages)
+                .map(|(language, _)| language)
+                .or_else(|| config.languages.first().cloned());
+            if !meta.targets(&name) || !meta.pushed() || !target.publishes(language.as_deref()) {
+                continue;
+            }
+            let Account { backend, articles } = accounts.get(meta.api_key_ref.as_deref()).await?;
+            let entry = state.get(&state::key(&source, &path));
+            let remote = entry
+                .and_then(|entry| articles.iter().find(|a| a.id == entry.id))
+                .map(|a| a.body_markdown.as_str());
+            let status = status(&content, remote, entry, backend.lists());
+            if !matches!(status, Status::Verified | Status::Untracked) {
+                drifted += 1;
+            }
+            println!("{}", push::line(&meta.title, status));
+        }
+    }
+    if drifted > 0 {
+        bail!(
+            "{} article(s) changed since they were last in sync",
+            drifted
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_without_entry_is_untracked() {
+        assert_eq!(status("a", Some("a"), None, true), Status::Untracked);
+    }
+
+    #[test]
+    fn status_reports_drift_on_either_side() {
+        let entry = Entry::new("1", "a", "a");
+        assert_eq!(status("a", Some("a"), Some(&entry), true), Status::Verified);
+        assert_eq!(
+            status("b", Some("a"), Some(&entry), true),
+            Status::LocalDrift
+        );
+        assert_eq!(
+            status("a", Some("b"), Some(&entry), true),
+            Status::RemoteDrift
+        );
+        assert_eq!(status("b", Some("b"), Some(&entry), true), Status::Drift);
+    }
+
+    #[test]
+    fn status_of_unlisted_remotes() {
+        let entry = Entry::new("1", "a", "a");
+        assert_eq!(status("a", None, Some(&entry), true), Status::Missing);
+        assert_eq!(status("a", None, Some(&entry), false), Status::Verified);
+    }
+}
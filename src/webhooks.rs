@@ -0,0 +1,126 @@
+use crate::{
+    api::Client,
+    config::{Config, Kind},
+    oauth,
+};
+use anyhow::bail;
+use colored::Colorize;
+use structopt::StructOpt;
+
+/// Events a webhook can be notified of
+const EVENTS: &[&str] = &["article_created", "article_updated", "article_destroyed"];
+
+/// Registers endpoints to be notified of events on your articles, i.e. to rebuild a blog when one
+/// is published
+#[derive(StructOpt, Debug)]
+pub enum Webhooks {
+    /// Registers an endpoint to be notified of events on your articles
+    Create {
+        /// Url of the endpoint to notify
+        #[structopt(long)]
+        url: String,
+        /// Events to notify the endpoint of, separated by commas
+        #[structopt(
+            long,
+            use_delimiter = true,
+            possible_values = EVENTS,
+            default_value = "article_created,article_updated,article_destroyed"
+        )]
+        events: Vec<String>,
+        /// Name identifying where notifications come from
+        #[structopt(long, default_value = "devtogo")]
+        source: String,
+        /// Configured target to register with. Required when more than one is configured
+        #[structopt(short, long)]
+        target: Option<String>,
+    },
+    /// Lists the endpoints registered, with their ids
+    List {
+        /// Configured target to list. Required when more than one is configured
+        #[structopt(short, long)]
+        target: Option<String>,
+    },
+    /// Stops notifying an endpoint, by its id as listed
+    Delete {
+        id: String,
+        /// Configured target to delete from. Required when more than one is configured
+        #[structopt(short, long)]
+        target: Option<String>,
+    },
+}
+
+pub async fn run(
+    config: &Config,
+    args: Webhooks,
+) -> anyhow::Result<()> {
+    let target = match &args {
+        Webhooks::Create { target, .. }
+        | Webhooks::List { target }
+        | Webhooks::Delete { target, .. } => target.as_deref(),
+    };
+    let (name, target) = config.target(target)?;
+    if target.kind != Kind::Forem {
+        bail!("webhooks are only supported with Forem targets");
+    }
+    let client = Client::new(&target, oauth::auth(&name, &target).await?)?;
+    match args {
+        Webhooks::Create {
+            url,
+            events,
+            source,
+            ..
+        } => {
+            let webhook = client.create_webhook(&url, &source, &events).await?;
+            println!(
+                "Registered {} for {} {}",
+                webhook.target_url.bold(),
+                webhook.events.join(", "),
+                format!("(id {})", webhook.id).dimmed()
+            );
+        }
+        Webhooks::List { .. } => {
+            let webhooks = client.webhooks().await?;
+            for webhook in &webhooks {
+                println!(
+                    "{:>8}  {} {}",
+                    webhook.id,
+                    webhook.target_url.bold(),
+                    format!("{} from {}", webhook.events.join(", "), webhook.source).dimmed()
+                );
+            }
+            if webhooks.is_empty() {
+                println!("No webhooks registered");
+            }
+        }
+        Webhooks::Delete { id, .. } => {
+            client.delete_webhook(&id).await?;
+            println!("Deleted webhook {}", id);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_defaults_to_every_event() {
+        match Webhooks::from_iter_safe(["webhooks", "create", "--url", "https://a.b/hook"]) {
+            Ok(Webhooks::Create { events, source, .. }) => {
+                assert_eq!(events, EVENTS);
+                assert_eq!(source, "devtogo");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(Webhooks::from_iter_safe([
+            "webhooks",
+            "create",
+            "--url",
+            "https://a.b/hook",
+            "--events",
+            "article_created,article_liked"
+        ])
+        .is_err());
+    }
+}
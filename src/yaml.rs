@@ -0,0 +1,234 @@
+/// How a scalar was written, so a value replacing it can be written the same way
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Style {
+    Plain,
+    Single,
+    Double,
+}
+
+/// Words read as something other than a string when written plain
+const RESERVED: &[&str] = &[
+    "true", "false", "yes", "no", "on", "off", "null", "~", "y", "n",
+];
+
+/// the style of a written value, if it's a scalar on one line
+fn style(value: &str) -> Option<Style> {
+    match value.chars().next()? {
+        '\'' => Some(Style::Single),
+        '"' => Some(Style::Double),
+        '[' | '{' | '|' | '>' | '&' | '*' | '!' => None,
+        _ => Some(Style::Plain),
+    }
+}
+
+/// true if a string reads back as the same string when written without quotes
+fn plain_safe(text: &str) -> bool {
+    let first = match text.chars().next() {
+        Some(first) => first,
+        None => return false,
+    };
+    !"-?:,[]{}#&*!|>'\"%@`".contains(first)
+        && text.trim() == text
+        && !text.contains(": ")
+        && !text.contains(" #")
+        && !text.ends_with(':')
+        && !text.contains(['\n', '\t'])
+        && !RESERVED.contains(&text.to_lowercase().as_str())
+        && text.parse::<f64>().is_err()
+        && !text.starts_with("0x")
+        && !text.starts_with("0o")
+}
+
+/// a yaml value written in `style` where it can be, otherwise as given
+fn restyle(
+    value: &str,
+    style: Style,
+) -> String {
+    // values are given double quoted, as json, when they're strings
+    let text = match serde_json::from_str::<String>(value) {
+        Ok(text) if value.starts_with('"') => text,
+        _ => return value.to_string(),
+    };
+    match style {
+        Style::Plain if plain_safe(&text) => text,
+        Style::Single if !text.contains('\n') => format!("'{}'", text.replace('\'', "''")),
+        _ => value.to_string(),
+    }
+}
+
+/// where the comment trailing a value starts, outside of quotes
+fn comment(value: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in value.char_indices() {
+        match (quote, c) {
+            // '' escapes a quote within single quotes, reopening them
+            (None, '\'' | '"') if previous.is_whitespace() || i == 0 || previous == c => {
+                quote = Some(c)
+            }
+            (Some(open), _) if c == open && previous != '\\' => quote = None,
+            (None, '#') if previous.is_whitespace() => return Some(i),
+            _ => (),
+        }
+        previous = c;
+    }
+    None
+}
+
+/// true if a line continues the value of the field before it: an indented line, a block
+/// sequence item or a blank line
+fn continues(line: &str) -> bool {
+    line.starts_with([' ', '\t']) || line.starts_with("- ") || line.trim_end() == "-"
+}
+
+/// the value of a top level field on its line, if the line sets it
+fn field<'a>(
+    line: &'a str,
+    name: &str,
+) -> Option<&'a str> {
+    let rest = line.strip_prefix(name)?.trim_start_matches([' ', '\t']);
+    let value = rest.strip_prefix(':')?;
+    (value.is_empty() || value.starts_with([' ', '\t', '\r'])).then_some(value)
+}
+
+/// yaml with a top level field set to a yaml `value`, leaving everything else as written:
+/// the order of fields, comments, blank lines and line endings. A replaced value keeps its
+/// trailing comment and is quoted the way the value it replaces was, where that's possible.
+/// Fields not yet set are added at the end
+pub fn set(
+    yaml: &str,
+    name: &str,
+    value: &str,
+) -> String {
+    let cr = if yaml.contains('\r') { "\r" } else { "" };
+    let mut lines = if yaml.is_empty() {
+        Vec::new()
+    } else {
+        yaml.split('\n').map(String::from).collect::<Vec<_>>()
+    };
+    let found = lines
+        .iter()
+        .enumerate()
+        .find_map(|(n, line)| field(line, name).map(|old| (n, old.to_string())));
+    let (n, old) = match found {
+        Some(found) => found,
+        None => {
+            // before a trailing newline, if any
+            let at = match lines.last() {
+                Some(last) if last.trim_end().is_empty() => lines.len() - 1,
+                _ => lines.len(),
+            };
+            lines.insert(at, format!("{}: {}{}", name, value, cr));
+            return lines.join("\n");
+        }
+    };
+    let old = old.trim_end_matches('\r');
+    let (written, trailing) = match comment(old) {
+        Some(at) => old.split_at(at),
+        None => (old, ""),
+    };
+    let spacing = &written[written.trim_end().len()..];
+    let written = written.trim();
+    let value = match style(written) {
+        Some(style) => restyle(value, style),
+        None => value.to_string(),
+    };
+    let line = match trailing {
+        "" => format!("{}: {}{}", name, value, cr),
+        trailing => format!("{}: {}{}{}{}", name, value, spacing, trailing, cr),
+    };
+    // a value spanning lines, i.e. a block sequence of tags, is replaced as a whole. Blank
+    // lines only belong to it when it carries on after them
+    let mut end = n + 1;
+    let mut last = n + 1;
+    while end < lines.len() {
+        let next = &lines[end];
+        if next.trim().is_empty() {
+            end += 1;
+        } else if continues(next) {
+            end += 1;
+            last = end;
+        } else {
+            break;
+        }
+    }
+    lines.splice(n..last, [line]);
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_keeps_comments_order_and_quoting() {
+        let yaml = "title: 'Hello' # shown on dev.to\n# draft until launch\npublished: false  # flip me\ntags: rust\n";
+        assert_eq!(
+            set(yaml, "published", "true"),
+            "title: 'Hello' # shown on dev.to\n# draft until launch\npublished: true  # flip me\ntags: rust\n"
+        );
+        assert_eq!(
+            set(yaml, "title", "\"It's here\""),
+            "title: 'It''s here' # shown on dev.to\n# draft until launch\npublished: false  # flip me\ntags: rust\n"
+        );
+        assert_eq!(
+            set(yaml, "tags", "\"rust, cli\""),
+            "title: 'Hello' # shown on dev.to\n# draft until launch\npublished: false  # flip me\ntags: rust, cli\n"
+        );
+        // plain values which would read as something else are quoted
+        assert_eq!(set("a: b\n", "a", "\"true\""), "a: \"true\"\n");
+        assert_eq!(set("a: b\n", "a", "\"x: y\""), "a: \"x: y\"\n");
+        assert_eq!(
+            set(yaml, "series", "\"Rust\""),
+            format!("{}series: \"Rust\"\n", yaml)
+        );
+        assert_eq!(set("", "title", "\"a\""), "title: \"a\"");
+    }
+
+    #[test]
+    fn set_replaces_values_spanning_lines() {
+        let yaml = "tags:\n  - rust\n  - cli\n\ntitle: a\n";
+        assert_eq!(
+            set(yaml, "tags", "\"rust\""),
+            "tags: \"rust\"\n\ntitle: a\n"
+        );
+        let yaml = "description: >\n  folded\n  text\ntags:\n- a\n- b\n";
+        assert_eq!(
+            set(yaml, "description", "\"short\""),
+            "description: \"short\"\ntags:\n- a\n- b\n"
+        );
+        assert_eq!(
+            set(yaml, "tags", "\"a, b\""),
+            "description: >\n  folded\n  text\ntags: \"a, b\"\n"
+        );
+    }
+
+    #[test]
+    fn set_keeps_line_endings_and_similar_fields() {
+        assert_eq!(
+            set("title: a\r\npublished: false\r\n", "published", "true"),
+            "title: a\r\npublished: true\r\n"
+        );
+        assert_eq!(
+            set("title: a\r\n", "series", "\"b\""),
+            "title: a\r\nseries: \"b\"\r\n"
+        );
+        assert_eq!(
+            set(
+                "cover_image_alt: x\ncover_image: y\n",
+                "cover_image",
+                "\"z\""
+            ),
+            "cover_image_alt: x\ncover_image: z\n"
+        );
+        assert_eq!(set("url: 'http://a#b'\n", "url", "\"c\""), "url: 'c'\n");
+        assert_eq!(
+            set("title: 'It''s #1' # mine\n", "title", "\"b\""),
+            "title: 'b' # mine\n"
+        );
+        assert_eq!(
+            set("title: \"say \\\"#1\\\"\"\n", "title", "\"b\""),
+            "title: \"b\"\n"
+        );
+    }
+}